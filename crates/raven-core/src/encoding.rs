@@ -0,0 +1,225 @@
+//! Text encoding and line ending detection for files opened in the editor.
+//!
+//! The editor always works with UTF-8 text and `\n` line endings. Files are
+//! decoded into that form on read, and encoded back with their original
+//! encoding, line endings and byte order mark on write.
+
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use serde::{Deserialize, Serialize};
+
+/// Bytes checked for NUL when deciding whether content is binary
+pub const BINARY_CHECK_LEN: usize = 8192;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+}
+
+impl LineEnding {
+    fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+        }
+    }
+}
+
+/// How a file's text is stored on disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextFormat {
+    /// WHATWG encoding name, e.g. "UTF-8", "UTF-16LE", "windows-1252"
+    pub encoding: String,
+    pub line_ending: LineEnding,
+    /// The file starts with a byte order mark
+    pub bom: bool,
+}
+
+impl Default for TextFormat {
+    fn default() -> Self {
+        Self {
+            encoding: UTF_8.name().to_string(),
+            line_ending: LineEnding::Lf,
+            bom: false,
+        }
+    }
+}
+
+/// Decode file contents, returning the text with `\n` line endings and the
+/// format it was stored in.
+pub fn decode(bytes: &[u8]) -> (String, TextFormat) {
+    let (encoding, bom_len) = match Encoding::for_bom(bytes) {
+        Some((encoding, bom_len)) => (encoding, bom_len),
+        None => (detect(bytes), 0),
+    };
+    let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+
+    let crlf = text.matches("\r\n").count();
+    let lf = text.matches('\n').count() - crlf;
+    let line_ending = if crlf > lf {
+        LineEnding::Crlf
+    } else {
+        LineEnding::Lf
+    };
+
+    let format = TextFormat {
+        encoding: encoding.name().to_string(),
+        line_ending,
+        bom: bom_len > 0,
+    };
+    (text.replace("\r\n", "\n"), format)
+}
+
+/// Whether content looks binary rather than text, judging by its first bytes:
+/// text (other than UTF-16 with a byte order mark) doesn't contain NUL.
+pub fn looks_binary(bytes: &[u8]) -> bool {
+    if Encoding::for_bom(bytes).is_some() {
+        return false;
+    }
+    bytes[..bytes.len().min(BINARY_CHECK_LEN)].contains(&0)
+}
+
+/// Guess the encoding of text without a byte order mark.
+fn detect(bytes: &[u8]) -> &'static Encoding {
+    if std::str::from_utf8(bytes).is_ok() {
+        return UTF_8;
+    }
+    let mut detector = chardetng::EncodingDetector::new();
+    detector.feed(bytes, true);
+    detector.guess(None, true)
+}
+
+/// Encode editor text (with `\n` line endings) in the given format.
+pub fn encode(text: &str, format: &TextFormat) -> Result<Vec<u8>, String> {
+    let encoding = Encoding::for_label(format.encoding.as_bytes())
+        .ok_or_else(|| format!("Unknown encoding: {}", format.encoding))?;
+
+    let normalized = text.replace("\r\n", "\n");
+    let text = match format.line_ending {
+        LineEnding::Lf => normalized,
+        LineEnding::Crlf => normalized.replace('\n', LineEnding::Crlf.as_str()),
+    };
+
+    let mut bytes = Vec::with_capacity(text.len() + 3);
+    // encoding_rs only encodes to UTF-8 and legacy encodings, not UTF-16
+    if encoding == UTF_16LE || encoding == UTF_16BE {
+        let little_endian = encoding == UTF_16LE;
+        let units = std::iter::once('\u{feff}')
+            .filter(|_| format.bom)
+            .chain(text.chars())
+            .flat_map(|c| {
+                let mut buf = [0u16; 2];
+                c.encode_utf16(&mut buf).to_vec()
+            });
+        for unit in units {
+            bytes.extend(if little_endian {
+                unit.to_le_bytes()
+            } else {
+                unit.to_be_bytes()
+            });
+        }
+        return Ok(bytes);
+    }
+
+    if format.bom && encoding == UTF_8 {
+        bytes.extend_from_slice(b"\xEF\xBB\xBF");
+    }
+    let (encoded, _, unmappable) = encoding.encode(&text);
+    if unmappable {
+        return Err(format!(
+            "Text contains characters that can't be saved as {}",
+            encoding.name()
+        ));
+    }
+    bytes.extend_from_slice(&encoded);
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(bytes: &[u8]) -> (String, TextFormat) {
+        let (text, format) = decode(bytes);
+        assert_eq!(encode(&text, &format).unwrap(), bytes);
+        (text, format)
+    }
+
+    #[test]
+    fn test_utf8() {
+        let (text, format) = round_trip("héllo\nworld\n".as_bytes());
+        assert_eq!(text, "héllo\nworld\n");
+        assert_eq!(format.encoding, "UTF-8");
+        assert_eq!(format.line_ending, LineEnding::Lf);
+        assert!(!format.bom);
+
+        let (text, format) = round_trip(b"\xEF\xBB\xBFbom\n");
+        assert_eq!(text, "bom\n");
+        assert!(format.bom);
+    }
+
+    #[test]
+    fn test_utf16_with_bom() {
+        let le = b"\xFF\xFEh\0\xE9\0\r\0\n\0";
+        let (text, format) = round_trip(le);
+        assert_eq!(text, "hé\n");
+        assert_eq!(format.encoding, "UTF-16LE");
+        assert_eq!(format.line_ending, LineEnding::Crlf);
+        assert!(format.bom);
+
+        // Astral characters are written as surrogate pairs
+        let be = b"\xFE\xFF\0h\xD8\x3D\xDE\x00\0\n";
+        let (text, format) = round_trip(be);
+        assert_eq!(text, "h\u{1F600}\n");
+        assert_eq!(format.encoding, "UTF-16BE");
+        assert_eq!(format.line_ending, LineEnding::Lf);
+    }
+
+    #[test]
+    fn test_majority_line_ending() {
+        let (text, format) = decode(b"a\r\nb\r\nc\n");
+        assert_eq!(text, "a\nb\nc\n");
+        assert_eq!(format.line_ending, LineEnding::Crlf);
+        // Writing back normalises to the majority ending
+        assert_eq!(encode(&text, &format).unwrap(), b"a\r\nb\r\nc\r\n");
+
+        let (_, format) = decode(b"a\r\nb\nc\n");
+        assert_eq!(format.line_ending, LineEnding::Lf);
+        // A tie, or no line endings at all, is LF
+        assert_eq!(decode(b"a\r\nb\n").1.line_ending, LineEnding::Lf);
+        assert_eq!(decode(b"a").1.line_ending, LineEnding::Lf);
+    }
+
+    #[test]
+    fn test_legacy_encoding_fallback() {
+        // Not valid UTF-8, so the encoding is guessed
+        let (bytes, _, _) = encoding_rs::WINDOWS_1252.encode("Ça été très réussi, déjà.\n");
+        let (text, format) = round_trip(&bytes);
+        assert_eq!(text, "Ça été très réussi, déjà.\n");
+        assert_eq!(format.encoding, "windows-1252");
+    }
+
+    #[test]
+    fn test_encode_errors() {
+        let format = TextFormat {
+            encoding: "windows-1252".to_string(),
+            ..TextFormat::default()
+        };
+        assert!(encode("snowman ☃", &format).is_err());
+        let format = TextFormat {
+            encoding: "no-such-encoding".to_string(),
+            ..TextFormat::default()
+        };
+        assert!(encode("text", &format).is_err());
+    }
+
+    #[test]
+    fn test_looks_binary() {
+        assert!(looks_binary(b"\x7FELF\0\0\0"));
+        assert!(!looks_binary(b"plain text"));
+        // UTF-16 text is full of NULs, but has a byte order mark
+        assert!(!looks_binary(b"\xFF\xFEh\0i\0"));
+    }
+}
@@ -0,0 +1,98 @@
+//! Filesystem helpers shared by file commands.
+
+use ignore::WalkBuilder;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Walker over a project that skips hidden and gitignored files.
+/// Anything that enumerates project files should agree with this.
+pub fn project_walk(root: &Path) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(root);
+    builder
+        .hidden(true) // skip hidden files
+        .git_ignore(true) // respect .gitignore
+        .git_global(true)
+        .git_exclude(true);
+    builder
+}
+
+/// Write to a temporary file next to `target` and rename it into place, so a
+/// crash mid-save leaves either the old or the new contents. Keeps the
+/// original file's permissions.
+pub fn atomic_write(target: &Path, contents: &[u8]) -> io::Result<()> {
+    let dir = match target.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    let name = target
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Path has no file name"))?;
+    let temp_path = dir.join(format!(
+        ".{}.{}.tmp",
+        name.to_string_lossy(),
+        uuid::Uuid::new_v4().simple()
+    ));
+
+    let write = || -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)?;
+        file.write_all(contents)?;
+        if let Ok(metadata) = fs::metadata(target) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.sync_all()?;
+        fs::rename(&temp_path, target)
+    };
+
+    if let Err(e) = write() {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+
+    // Make the rename itself durable
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// Copy a file or directory (recursively). Symlinks are copied as links.
+pub fn copy_recursive(from: &Path, to: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(from)?;
+    if metadata.is_symlink() {
+        std::os::unix::fs::symlink(fs::read_link(from)?, to)
+    } else if metadata.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        fs::set_permissions(to, metadata.permissions())
+    } else {
+        // fs::copy keeps permissions
+        fs::copy(from, to).map(|_| ())
+    }
+}
+
+/// Rename, falling back to copy and delete when moving across filesystems.
+pub fn move_path(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            copy_recursive(from, to)?;
+            remove_recursive(from)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn remove_recursive(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
@@ -0,0 +1,14 @@
+//! Editor logic that doesn't depend on the GUI: file encodings, the path
+//! scope, filesystem change filtering and applying LSP workspace edits.
+//!
+//! The Tauri app wraps these in commands. Keeping them out of the app crate
+//! lets their tests build and run without the webview's system libraries.
+
+pub mod encoding;
+pub mod file;
+pub mod lsp;
+pub mod scope;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+pub mod trash;
+pub mod watcher;
@@ -0,0 +1,1079 @@
+//! Applying LSP workspace edits to files on disk.
+
+use super::protocol::{
+    CreateFile, DeleteFile, DocumentChange, OptionalVersionedTextDocumentIdentifier, Position,
+    RenameFile, TextDocumentEdit, TextEdit, TextEditOrAnnotated, WorkspaceEdit,
+};
+use super::serialized::{SerializedDocumentChange, SerializedTextEdit, SerializedWorkspaceEdit};
+use crate::encoding::{self, TextFormat};
+use crate::file::{atomic_write, move_path};
+use crate::trash::{trash_path, untrash, TrashEntry};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Convert a path to a `file://` URI, percent-encoding everything but
+/// unreserved characters and separators.
+pub fn path_to_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for &byte in path.to_string_lossy().as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri
+}
+
+/// Convert a `file://` URI to a path, decoding percent escapes.
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?;
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 3 <= bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8(decoded).ok().map(PathBuf::from)
+}
+
+/// Text edits from a workspace edit, grouped by file in the order given.
+/// Resource operations (create/rename/delete) are skipped.
+pub fn text_edits_by_path(edit: WorkspaceEdit) -> Vec<(PathBuf, Vec<TextEdit>)> {
+    let mut by_path: Vec<(PathBuf, Vec<TextEdit>)> = Vec::new();
+    let mut push = |uri: &str, edits: Vec<TextEdit>| {
+        let Some(path) = uri_to_path(uri) else {
+            return;
+        };
+        match by_path.iter_mut().find(|(p, _)| *p == path) {
+            Some((_, existing)) => existing.extend(edits),
+            None => by_path.push((path, edits)),
+        }
+    };
+
+    if let Some(changes) = edit.changes {
+        for (uri, edits) in changes {
+            push(&uri, edits);
+        }
+    }
+    for change in edit.document_changes.unwrap_or_default() {
+        if let DocumentChange::Edit(edit) = change {
+            let edits = edit.edits.into_iter().map(plain_edit).collect();
+            push(&edit.text_document.uri, edits);
+        }
+    }
+    by_path
+}
+
+/// A text edit without its annotation.
+fn plain_edit(edit: TextEditOrAnnotated) -> TextEdit {
+    match edit {
+        TextEditOrAnnotated::TextEdit(te) => te,
+        TextEditOrAnnotated::Annotated(ate) => TextEdit {
+            range: ate.range,
+            new_text: ate.new_text,
+        },
+    }
+}
+
+/// Apply text edits to a document. Edits must not overlap; edits starting at
+/// the same position are applied in the order given.
+pub fn apply_text_edits(text: &str, edits: &[TextEdit]) -> Result<String, String> {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+
+    let mut resolved: Vec<(usize, usize, &str)> = edits
+        .iter()
+        .map(|edit| {
+            let start = offset_of(text, &line_starts, edit.range.start);
+            let end = offset_of(text, &line_starts, edit.range.end);
+            (start, end.max(start), edit.new_text.as_str())
+        })
+        .collect();
+    // Stable sort, so equal starts keep their order
+    resolved.sort_by_key(|(start, _, _)| *start);
+
+    for pair in resolved.windows(2) {
+        if pair[1].0 < pair[0].1 {
+            return Err("Overlapping text edits".to_string());
+        }
+    }
+
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+    for (start, end, new_text) in resolved {
+        result.push_str(&text[last..start]);
+        result.push_str(new_text);
+        last = end;
+    }
+    result.push_str(&text[last..]);
+    Ok(result)
+}
+
+/// Whether a position is inside a document: on one of its lines, and no
+/// further along it than its end.
+pub fn position_in_bounds(text: &str, position: Position) -> bool {
+    let Some(line) = text.split('\n').nth(position.line as usize) else {
+        return false;
+    };
+    let length: usize = line.chars().map(char::len_utf16).sum();
+    position.character as usize <= length
+}
+
+/// Byte offset of an LSP position (UTF-16 character offset), clamped to the
+/// end of its line or of the document.
+fn offset_of(text: &str, line_starts: &[usize], position: Position) -> usize {
+    let Some(&line_start) = line_starts.get(position.line as usize) else {
+        return text.len();
+    };
+    let line_end = line_starts
+        .get(position.line as usize + 1)
+        .map_or(text.len(), |next| next - 1);
+    let line = text[line_start..line_end].trim_end_matches('\r');
+
+    let mut utf16 = 0;
+    for (i, c) in line.char_indices() {
+        if utf16 >= position.character as usize {
+            return line_start + i;
+        }
+        utf16 += c.len_utf16();
+    }
+    line_start + line.len()
+}
+
+/// New contents for files on disk, worked out from workspace edits before any
+/// file is written, so a bad edit changes nothing.
+#[derive(Debug, Default)]
+pub struct PendingFileEdits {
+    files: Vec<(PathBuf, String, TextFormat)>,
+}
+
+impl PendingFileEdits {
+    /// Work out a workspace edit's text edits, on top of any already added,
+    /// except to files in `open_paths`: their edits are returned for the
+    /// editor to apply to its buffers instead, keyed by URI.
+    pub fn add(
+        &mut self,
+        edit: WorkspaceEdit,
+        open_paths: &HashSet<PathBuf>,
+    ) -> Result<HashMap<String, Vec<SerializedTextEdit>>, String> {
+        let mut open_edits: HashMap<String, Vec<SerializedTextEdit>> = HashMap::new();
+        for (path, edits) in text_edits_by_path(edit) {
+            if open_paths.contains(&path) {
+                open_edits
+                    .entry(path_to_uri(&path))
+                    .or_default()
+                    .extend(edits.into_iter().map(SerializedTextEdit::from));
+                continue;
+            }
+
+            let index = self.files.iter().position(|(p, _, _)| *p == path);
+            let (text, format) = match index {
+                Some(i) => (self.files[i].1.clone(), self.files[i].2.clone()),
+                None => read_text(&path)?,
+            };
+            let new_text = apply_text_edits(&text, &edits)
+                .map_err(|e| format!("Failed to edit '{}': {}", path.display(), e))?;
+            match index {
+                Some(i) => self.files[i].1 = new_text,
+                None => self.files.push((path, new_text, format)),
+            }
+        }
+        Ok(open_edits)
+    }
+
+    /// Write to files where they are after `from` moved to `to`.
+    pub fn move_paths(&mut self, from: &Path, to: &Path) {
+        for (path, _, _) in &mut self.files {
+            *path = moved(std::mem::take(path), from, to);
+        }
+    }
+
+    /// Write every file, or none: files already written are restored if a
+    /// later write fails.
+    pub fn write(self) -> Result<(), String> {
+        let mut undo = Vec::new();
+        for (path, text, format) in self.files {
+            if let Err(e) = write_text(&path, &text, &format, &mut undo) {
+                roll_back(undo);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Read a file as editor text, with the format to write it back in.
+fn read_text(path: &Path) -> Result<(String, TextFormat), String> {
+    let bytes =
+        fs::read(path).map_err(|e| format!("Failed to read file '{}': {}", path.display(), e))?;
+    Ok(encoding::decode(&bytes))
+}
+
+/// Write editor text to a file in the given format, noting how to undo it.
+fn write_text(
+    path: &Path,
+    text: &str,
+    format: &TextFormat,
+    undo: &mut Vec<Undo>,
+) -> Result<(), String> {
+    let bytes = encoding::encode(text, format)
+        .map_err(|e| format!("Failed to encode '{}': {}", path.display(), e))?;
+    let original = fs::read(path).ok();
+    atomic_write(path, &bytes)
+        .map_err(|e| format!("Failed to write file '{}': {}", path.display(), e))?;
+    undo.push(Undo::Write {
+        path: path.to_path_buf(),
+        original,
+    });
+    Ok(())
+}
+
+/// A change made on disk, and what it takes to undo it.
+#[derive(Debug)]
+enum Undo {
+    /// A file was written over `original`, or created if there was none.
+    Write {
+        path: PathBuf,
+        original: Option<Vec<u8>>,
+    },
+    /// `from` was moved to `to`.
+    Move { from: PathBuf, to: PathBuf },
+    /// A path was moved to the trash.
+    Trash(TrashEntry),
+}
+
+/// Undo changes made on disk, most recent first. Best effort: each step is
+/// tried even if an earlier one fails.
+fn roll_back(undo: Vec<Undo>) {
+    for step in undo.into_iter().rev() {
+        match step {
+            Undo::Write {
+                path,
+                original: Some(original),
+            } => {
+                let _ = atomic_write(&path, &original);
+            }
+            Undo::Write {
+                path,
+                original: None,
+            } => {
+                let _ = fs::remove_file(&path);
+            }
+            Undo::Move { from, to } => {
+                let _ = move_path(&to, &from);
+            }
+            Undo::Trash(entry) => {
+                let _ = untrash(&entry.id, Path::new(&entry.original_path));
+            }
+        }
+    }
+}
+
+/// A file or directory moved by a workspace edit.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MovedPath {
+    pub from: String,
+    pub to: String,
+}
+
+/// What's left for the editor to do after a workspace edit is applied on disk.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedWorkspaceEdit {
+    /// Edits to files open in the editor, as document changes to apply in
+    /// order, with URIs as they are after any moves. Edits to other files are
+    /// already applied on disk.
+    pub buffer_edits: Option<SerializedWorkspaceEdit>,
+    /// Moves in the order made.
+    pub moved: Vec<MovedPath>,
+    /// Paths moved to the trash.
+    pub deleted: Vec<TrashEntry>,
+    /// Open files the edit moved, from where they were before it to where
+    /// they are after it. Their buffers now belong to the new path.
+    pub moved_buffers: Vec<MovedPath>,
+    /// Open files the edit deleted, by where they were before it. Their
+    /// buffers should be closed.
+    pub closed_buffers: Vec<String>,
+}
+
+/// A workspace edit's changes in the order to make them. `documentChanges`
+/// is used when present, as the LSP spec prefers it over `changes`.
+fn ordered_changes(edit: WorkspaceEdit) -> Vec<DocumentChange> {
+    if let Some(document_changes) = edit.document_changes {
+        return document_changes;
+    }
+    let mut changes: Vec<(String, Vec<TextEdit>)> =
+        edit.changes.unwrap_or_default().into_iter().collect();
+    changes.sort_by(|a, b| a.0.cmp(&b.0));
+    changes
+        .into_iter()
+        .map(|(uri, edits)| {
+            DocumentChange::Edit(TextDocumentEdit {
+                text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+                edits: edits
+                    .into_iter()
+                    .map(TextEditOrAnnotated::TextEdit)
+                    .collect(),
+            })
+        })
+        .collect()
+}
+
+fn file_path(uri: &str) -> Result<PathBuf, String> {
+    uri_to_path(uri).ok_or_else(|| format!("Unsupported URI: {}", uri))
+}
+
+/// Every path a workspace edit touches, so they can be checked before any
+/// change is made.
+pub fn workspace_edit_paths(edit: &WorkspaceEdit) -> Result<Vec<PathBuf>, String> {
+    let mut uris: Vec<&str> = Vec::new();
+    if let Some(changes) = &edit.changes {
+        uris.extend(changes.keys().map(String::as_str));
+    }
+    for change in edit.document_changes.iter().flatten() {
+        match change {
+            DocumentChange::Edit(edit) => uris.push(&edit.text_document.uri),
+            DocumentChange::Create(create) => uris.push(&create.uri),
+            DocumentChange::Rename(rename) => uris.extend([&*rename.old_uri, &*rename.new_uri]),
+            DocumentChange::Delete(delete) => uris.push(&delete.uri),
+        }
+    }
+    uris.into_iter().map(file_path).collect()
+}
+
+/// A file operation to make on disk, once every change has been checked.
+#[derive(Debug)]
+enum FileOp {
+    /// Create an empty file, replacing any there.
+    Create(PathBuf),
+    /// Move a path, trashing whatever is at `to` first if `replace`.
+    Move {
+        from: PathBuf,
+        to: PathBuf,
+        replace: bool,
+    },
+    Trash(PathBuf),
+}
+
+/// A workspace edit worked out against the disk as its earlier changes will
+/// have left it, before any change is made.
+#[derive(Debug, Default)]
+struct Plan {
+    /// File operations in order.
+    ops: Vec<FileOp>,
+    /// Files created or edited, where they are now, with their new text.
+    files: Vec<(PathBuf, String, TextFormat)>,
+    /// Open files where they are now, and where they were before the edit.
+    open: HashMap<PathBuf, PathBuf>,
+    /// Edits to open files, by where they are now.
+    buffer_edits: Vec<(PathBuf, Vec<SerializedTextEdit>)>,
+    /// Open files deleted, by where they were before the edit.
+    closed: Vec<String>,
+}
+
+impl Plan {
+    /// Where a path's content is on disk before the edit, or `None` if
+    /// nothing is there by the time the edit's changes so far are made.
+    fn on_disk(&self, path: &Path) -> Option<PathBuf> {
+        let mut path = path.to_path_buf();
+        for op in self.ops.iter().rev() {
+            match op {
+                FileOp::Move { from, to, .. } => {
+                    if path.starts_with(to) {
+                        path = moved(path, to, from);
+                    } else if path.starts_with(from) {
+                        return None;
+                    }
+                }
+                FileOp::Trash(trashed) if path.starts_with(trashed) => return None,
+                _ => {}
+            }
+        }
+        fs::symlink_metadata(&path).is_ok().then_some(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.files.iter().any(|(p, _, _)| p == path) || self.on_disk(path).is_some()
+    }
+
+    fn edit(&mut self, path: PathBuf, edits: Vec<TextEdit>) -> Result<(), String> {
+        if self.open.contains_key(&path) {
+            // Kept apart from earlier edits to the file, which these edits'
+            // positions already account for
+            self.buffer_edits.push((
+                path,
+                edits.into_iter().map(SerializedTextEdit::from).collect(),
+            ));
+            return Ok(());
+        }
+        let index = self.files.iter().position(|(p, _, _)| *p == path);
+        let (text, format) = match index {
+            Some(i) => (self.files[i].1.clone(), self.files[i].2.clone()),
+            None => {
+                let on_disk = self
+                    .on_disk(&path)
+                    .ok_or_else(|| format!("Not found: {}", path.display()))?;
+                read_text(&on_disk)?
+            }
+        };
+        let new_text = apply_text_edits(&text, &edits)
+            .map_err(|e| format!("Failed to edit '{}': {}", path.display(), e))?;
+        match index {
+            Some(i) => self.files[i].1 = new_text,
+            None => self.files.push((path, new_text, format)),
+        }
+        Ok(())
+    }
+
+    /// Create an empty file. An existing file is emptied with `overwrite`,
+    /// left alone with `ignoreIfExists`, and an error otherwise.
+    fn create(&mut self, create: &CreateFile) -> Result<(), String> {
+        let path = file_path(&create.uri)?;
+        let options = create.options.as_ref();
+        let overwrite = options.and_then(|o| o.overwrite).unwrap_or(false);
+        let ignore_if_exists = options.and_then(|o| o.ignore_if_exists).unwrap_or(false);
+        if self.exists(&path) && !overwrite {
+            if ignore_if_exists {
+                return Ok(());
+            }
+            return Err(format!("Already exists: {}", path.display()));
+        }
+        self.files.retain(|(p, _, _)| *p != path);
+        self.files
+            .push((path.clone(), String::new(), TextFormat::default()));
+        self.ops.push(FileOp::Create(path));
+        Ok(())
+    }
+
+    /// Move a file or directory. An existing target is replaced with
+    /// `overwrite`, or the move skipped with `ignoreIfExists`.
+    fn rename(&mut self, rename: &RenameFile) -> Result<(), String> {
+        let (from, to) = (file_path(&rename.old_uri)?, file_path(&rename.new_uri)?);
+        if from == to {
+            return Ok(());
+        }
+        if to.starts_with(&from) {
+            return Err(format!("Cannot move '{}' into itself", from.display()));
+        }
+        if !self.exists(&from) {
+            return Err(format!("Not found: {}", from.display()));
+        }
+        let mut replace = false;
+        if self.exists(&to) {
+            let options = rename.options.as_ref();
+            if !options.and_then(|o| o.overwrite).unwrap_or(false) {
+                if options.and_then(|o| o.ignore_if_exists).unwrap_or(false) {
+                    return Ok(());
+                }
+                return Err(format!("Already exists: {}", to.display()));
+            }
+            self.close_under(&to);
+            replace = true;
+        }
+        self.open = std::mem::take(&mut self.open)
+            .into_iter()
+            .map(|(p, original)| (moved(p, &from, &to), original))
+            .collect();
+        for (path, _) in &mut self.buffer_edits {
+            *path = moved(std::mem::take(path), &from, &to);
+        }
+        for (path, _, _) in &mut self.files {
+            *path = moved(std::mem::take(path), &from, &to);
+        }
+        self.ops.push(FileOp::Move { from, to, replace });
+        Ok(())
+    }
+
+    /// Trash a file or directory. A directory with entries needs `recursive`;
+    /// a missing path is an error unless `ignoreIfNotExists`.
+    fn delete(&mut self, delete: &DeleteFile) -> Result<(), String> {
+        let path = file_path(&delete.uri)?;
+        let options = delete.options.as_ref();
+        let recursive = options.and_then(|o| o.recursive).unwrap_or(false);
+        let ignore_if_not_exists = options
+            .and_then(|o| o.ignore_if_not_exists)
+            .unwrap_or(false);
+        if !self.exists(&path) {
+            if ignore_if_not_exists {
+                return Ok(());
+            }
+            return Err(format!("Failed to delete '{}': not found", path.display()));
+        }
+        if !recursive {
+            let created_entries = self
+                .files
+                .iter()
+                .any(|(p, _, _)| p != &path && p.starts_with(&path));
+            let disk_entries = match self.on_disk(&path) {
+                Some(on_disk) if on_disk.is_dir() => fs::read_dir(&on_disk)
+                    .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?
+                    .next()
+                    .is_some(),
+                _ => false,
+            };
+            if created_entries || disk_entries {
+                return Err(format!("Directory not empty: {}", path.display()));
+            }
+        }
+        self.close_under(&path);
+        self.ops.push(FileOp::Trash(path));
+        Ok(())
+    }
+
+    /// Forget files at or under a deleted path, and pending edits to them,
+    /// noting open files' buffers are to be closed.
+    fn close_under(&mut self, path: &Path) {
+        let closed = &mut self.closed;
+        self.open.retain(|p, original| {
+            if p.starts_with(path) {
+                closed.push(original.to_string_lossy().to_string());
+            }
+            !p.starts_with(path)
+        });
+        self.buffer_edits.retain(|(p, _)| !p.starts_with(path));
+        self.files.retain(|(p, _, _)| !p.starts_with(path));
+    }
+
+    /// Make the planned changes on disk, in order. If one fails, the changes
+    /// already made are undone.
+    fn execute(&self, applied: &mut AppliedWorkspaceEdit) -> Result<(), String> {
+        let mut undo = Vec::new();
+        let result = self.execute_steps(applied, &mut undo);
+        if result.is_err() {
+            roll_back(undo);
+            applied.moved.clear();
+            applied.deleted.clear();
+        }
+        result
+    }
+
+    fn execute_steps(
+        &self,
+        applied: &mut AppliedWorkspaceEdit,
+        undo: &mut Vec<Undo>,
+    ) -> Result<(), String> {
+        for op in &self.ops {
+            match op {
+                FileOp::Create(path) => {
+                    create_parent(path)?;
+                    write_text(path, "", &TextFormat::default(), undo)?;
+                }
+                FileOp::Move { from, to, replace } => {
+                    if *replace {
+                        let entry = trash_path(to)?;
+                        applied.deleted.push(entry.clone());
+                        undo.push(Undo::Trash(entry));
+                    }
+                    create_parent(to)?;
+                    move_path(from, to).map_err(|e| {
+                        format!(
+                            "Failed to move '{}' to '{}': {}",
+                            from.display(),
+                            to.display(),
+                            e
+                        )
+                    })?;
+                    undo.push(Undo::Move {
+                        from: from.clone(),
+                        to: to.clone(),
+                    });
+                    applied.moved.push(MovedPath {
+                        from: from.to_string_lossy().to_string(),
+                        to: to.to_string_lossy().to_string(),
+                    });
+                }
+                FileOp::Trash(path) => {
+                    let entry = trash_path(path)?;
+                    applied.deleted.push(entry.clone());
+                    undo.push(Undo::Trash(entry));
+                }
+            }
+        }
+        for (path, text, format) in &self.files {
+            write_text(path, text, format, undo)?;
+        }
+        Ok(())
+    }
+}
+
+/// Apply a workspace edit, including creates, renames and deletes, to files
+/// on disk. Text edits to files in `open_paths` are returned for the editor
+/// to apply to its buffers instead. Every change is checked before any is
+/// made, and if making one fails the others are undone, so a bad edit
+/// changes nothing; deleted paths go to the trash.
+pub fn apply_workspace_edit(
+    edit: WorkspaceEdit,
+    open_paths: &HashSet<PathBuf>,
+) -> Result<AppliedWorkspaceEdit, String> {
+    let mut plan = Plan {
+        open: open_paths.iter().map(|p| (p.clone(), p.clone())).collect(),
+        ..Plan::default()
+    };
+    for change in ordered_changes(edit) {
+        match change {
+            DocumentChange::Edit(edit) => {
+                let path = file_path(&edit.text_document.uri)?;
+                plan.edit(path, edit.edits.into_iter().map(plain_edit).collect())?;
+            }
+            DocumentChange::Create(create) => plan.create(&create)?,
+            DocumentChange::Rename(rename) => plan.rename(&rename)?,
+            DocumentChange::Delete(delete) => plan.delete(&delete)?,
+        }
+    }
+
+    let mut applied = AppliedWorkspaceEdit::default();
+    plan.execute(&mut applied)?;
+
+    applied.moved_buffers = plan
+        .open
+        .into_iter()
+        .filter(|(path, original)| path != original)
+        .map(|(path, original)| MovedPath {
+            from: original.to_string_lossy().to_string(),
+            to: path.to_string_lossy().to_string(),
+        })
+        .collect();
+    applied.moved_buffers.sort_by(|a, b| a.from.cmp(&b.from));
+    applied.closed_buffers = plan.closed;
+    applied.closed_buffers.sort();
+
+    if !plan.buffer_edits.is_empty() {
+        applied.buffer_edits = Some(SerializedWorkspaceEdit {
+            changes: None,
+            document_changes: Some(
+                plan.buffer_edits
+                    .into_iter()
+                    .map(|(path, edits)| SerializedDocumentChange::Edit {
+                        uri: path_to_uri(&path),
+                        edits,
+                    })
+                    .collect(),
+            ),
+        });
+    }
+    Ok(applied)
+}
+
+fn create_parent(path: &Path) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory '{}': {}", parent.display(), e))?;
+    }
+    Ok(())
+}
+
+/// `path` updated for `from` having moved to `to`.
+fn moved(path: PathBuf, from: &Path, to: &Path) -> PathBuf {
+    match path.strip_prefix(from) {
+        Ok(rest) if rest.as_os_str().is_empty() => to.to_path_buf(),
+        Ok(rest) => to.join(rest),
+        Err(_) => path,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn edit(line: u32, start: u32, end: u32, new_text: &str) -> TextEdit {
+        TextEdit {
+            range: super::super::protocol::Range {
+                start: Position {
+                    line,
+                    character: start,
+                },
+                end: Position {
+                    line,
+                    character: end,
+                },
+            },
+            new_text: new_text.to_string(),
+        }
+    }
+
+    fn changes(path: &Path, edits: Vec<TextEdit>) -> WorkspaceEdit {
+        WorkspaceEdit {
+            changes: Some(HashMap::from([(path_to_uri(path), edits)])),
+            document_changes: None,
+        }
+    }
+
+    #[test]
+    fn test_uri_round_trip() {
+        let path = Path::new("/home/me/my project/naïve%.rs");
+        let uri = path_to_uri(path);
+        assert_eq!(uri, "file:///home/me/my%20project/na%C3%AFve%25.rs");
+        assert_eq!(uri_to_path(&uri).unwrap(), path);
+        // An escape at the very end is decoded too
+        assert_eq!(
+            uri_to_path("file:///tmp/a%20").unwrap(),
+            Path::new("/tmp/a ")
+        );
+        // Invalid escapes are kept as they are
+        assert_eq!(
+            uri_to_path("file:///tmp/100%/%zz%2").unwrap(),
+            Path::new("/tmp/100%/%zz%2")
+        );
+        assert_eq!(uri_to_path("untitled:Untitled-1"), None);
+    }
+
+    fn position(line: u32, character: u32) -> Position {
+        Position { line, character }
+    }
+
+    #[test]
+    fn test_position_in_bounds() {
+        let text = "ab\r\nhé😀\n";
+        assert!(position_in_bounds(text, position(0, 0)));
+        assert!(position_in_bounds(text, position(0, 2)));
+        // Line length counts UTF-16 units, with the emoji taking two
+        assert!(position_in_bounds(text, position(1, 4)));
+        assert!(!position_in_bounds(text, position(1, 5)));
+        // The empty line after the final newline is in the document
+        assert!(position_in_bounds(text, position(2, 0)));
+        assert!(!position_in_bounds(text, position(2, 1)));
+        assert!(!position_in_bounds(text, position(3, 0)));
+        assert!(position_in_bounds("", position(0, 0)));
+    }
+
+    #[test]
+    fn test_apply_text_edits() {
+        let text = "fn main() {\n    old();\n}\n";
+        // Given out of order, applied by position
+        let edits = [edit(1, 4, 7, "new"), edit(0, 3, 7, "start")];
+        assert_eq!(
+            apply_text_edits(text, &edits).unwrap(),
+            "fn start() {\n    new();\n}\n"
+        );
+        // Edits at the same position are applied in the order given
+        let edits = [edit(0, 0, 0, "a"), edit(0, 0, 0, "b")];
+        assert_eq!(apply_text_edits("x", &edits).unwrap(), "abx");
+        let edits = [edit(0, 0, 2, "x"), edit(0, 1, 3, "y")];
+        assert!(apply_text_edits("abcd", &edits).is_err());
+    }
+
+    #[test]
+    fn test_apply_text_edits_positions() {
+        // UTF-16 positions, and a CRLF isn't part of its line
+        let text = "é😀x\r\nend";
+        assert_eq!(
+            apply_text_edits(text, &[edit(0, 3, 4, "y")]).unwrap(),
+            "é😀y\r\nend"
+        );
+        assert_eq!(
+            apply_text_edits(text, &[edit(0, 4, 99, "!")]).unwrap(),
+            "é😀x!\r\nend"
+        );
+        // Positions past the end of the document clamp to it
+        assert_eq!(
+            apply_text_edits(text, &[edit(9, 0, 0, "\n")]).unwrap(),
+            "é😀x\r\nend\n"
+        );
+        // Across lines
+        let multi_line = TextEdit {
+            range: super::super::protocol::Range {
+                start: position(0, 1),
+                end: position(1, 1),
+            },
+            new_text: String::new(),
+        };
+        assert_eq!(apply_text_edits(text, &[multi_line]).unwrap(), "énd");
+    }
+
+    fn workspace_edit(value: serde_json::Value) -> WorkspaceEdit {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn range_json(line: u32, start: u32, end: u32) -> serde_json::Value {
+        serde_json::json!({
+            "start": {"line": line, "character": start},
+            "end": {"line": line, "character": end}
+        })
+    }
+
+    #[test]
+    fn test_apply_workspace_edit_on_disk() {
+        let dir = TempDir::new();
+        let a = dir.write("a.rs", "mod old;\n");
+        let b = dir.path().join("new/b.rs");
+        let edit = workspace_edit(serde_json::json!({"documentChanges": [
+            {"textDocument": {"uri": path_to_uri(&a), "version": null},
+             "edits": [{"range": range_json(0, 4, 7), "newText": "b"}]},
+            {"kind": "create", "uri": path_to_uri(&b)},
+            {"textDocument": {"uri": path_to_uri(&b), "version": null},
+             "edits": [{"range": range_json(0, 0, 0), "newText": "fn b() {}\n"}]}
+        ]}));
+
+        let applied = apply_workspace_edit(edit, &HashSet::new()).unwrap();
+        assert!(applied.buffer_edits.is_none());
+        assert_eq!(fs::read_to_string(&a).unwrap(), "mod b;\n");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "fn b() {}\n");
+
+        // Creating an existing file fails unless told what to do
+        let create = |options: serde_json::Value| {
+            workspace_edit(serde_json::json!({"documentChanges": [
+                {"kind": "create", "uri": path_to_uri(&a), "options": options}
+            ]}))
+        };
+        assert!(apply_workspace_edit(create(serde_json::json!({})), &HashSet::new()).is_err());
+        let ignore = create(serde_json::json!({"ignoreIfExists": true}));
+        apply_workspace_edit(ignore, &HashSet::new()).unwrap();
+        assert_eq!(fs::read_to_string(&a).unwrap(), "mod b;\n");
+    }
+
+    #[test]
+    fn test_apply_workspace_edit_moves_open_files() {
+        let dir = TempDir::new();
+        let old = dir.write("src/old.rs", "fn old() {}\n");
+        let new = dir.path().join("src/new.rs");
+        let open_paths = HashSet::from([old.clone()]);
+        let edit = workspace_edit(serde_json::json!({"documentChanges": [
+            {"textDocument": {"uri": path_to_uri(&old), "version": 1},
+             "edits": [{"range": range_json(0, 3, 6), "newText": "new"}]},
+            {"kind": "rename", "oldUri": path_to_uri(&old), "newUri": path_to_uri(&new)},
+            {"textDocument": {"uri": path_to_uri(&new), "version": 2},
+             "edits": [{"range": range_json(0, 0, 0), "newText": "pub "}]}
+        ]}));
+
+        let applied = apply_workspace_edit(edit, &open_paths).unwrap();
+        // The open file is moved on disk, but its edits are left to the editor
+        assert!(!old.exists());
+        assert_eq!(fs::read_to_string(&new).unwrap(), "fn old() {}\n");
+        assert_eq!(applied.moved.len(), 1);
+        assert_eq!(applied.moved[0].to, new.to_string_lossy());
+        assert_eq!(applied.moved_buffers.len(), 1);
+        assert_eq!(applied.moved_buffers[0].from, old.to_string_lossy());
+        assert_eq!(applied.moved_buffers[0].to, new.to_string_lossy());
+
+        // Kept as separate changes, in order, under the new path
+        let changes = applied.buffer_edits.unwrap().document_changes.unwrap();
+        let uris: Vec<_> = changes
+            .iter()
+            .map(|change| match change {
+                SerializedDocumentChange::Edit { uri, .. } => uri.clone(),
+                other => panic!("unexpected change {:?}", other),
+            })
+            .collect();
+        let new_uri = path_to_uri(&new);
+        assert_eq!(uris, vec![new_uri.clone(), new_uri]);
+    }
+
+    #[test]
+    fn test_close_under() {
+        // `a.rs` was moved over `b.rs` earlier in the edit
+        let mut plan = Plan {
+            open: HashMap::from([
+                (PathBuf::from("/p/src/b.rs"), PathBuf::from("/p/src/a.rs")),
+                (PathBuf::from("/p/lib.rs"), PathBuf::from("/p/lib.rs")),
+            ]),
+            buffer_edits: vec![
+                (PathBuf::from("/p/src/b.rs"), Vec::new()),
+                (PathBuf::from("/p/lib.rs"), Vec::new()),
+            ],
+            files: vec![(
+                PathBuf::from("/p/src/c.rs"),
+                String::new(),
+                TextFormat::default(),
+            )],
+            ..Plan::default()
+        };
+
+        plan.close_under(Path::new("/p/src"));
+        // Closed by the path the editor knows it by
+        assert_eq!(plan.closed, vec!["/p/src/a.rs".to_string()]);
+        assert_eq!(plan.open.len(), 1);
+        assert_eq!(plan.buffer_edits.len(), 1);
+        assert_eq!(plan.buffer_edits[0].0, PathBuf::from("/p/lib.rs"));
+        assert!(plan.files.is_empty());
+    }
+
+    #[test]
+    fn test_apply_workspace_edit_follows_moves() {
+        let dir = TempDir::new();
+        let a = dir.write("src/a.rs", "mod b;\n");
+        let lib = dir.path().join("lib");
+        let edit = workspace_edit(serde_json::json!({"documentChanges": [
+            {"kind": "rename", "oldUri": path_to_uri(&a), "newUri": path_to_uri(&a)},
+            {"kind": "rename",
+             "oldUri": path_to_uri(&dir.path().join("src")),
+             "newUri": path_to_uri(&lib)},
+            {"textDocument": {"uri": path_to_uri(&lib.join("a.rs")), "version": null},
+             "edits": [{"range": range_json(0, 4, 5), "newText": "c"}]}
+        ]}));
+
+        // Renaming a file to itself does nothing, and edits after a move
+        // are made to the moved file
+        let applied = apply_workspace_edit(edit, &HashSet::new()).unwrap();
+        assert_eq!(applied.moved.len(), 1);
+        assert!(!a.exists());
+        assert_eq!(fs::read_to_string(lib.join("a.rs")).unwrap(), "mod c;\n");
+    }
+
+    #[test]
+    fn test_apply_workspace_edit_changes_nothing_on_failure() {
+        let dir = TempDir::new();
+        let a = dir.write("a.rs", "mod old;\n");
+        let b = dir.write("b.rs", "fn b() {}\n");
+        let c = dir.path().join("c.rs");
+        let edit = workspace_edit(serde_json::json!({"documentChanges": [
+            {"textDocument": {"uri": path_to_uri(&a), "version": null},
+             "edits": [{"range": range_json(0, 4, 7), "newText": "new"}]},
+            {"kind": "rename", "oldUri": path_to_uri(&b), "newUri": path_to_uri(&c)},
+            {"textDocument": {"uri": path_to_uri(&c), "version": null},
+             "edits": [{"range": range_json(0, 0, 4), "newText": "x"},
+                       {"range": range_json(0, 2, 6), "newText": "y"}]}
+        ]}));
+
+        assert!(apply_workspace_edit(edit, &HashSet::new()).is_err());
+        assert_eq!(fs::read_to_string(&a).unwrap(), "mod old;\n");
+        assert_eq!(fs::read_to_string(&b).unwrap(), "fn b() {}\n");
+        assert!(!c.exists());
+    }
+
+    #[test]
+    fn test_roll_back() {
+        let dir = TempDir::new();
+        let a = dir.write("a.rs", "old");
+        let b = dir.path().join("b.rs");
+        let c = dir.path().join("c.rs");
+        let mut undo = Vec::new();
+        move_path(&a, &b).unwrap();
+        undo.push(Undo::Move {
+            from: a.clone(),
+            to: b.clone(),
+        });
+        write_text(&b, "new", &TextFormat::default(), &mut undo).unwrap();
+        write_text(&c, "created", &TextFormat::default(), &mut undo).unwrap();
+
+        roll_back(undo);
+        assert_eq!(fs::read_to_string(&a).unwrap(), "old");
+        assert!(!b.exists());
+        assert!(!c.exists());
+    }
+
+    #[test]
+    fn test_apply_workspace_edit_keeps_format() {
+        let dir = TempDir::new();
+        let crlf = dir.write("crlf.rs", "\u{feff}fn a() {}\r\nfn b() {}\r\n");
+        let latin1 = dir.path().join("latin1.txt");
+        // "café" in windows-1252
+        fs::write(&latin1, b"caf\xe9 old\n").unwrap();
+        let edit = workspace_edit(serde_json::json!({"documentChanges": [
+            {"textDocument": {"uri": path_to_uri(&crlf), "version": null},
+             "edits": [{"range": range_json(1, 3, 4), "newText": "c"},
+                       {"range": range_json(1, 9, 9), "newText": "\nfn d() {}"}]},
+            {"textDocument": {"uri": path_to_uri(&latin1), "version": null},
+             "edits": [{"range": range_json(0, 5, 8), "newText": "new"}]}
+        ]}));
+
+        // The BOM doesn't shift positions, and line endings and encoding are kept
+        apply_workspace_edit(edit, &HashSet::new()).unwrap();
+        assert_eq!(
+            fs::read_to_string(&crlf).unwrap(),
+            "\u{feff}fn a() {}\r\nfn c() {}\r\nfn d() {}\r\n"
+        );
+        assert_eq!(fs::read(&latin1).unwrap(), b"caf\xe9 new\n");
+    }
+
+    #[test]
+    fn test_apply_workspace_edit_errors() {
+        let dir = TempDir::new();
+        let file = dir.write("src/lib.rs", "");
+        let src = dir.path().join("src");
+        let apply = |change: serde_json::Value| {
+            apply_workspace_edit(
+                workspace_edit(serde_json::json!({ "documentChanges": [change] })),
+                &HashSet::new(),
+            )
+        };
+
+        let into_itself = serde_json::json!({
+            "kind": "rename",
+            "oldUri": path_to_uri(&src),
+            "newUri": path_to_uri(&src.join("inner"))
+        });
+        assert!(apply(into_itself).is_err());
+        let onto_existing = serde_json::json!({
+            "kind": "rename",
+            "oldUri": path_to_uri(&src),
+            "newUri": path_to_uri(dir.path())
+        });
+        assert!(apply(onto_existing).is_err());
+        let not_recursive = serde_json::json!({"kind": "delete", "uri": path_to_uri(&src)});
+        assert!(apply(not_recursive).is_err());
+        let missing = serde_json::json!({
+            "kind": "delete",
+            "uri": path_to_uri(&dir.path().join("missing")),
+            "options": {"ignoreIfNotExists": true}
+        });
+        assert!(apply(missing).unwrap().deleted.is_empty());
+        assert!(file.exists());
+    }
+
+    #[test]
+    fn test_pending_file_edits() {
+        let dir = TempDir::new();
+        let closed = dir.write("src/a.rs", "use old;\n");
+        let open = dir.write("src/b.rs", "use old;\n");
+        let open_paths = HashSet::from([open.clone()]);
+
+        let mut pending = PendingFileEdits::default();
+        let open_edits = pending
+            .add(changes(&closed, vec![edit(0, 4, 7, "new")]), &open_paths)
+            .unwrap();
+        assert!(open_edits.is_empty());
+        // A second edit to the same file applies on top of the first
+        pending
+            .add(changes(&closed, vec![edit(0, 0, 0, "pub ")]), &open_paths)
+            .unwrap();
+        let open_edits = pending
+            .add(changes(&open, vec![edit(0, 4, 7, "new")]), &open_paths)
+            .unwrap();
+        assert_eq!(open_edits.len(), 1);
+        assert_eq!(pending.files.len(), 1);
+        assert_eq!(pending.files[0].0, closed);
+        // Nothing is written until asked
+        assert_eq!(fs::read_to_string(&closed).unwrap(), "use old;\n");
+
+        let moved_dir = dir.path().join("lib");
+        fs::rename(dir.path().join("src"), &moved_dir).unwrap();
+        pending.move_paths(&dir.path().join("src"), &moved_dir);
+        pending.write().unwrap();
+        assert_eq!(
+            fs::read_to_string(moved_dir.join("a.rs")).unwrap(),
+            "pub use new;\n"
+        );
+        assert_eq!(
+            fs::read_to_string(moved_dir.join("b.rs")).unwrap(),
+            "use old;\n"
+        );
+    }
+
+    #[test]
+    fn test_pending_file_edits_bad_edit() {
+        let dir = TempDir::new();
+        let path = dir.write("a.rs", "fn a() {}\n");
+        let mut pending = PendingFileEdits::default();
+        assert!(pending
+            .add(
+                changes(&path, vec![edit(0, 0, 4, "x"), edit(0, 2, 6, "y")]),
+                &HashSet::new()
+            )
+            .is_err());
+    }
+}
@@ -0,0 +1,14 @@
+//! Language Server Protocol types, and applying workspace edits to files on
+//! disk.
+
+pub mod edit;
+pub mod protocol;
+mod serialized;
+
+pub use edit::{
+    path_to_uri, uri_to_path, workspace_edit_paths, AppliedWorkspaceEdit, PendingFileEdits,
+};
+pub use serialized::{
+    SerializedDocumentChange, SerializedPosition, SerializedRange, SerializedTextEdit,
+    SerializedWorkspaceEdit,
+};
@@ -0,0 +1,1917 @@
+//! LSP protocol types.
+//!
+//! These types mirror the Language Server Protocol specification.
+//! We define only what we need, not the full spec.
+
+// Allow unused constants - these are LSP protocol values that may be used in the future
+#![allow(dead_code)]
+
+use serde::{Deserialize, Serialize};
+
+/// Position in a text document (0-indexed line and character).
+/// Character offset is in UTF-16 code units per LSP spec.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position {
+    pub line: u32,
+    pub character: u32,
+}
+
+/// A range in a text document.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// A location in a document (URI + range).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Location {
+    pub uri: String,
+    pub range: Range,
+}
+
+/// Diagnostic severity levels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DiagnosticSeverity(pub u8);
+
+impl DiagnosticSeverity {
+    pub const ERROR: Self = Self(1);
+    pub const WARNING: Self = Self(2);
+    pub const INFORMATION: Self = Self(3);
+    pub const HINT: Self = Self(4);
+}
+
+/// A diagnostic (error, warning, etc.) in a document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    pub range: Range,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<DiagnosticSeverity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<DiagnosticCode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    pub message: String,
+}
+
+/// Diagnostic code can be a number or string.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DiagnosticCode {
+    Number(i32),
+    String(String),
+}
+
+/// Text document identifier.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextDocumentIdentifier {
+    pub uri: String,
+}
+
+/// Versioned text document identifier.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionedTextDocumentIdentifier {
+    pub uri: String,
+    pub version: i32,
+}
+
+/// Text document item (for didOpen).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextDocumentItem {
+    pub uri: String,
+    pub language_id: String,
+    pub version: i32,
+    pub text: String,
+}
+
+/// Text document position params (common for many requests).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextDocumentPositionParams {
+    pub text_document: TextDocumentIdentifier,
+    pub position: Position,
+}
+
+/// Markup content (for hover, etc.).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarkupContent {
+    pub kind: MarkupKind,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarkupKind {
+    PlainText,
+    Markdown,
+}
+
+/// Hover result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Hover {
+    pub contents: HoverContents,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<Range>,
+}
+
+/// Hover contents can be various types.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum HoverContents {
+    Markup(MarkupContent),
+    String(String),
+    Array(Vec<MarkedString>),
+    MarkedString(MarkedString),
+}
+
+/// Marked string (deprecated but still used).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MarkedString {
+    String(String),
+    LanguageString { language: String, value: String },
+}
+
+/// Completion item kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CompletionItemKind(pub u8);
+
+impl CompletionItemKind {
+    pub const TEXT: Self = Self(1);
+    pub const METHOD: Self = Self(2);
+    pub const FUNCTION: Self = Self(3);
+    pub const CONSTRUCTOR: Self = Self(4);
+    pub const FIELD: Self = Self(5);
+    pub const VARIABLE: Self = Self(6);
+    pub const CLASS: Self = Self(7);
+    pub const INTERFACE: Self = Self(8);
+    pub const MODULE: Self = Self(9);
+    pub const PROPERTY: Self = Self(10);
+    pub const UNIT: Self = Self(11);
+    pub const VALUE: Self = Self(12);
+    pub const ENUM: Self = Self(13);
+    pub const KEYWORD: Self = Self(14);
+    pub const SNIPPET: Self = Self(15);
+    pub const COLOR: Self = Self(16);
+    pub const FILE: Self = Self(17);
+    pub const REFERENCE: Self = Self(18);
+    pub const FOLDER: Self = Self(19);
+    pub const ENUM_MEMBER: Self = Self(20);
+    pub const CONSTANT: Self = Self(21);
+    pub const STRUCT: Self = Self(22);
+    pub const EVENT: Self = Self(23);
+    pub const OPERATOR: Self = Self(24);
+    pub const TYPE_PARAMETER: Self = Self(25);
+}
+
+/// Completion item.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionItem {
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<CompletionItemKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<Documentation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insert_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insert_text_format: Option<InsertTextFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_edit: Option<TextEdit>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter_text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort_text: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Documentation {
+    String(String),
+    Markup(MarkupContent),
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct InsertTextFormat(pub u8);
+
+impl InsertTextFormat {
+    pub const PLAIN_TEXT: Self = Self(1);
+    pub const SNIPPET: Self = Self(2);
+}
+
+/// Text edit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextEdit {
+    pub range: Range,
+    pub new_text: String,
+}
+
+/// Completion list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionList {
+    pub is_incomplete: bool,
+    pub items: Vec<CompletionItem>,
+}
+
+/// Completion response can be list or array.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CompletionResponse {
+    List(CompletionList),
+    Array(Vec<CompletionItem>),
+}
+
+impl CompletionResponse {
+    pub fn into_items(self) -> Vec<CompletionItem> {
+        match self {
+            Self::List(list) => list.items,
+            Self::Array(items) => items,
+        }
+    }
+}
+
+/// Definition response can be single location, array, or location links.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DefinitionResponse {
+    Single(Location),
+    Array(Vec<Location>),
+    Links(Vec<LocationLink>),
+}
+
+impl DefinitionResponse {
+    pub fn into_locations(self) -> Vec<Location> {
+        match self {
+            Self::Single(loc) => vec![loc],
+            Self::Array(locs) => locs,
+            Self::Links(links) => links
+                .into_iter()
+                .map(|link| Location {
+                    uri: link.target_uri,
+                    range: link.target_selection_range,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Location link (richer than Location).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocationLink {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin_selection_range: Option<Range>,
+    pub target_uri: String,
+    pub target_range: Range,
+    pub target_selection_range: Range,
+}
+
+// === Initialize Request/Response ===
+
+/// Client capabilities we send during initialize.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientCapabilities {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<WorkspaceClientCapabilities>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_document: Option<TextDocumentClientCapabilities>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceClientCapabilities {
+    /// The client answers workspace/configuration requests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub configuration: Option<bool>,
+    /// The client answers workspace/applyEdit requests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub apply_edit: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub did_change_watched_files: Option<DidChangeWatchedFilesClientCapabilities>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_operations: Option<FileOperationClientCapabilities>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace_edit: Option<WorkspaceEditClientCapabilities>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inlay_hint: Option<InlayHintWorkspaceClientCapabilities>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub semantic_tokens: Option<SemanticTokensWorkspaceClientCapabilities>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlayHintWorkspaceClientCapabilities {
+    /// The client handles workspace/inlayHint/refresh requests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_support: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensWorkspaceClientCapabilities {
+    /// The client handles workspace/semanticTokens/refresh requests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_support: Option<bool>,
+}
+
+/// The kinds of workspace edit the client can apply.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceEditClientCapabilities {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document_changes: Option<bool>,
+    /// "create", "rename" and/or "delete"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_operations: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidChangeWatchedFilesClientCapabilities {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dynamic_registration: Option<bool>,
+}
+
+/// File operations the client sends will/did notifications for.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileOperationClientCapabilities {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dynamic_registration: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub will_rename: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub did_rename: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextDocumentClientCapabilities {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub synchronization: Option<TextDocumentSyncClientCapabilities>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completion: Option<CompletionClientCapabilities>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hover: Option<HoverClientCapabilities>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub definition: Option<DefinitionClientCapabilities>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub references: Option<ReferencesClientCapabilities>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publish_diagnostics: Option<PublishDiagnosticsClientCapabilities>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_action: Option<CodeActionClientCapabilities>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rename: Option<RenameClientCapabilities>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document_symbol: Option<DocumentSymbolClientCapabilities>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature_help: Option<SignatureHelpClientCapabilities>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inlay_hint: Option<InlayHintClientCapabilities>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub semantic_tokens: Option<SemanticTokensClientCapabilities>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextDocumentSyncClientCapabilities {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub will_save: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub will_save_wait_until: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub did_save: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionClientCapabilities {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completion_item: Option<CompletionItemClientCapabilities>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionItemClientCapabilities {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet_support: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub documentation_format: Option<Vec<MarkupKind>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HoverClientCapabilities {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_format: Option<Vec<MarkupKind>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DefinitionClientCapabilities {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_support: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReferencesClientCapabilities {}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PublishDiagnosticsClientCapabilities {}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeActionClientCapabilities {
+    /// Whether code action supports dynamic registration.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dynamic_registration: Option<bool>,
+    /// The client supports code action literals.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_action_literal_support: Option<CodeActionLiteralSupport>,
+    /// Whether the client supports the `isPreferred` property.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_preferred_support: Option<bool>,
+    /// Whether the client supports the `disabled` property.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disabled_support: Option<bool>,
+    /// Whether the client supports the `data` property for lazy resolution.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_support: Option<bool>,
+    /// Whether the client supports resolve for additional properties.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolve_support: Option<CodeActionResolveSupport>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureHelpClientCapabilities {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature_information: Option<SignatureInformationClientCapabilities>,
+    /// The client sends a `SignatureHelpContext` with requests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_support: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureInformationClientCapabilities {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub documentation_format: Option<Vec<MarkupKind>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameter_information: Option<ParameterInformationClientCapabilities>,
+    /// The client reads `activeParameter` on each signature.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_parameter_support: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParameterInformationClientCapabilities {
+    /// The client takes parameter labels as offsets into the signature label.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label_offset_support: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlayHintClientCapabilities {
+    /// Properties the server may leave out until inlayHint/resolve.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolve_support: Option<InlayHintResolveSupport>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InlayHintResolveSupport {
+    pub properties: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensClientCapabilities {
+    pub requests: SemanticTokensClientRequests,
+    pub token_types: Vec<String>,
+    pub token_modifiers: Vec<String>,
+    /// Only "relative" is defined
+    pub formats: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overlapping_token_support: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multiline_token_support: Option<bool>,
+    /// Whether tokens are combined with the client's own highlighting, so
+    /// the server can leave out tokens the client already colors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub augments_syntax_tokens: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensClientRequests {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub full: Option<SemanticTokensFullCapability>,
+}
+
+/// Support for whole-document semantic tokens - either a boolean or
+/// whether deltas are supported too.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SemanticTokensFullCapability {
+    Simple(bool),
+    Options {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        delta: Option<bool>,
+    },
+}
+
+impl SemanticTokensFullCapability {
+    pub fn is_supported(&self) -> bool {
+        !matches!(self, Self::Simple(false))
+    }
+
+    pub fn delta_supported(&self) -> bool {
+        matches!(self, Self::Options { delta: Some(true) })
+    }
+}
+
+/// Standard semantic token types, which servers may add to.
+pub const SEMANTIC_TOKEN_TYPES: &[&str] = &[
+    "namespace",
+    "type",
+    "class",
+    "enum",
+    "interface",
+    "struct",
+    "typeParameter",
+    "parameter",
+    "variable",
+    "property",
+    "enumMember",
+    "event",
+    "function",
+    "method",
+    "macro",
+    "keyword",
+    "modifier",
+    "comment",
+    "string",
+    "number",
+    "regexp",
+    "operator",
+    "decorator",
+];
+
+/// Standard semantic token modifiers, which servers may add to.
+pub const SEMANTIC_TOKEN_MODIFIERS: &[&str] = &[
+    "declaration",
+    "definition",
+    "readonly",
+    "static",
+    "deprecated",
+    "abstract",
+    "async",
+    "modification",
+    "documentation",
+    "defaultLibrary",
+];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentSymbolClientCapabilities {
+    /// The client takes a tree of `DocumentSymbol`s rather than a flat list.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hierarchical_document_symbol_support: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameClientCapabilities {
+    /// The client sends textDocument/prepareRename before renaming.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prepare_support: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeActionLiteralSupport {
+    /// The code action kind values the client supports.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_action_kind: Option<CodeActionKindValueSet>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeActionKindValueSet {
+    /// The code action kind values the client supports.
+    pub value_set: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeActionResolveSupport {
+    /// The properties that the client can resolve lazily.
+    pub properties: Vec<String>,
+}
+
+/// Initialize request params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitializeParams {
+    pub process_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_info: Option<ClientInfo>,
+    pub root_uri: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initialization_options: Option<serde_json::Value>,
+    pub capabilities: ClientCapabilities,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientInfo {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+/// Initialize response result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InitializeResult {
+    pub capabilities: ServerCapabilities,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_info: Option<ServerInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerInfo {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+/// Server capabilities (we only parse what we care about).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerCapabilities {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_document_sync: Option<TextDocumentSyncCapability>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completion_provider: Option<CompletionOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature_help_provider: Option<SignatureHelpOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hover_provider: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub definition_provider: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub references_provider: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rename_provider: Option<RenameProviderCapability>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document_symbol_provider: Option<BoolOrOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace_symbol_provider: Option<BoolOrOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document_formatting_provider: Option<BoolOrOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document_range_formatting_provider: Option<BoolOrOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inlay_hint_provider: Option<InlayHintProviderCapability>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub semantic_tokens_provider: Option<SemanticTokensOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub call_hierarchy_provider: Option<BoolOrOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_hierarchy_provider: Option<BoolOrOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<WorkspaceServerCapabilities>,
+}
+
+/// A capability given as a boolean or as options we don't read.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BoolOrOptions {
+    Bool(bool),
+    Options(serde_json::Value),
+}
+
+impl BoolOrOptions {
+    pub fn is_supported(&self) -> bool {
+        !matches!(self, Self::Bool(false))
+    }
+}
+
+/// Rename support - either a boolean or options.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RenameProviderCapability {
+    Simple(bool),
+    Options(RenameOptions),
+}
+
+impl RenameProviderCapability {
+    pub fn is_supported(&self) -> bool {
+        !matches!(self, Self::Simple(false))
+    }
+
+    /// Whether the server answers textDocument/prepareRename.
+    pub fn prepare_supported(&self) -> bool {
+        matches!(
+            self,
+            Self::Options(RenameOptions {
+                prepare_provider: Some(true)
+            })
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prepare_provider: Option<bool>,
+}
+
+/// Inlay hint support - either a boolean or options.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum InlayHintProviderCapability {
+    Simple(bool),
+    Options(InlayHintOptions),
+}
+
+impl InlayHintProviderCapability {
+    pub fn is_supported(&self) -> bool {
+        !matches!(self, Self::Simple(false))
+    }
+
+    /// Whether the server answers inlayHint/resolve.
+    pub fn resolve_supported(&self) -> bool {
+        matches!(
+            self,
+            Self::Options(InlayHintOptions {
+                resolve_provider: Some(true)
+            })
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlayHintOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolve_provider: Option<bool>,
+}
+
+/// The server's semantic tokens support. Registration options have the same
+/// fields, so this reads either.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensOptions {
+    pub legend: SemanticTokensLegend,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<BoolOrOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub full: Option<SemanticTokensFullCapability>,
+}
+
+/// Names for the token type indexes and modifier bits in token data.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensLegend {
+    pub token_types: Vec<String>,
+    pub token_modifiers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceServerCapabilities {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_operations: Option<FileOperationOptions>,
+}
+
+/// File operations the server wants to hear about, by filter.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileOperationOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub will_rename: Option<FileOperationRegistrationOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub did_rename: Option<FileOperationRegistrationOptions>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileOperationRegistrationOptions {
+    pub filters: Vec<FileOperationFilter>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileOperationFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheme: Option<String>,
+    pub pattern: FileOperationPattern,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileOperationPattern {
+    pub glob: String,
+    /// "file" or "folder"; both if omitted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matches: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<FileOperationPatternOptions>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileOperationPatternOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ignore_case: Option<bool>,
+}
+
+impl FileOperationRegistrationOptions {
+    /// Whether any filter matches a file or folder path.
+    pub fn matches(&self, path: &std::path::Path, is_dir: bool) -> bool {
+        self.filters.iter().any(|filter| {
+            if filter.scheme.as_deref().is_some_and(|s| s != "file") {
+                return false;
+            }
+            let kind_matches = match filter.pattern.matches.as_deref() {
+                Some("file") => !is_dir,
+                Some("folder") => is_dir,
+                _ => true,
+            };
+            let ignore_case = filter
+                .pattern
+                .options
+                .as_ref()
+                .and_then(|o| o.ignore_case)
+                .unwrap_or(false);
+            kind_matches
+                && globset::GlobBuilder::new(&filter.pattern.glob)
+                    .case_insensitive(ignore_case)
+                    .literal_separator(true)
+                    .build()
+                    .is_ok_and(|glob| glob.compile_matcher().is_match(path))
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TextDocumentSyncCapability {
+    Kind(TextDocumentSyncKind),
+    Options(TextDocumentSyncOptions),
+}
+
+impl TextDocumentSyncCapability {
+    /// How the server wants document changes sent.
+    pub fn change_kind(&self) -> TextDocumentSyncKind {
+        match self {
+            TextDocumentSyncCapability::Kind(kind) => *kind,
+            TextDocumentSyncCapability::Options(options) => {
+                options.change.unwrap_or(TextDocumentSyncKind::NONE)
+            }
+        }
+    }
+
+    /// Whether the server wants textDocument/willSave.
+    pub fn will_save(&self) -> bool {
+        match self {
+            TextDocumentSyncCapability::Kind(_) => false,
+            TextDocumentSyncCapability::Options(options) => options.will_save.unwrap_or(false),
+        }
+    }
+
+    /// Whether the server answers textDocument/willSaveWaitUntil.
+    pub fn will_save_wait_until(&self) -> bool {
+        match self {
+            TextDocumentSyncCapability::Kind(_) => false,
+            TextDocumentSyncCapability::Options(options) => {
+                options.will_save_wait_until.unwrap_or(false)
+            }
+        }
+    }
+
+    /// Whether the server wants textDocument/didSave, and if so whether with
+    /// the saved text. A bare sync kind is taken as wanting it without text.
+    pub fn save(&self) -> Option<bool> {
+        match self {
+            TextDocumentSyncCapability::Kind(_) => Some(false),
+            TextDocumentSyncCapability::Options(options) => match options.save.as_ref()? {
+                SaveCapability::Simple(wanted) => wanted.then_some(false),
+                SaveCapability::Options(save) => Some(save.include_text.unwrap_or(false)),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TextDocumentSyncKind(pub u8);
+
+impl TextDocumentSyncKind {
+    pub const NONE: Self = Self(0);
+    pub const FULL: Self = Self(1);
+    pub const INCREMENTAL: Self = Self(2);
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextDocumentSyncOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub open_close: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change: Option<TextDocumentSyncKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub will_save: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub will_save_wait_until: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub save: Option<SaveCapability>,
+}
+
+/// Save notification support - either a boolean or options.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SaveCapability {
+    Simple(bool),
+    Options(SaveOptions),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_text: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trigger_characters: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolve_provider: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureHelpOptions {
+    /// Characters that open signature help
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trigger_characters: Option<Vec<String>>,
+    /// Characters that update signature help that's already showing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retrigger_characters: Option<Vec<String>>,
+}
+
+// === Server Request Types ===
+
+/// workspace/configuration request params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigurationParams {
+    pub items: Vec<ConfigurationItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigurationItem {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope_uri: Option<String>,
+    /// Dot-separated, e.g. "rust-analyzer.cargo"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub section: Option<String>,
+}
+
+/// client/registerCapability request params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegistrationParams {
+    pub registrations: Vec<Registration>,
+}
+
+/// A capability the server registered after initialization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Registration {
+    /// Used to unregister it again
+    pub id: String,
+    pub method: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub register_options: Option<serde_json::Value>,
+}
+
+/// Registration options of methods about text documents.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextDocumentRegistrationOptions {
+    /// The documents it applies to; all the server handles if null
+    pub document_selector: Option<Vec<DocumentFilter>>,
+}
+
+/// Documents matching all of the given fields.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scheme: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<GlobPattern>,
+}
+
+/// client/unregisterCapability request params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnregistrationParams {
+    /// Misspelled in the specification, kept for compatibility
+    pub unregisterations: Vec<Unregistration>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Unregistration {
+    pub id: String,
+    pub method: String,
+}
+
+/// workspace/applyEdit request params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyWorkspaceEditParams {
+    /// Shown to the user, e.g. in undo history
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub edit: WorkspaceEdit,
+}
+
+/// Result of a workspace/applyEdit request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplyWorkspaceEditResult {
+    pub applied: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_reason: Option<String>,
+}
+
+// === Notification params ===
+
+/// didOpen notification params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidOpenTextDocumentParams {
+    pub text_document: TextDocumentItem,
+}
+
+/// didChange notification params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidChangeTextDocumentParams {
+    pub text_document: VersionedTextDocumentIdentifier,
+    pub content_changes: Vec<TextDocumentContentChangeEvent>,
+}
+
+/// Content change event: `text` replaces `range`, or the whole document
+/// if there's no range.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextDocumentContentChangeEvent {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<Range>,
+    pub text: String,
+}
+
+/// didClose notification params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidCloseTextDocumentParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+/// File change types for didChangeWatchedFiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FileChangeType(pub u8);
+
+impl FileChangeType {
+    pub const CREATED: Self = Self(1);
+    pub const CHANGED: Self = Self(2);
+    pub const DELETED: Self = Self(3);
+
+    /// The `WatchKind` bit a watcher sets to hear about this change.
+    pub fn watch_kind(self) -> u8 {
+        1 << (self.0 - 1)
+    }
+}
+
+/// A file change reported to the server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileEvent {
+    pub uri: String,
+    #[serde(rename = "type")]
+    pub change_type: FileChangeType,
+}
+
+/// workspace/didChangeWatchedFiles notification params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DidChangeWatchedFilesParams {
+    pub changes: Vec<FileEvent>,
+}
+
+/// workspace/didChangeWatchedFiles registration options.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DidChangeWatchedFilesRegistrationOptions {
+    pub watchers: Vec<FileSystemWatcher>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileSystemWatcher {
+    pub glob_pattern: GlobPattern,
+    /// Bitmask of `WatchKind`s (1 create, 2 change, 4 delete); all if omitted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<u8>,
+}
+
+impl FileSystemWatcher {
+    /// The changes it asks for, as `WatchKind` bits.
+    pub fn kind(&self) -> u8 {
+        self.kind.unwrap_or(1 | 2 | 4)
+    }
+}
+
+/// A glob matched against the whole path, or against the path relative to
+/// a base folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GlobPattern {
+    Pattern(String),
+    Relative(RelativePattern),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelativePattern {
+    pub base_uri: BaseUri,
+    pub pattern: String,
+}
+
+/// A workspace folder or a plain URI.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BaseUri {
+    Uri(String),
+    Folder { uri: String },
+}
+
+/// A file or folder rename, for workspace/willRenameFiles and didRenameFiles.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileRename {
+    pub old_uri: String,
+    pub new_uri: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenameFilesParams {
+    pub files: Vec<FileRename>,
+}
+
+/// publishDiagnostics notification params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishDiagnosticsParams {
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<i32>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// references request params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferenceParams {
+    pub text_document: TextDocumentIdentifier,
+    pub position: Position,
+    pub context: ReferenceContext,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReferenceContext {
+    pub include_declaration: bool,
+}
+
+// === Signature Help Types ===
+
+/// textDocument/signatureHelp request params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureHelpParams {
+    pub text_document: TextDocumentIdentifier,
+    pub position: Position,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<SignatureHelpContext>,
+}
+
+/// What triggered a signature help request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureHelpContext {
+    pub trigger_kind: SignatureHelpTriggerKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trigger_character: Option<String>,
+    /// Signature help was already showing
+    pub is_retrigger: bool,
+    /// The signature help showing, with the signature the user picked
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_signature_help: Option<SignatureHelp>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SignatureHelpTriggerKind(pub u8);
+
+impl SignatureHelpTriggerKind {
+    pub const INVOKED: Self = Self(1);
+    pub const TRIGGER_CHARACTER: Self = Self(2);
+    pub const CONTENT_CHANGE: Self = Self(3);
+}
+
+/// Signatures of the callable at a position.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureHelp {
+    pub signatures: Vec<SignatureInformation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_signature: Option<u32>,
+    /// Used for signatures without their own `activeParameter`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_parameter: Option<u32>,
+}
+
+/// One signature of a callable, e.g. one overload.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureInformation {
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<Documentation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<Vec<ParameterInformation>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_parameter: Option<u32>,
+}
+
+/// A parameter of a signature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParameterInformation {
+    pub label: ParameterLabel,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<Documentation>,
+}
+
+/// A parameter label: a substring of the signature label, or its start and
+/// end offsets in the signature label in UTF-16 code units.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ParameterLabel {
+    Simple(String),
+    Offsets([u32; 2]),
+}
+
+// === Semantic Token Types ===
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensDeltaParams {
+    pub text_document: TextDocumentIdentifier,
+    /// The `result_id` of the tokens the delta applies to
+    pub previous_result_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensRangeParams {
+    pub text_document: TextDocumentIdentifier,
+    pub range: Range,
+}
+
+/// Semantic tokens, relative-encoded: five integers per token giving the
+/// line (relative to the previous token), start character (relative to the
+/// previous token if on the same line), length, type index and modifier bits.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokens {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result_id: Option<String>,
+    pub data: Vec<u32>,
+}
+
+/// Edits to the data of the previous semantic tokens.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensDelta {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result_id: Option<String>,
+    pub edits: Vec<SemanticTokensEdit>,
+}
+
+/// Replace `delete_count` integers of token data at `start` with `data`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SemanticTokensEdit {
+    pub start: u32,
+    pub delete_count: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Vec<u32>>,
+}
+
+/// Response to textDocument/semanticTokens/full/delta: servers may send
+/// all tokens instead of a delta.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SemanticTokensDeltaResponse {
+    Tokens(SemanticTokens),
+    Delta(SemanticTokensDelta),
+}
+
+// === Inlay Hint Types ===
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlayHintParams {
+    pub text_document: TextDocumentIdentifier,
+    pub range: Range,
+}
+
+/// Inlay hint kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct InlayHintKind(pub u8);
+
+impl InlayHintKind {
+    pub const TYPE: Self = Self(1);
+    pub const PARAMETER: Self = Self(2);
+}
+
+/// A hint shown inline in the source, e.g. an inferred type or a parameter
+/// name. Sent back as is to inlayHint/resolve.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlayHint {
+    pub position: Position,
+    pub label: InlayHintLabel,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<InlayHintKind>,
+    /// Edits inserting the hint into the document
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_edits: Option<Vec<TextEdit>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tooltip: Option<Documentation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub padding_left: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub padding_right: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+/// An inlay hint label: plain text, or parts that can each have a tooltip
+/// and a location.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum InlayHintLabel {
+    String(String),
+    Parts(Vec<InlayHintLabelPart>),
+}
+
+impl InlayHintLabel {
+    /// The label as shown.
+    pub fn text(&self) -> String {
+        match self {
+            Self::String(s) => s.clone(),
+            Self::Parts(parts) => parts.iter().map(|p| p.value.as_str()).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlayHintLabelPart {
+    pub value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tooltip: Option<Documentation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<Command>,
+}
+
+// === Symbol Types ===
+
+/// Symbol kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SymbolKind(pub u8);
+
+impl SymbolKind {
+    pub const FILE: Self = Self(1);
+    pub const MODULE: Self = Self(2);
+    pub const NAMESPACE: Self = Self(3);
+    pub const PACKAGE: Self = Self(4);
+    pub const CLASS: Self = Self(5);
+    pub const METHOD: Self = Self(6);
+    pub const PROPERTY: Self = Self(7);
+    pub const FIELD: Self = Self(8);
+    pub const CONSTRUCTOR: Self = Self(9);
+    pub const ENUM: Self = Self(10);
+    pub const INTERFACE: Self = Self(11);
+    pub const FUNCTION: Self = Self(12);
+    pub const VARIABLE: Self = Self(13);
+    pub const CONSTANT: Self = Self(14);
+    pub const STRING: Self = Self(15);
+    pub const NUMBER: Self = Self(16);
+    pub const BOOLEAN: Self = Self(17);
+    pub const ARRAY: Self = Self(18);
+    pub const OBJECT: Self = Self(19);
+    pub const KEY: Self = Self(20);
+    pub const NULL: Self = Self(21);
+    pub const ENUM_MEMBER: Self = Self(22);
+    pub const STRUCT: Self = Self(23);
+    pub const EVENT: Self = Self(24);
+    pub const OPERATOR: Self = Self(25);
+    pub const TYPE_PARAMETER: Self = Self(26);
+}
+
+/// textDocument/documentSymbol request params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentSymbolParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+/// A symbol in a document, with the symbols nested in it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentSymbol {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub kind: SymbolKind,
+    /// The whole symbol, e.g. a function including its body
+    pub range: Range,
+    /// The part to select when jumping to the symbol, e.g. its name
+    pub selection_range: Range,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub children: Option<Vec<DocumentSymbol>>,
+}
+
+/// A symbol with its location, from a flat list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolInformation {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub location: Location,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container_name: Option<String>,
+}
+
+/// Response from textDocument/documentSymbol - a tree or a flat list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum DocumentSymbolResponse {
+    Nested(Vec<DocumentSymbol>),
+    Flat(Vec<SymbolInformation>),
+}
+
+/// workspace/symbol request params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceSymbolParams {
+    pub query: String,
+}
+
+/// A symbol from workspace/symbol. Also parses `SymbolInformation`, which
+/// servers may answer with instead. Servers may leave out the range if the
+/// client can resolve it later; ours can't, so that means the start of the file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceSymbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub location: WorkspaceSymbolLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum WorkspaceSymbolLocation {
+    Location(Location),
+    Uri { uri: String },
+}
+
+// === Hierarchy Types ===
+
+/// An item in a call or type hierarchy, e.g. a function or a trait. Both
+/// hierarchies use the same fields. Sent back as is to expand the item.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HierarchyItem {
+    pub name: String,
+    pub kind: SymbolKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<u8>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub uri: String,
+    /// The whole item, e.g. a function including its body
+    pub range: Range,
+    /// The part to select when jumping to the item, e.g. its name
+    pub selection_range: Range,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+/// Params for callHierarchy/incomingCalls, callHierarchy/outgoingCalls,
+/// typeHierarchy/supertypes and typeHierarchy/subtypes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HierarchyItemParams {
+    pub item: HierarchyItem,
+}
+
+/// A caller of an item.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallHierarchyIncomingCall {
+    pub from: HierarchyItem,
+    /// Where the calls are, in `from`'s document
+    pub from_ranges: Vec<Range>,
+}
+
+/// Something an item calls.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallHierarchyOutgoingCall {
+    pub to: HierarchyItem,
+    /// Where the calls are, in the calling item's document
+    pub from_ranges: Vec<Range>,
+}
+
+// === Formatting and Save Types ===
+
+/// How to format a document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormattingOptions {
+    pub tab_size: u32,
+    pub insert_spaces: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trim_trailing_whitespace: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insert_final_newline: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trim_final_newlines: Option<bool>,
+}
+
+/// textDocument/formatting request params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentFormattingParams {
+    pub text_document: TextDocumentIdentifier,
+    pub options: FormattingOptions,
+}
+
+/// textDocument/rangeFormatting request params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentRangeFormattingParams {
+    pub text_document: TextDocumentIdentifier,
+    pub range: Range,
+    pub options: FormattingOptions,
+}
+
+/// Why a document is being saved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TextDocumentSaveReason(pub u8);
+
+impl TextDocumentSaveReason {
+    pub const MANUAL: Self = Self(1);
+    pub const AFTER_DELAY: Self = Self(2);
+    pub const FOCUS_OUT: Self = Self(3);
+}
+
+/// textDocument/willSave notification and willSaveWaitUntil request params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WillSaveTextDocumentParams {
+    pub text_document: TextDocumentIdentifier,
+    pub reason: TextDocumentSaveReason,
+}
+
+/// textDocument/didSave notification params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidSaveTextDocumentParams {
+    pub text_document: TextDocumentIdentifier,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
+// === Rename Types ===
+
+/// textDocument/rename request params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameParams {
+    pub text_document: TextDocumentIdentifier,
+    pub position: Position,
+    pub new_name: String,
+}
+
+/// Response from textDocument/prepareRename: the range to rename, optionally
+/// with placeholder text, or a request to use the word at the position.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PrepareRenameResponse {
+    Range(Range),
+    RangeWithPlaceholder {
+        range: Range,
+        placeholder: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    DefaultBehavior {
+        default_behavior: bool,
+    },
+}
+
+// === Code Action Types ===
+
+/// Code action kind values (subset of the full LSP spec).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeActionKind(pub String);
+
+impl CodeActionKind {
+    pub const QUICK_FIX: &'static str = "quickfix";
+    pub const REFACTOR: &'static str = "refactor";
+    pub const REFACTOR_EXTRACT: &'static str = "refactor.extract";
+    pub const REFACTOR_INLINE: &'static str = "refactor.inline";
+    pub const REFACTOR_REWRITE: &'static str = "refactor.rewrite";
+    pub const SOURCE: &'static str = "source";
+    pub const SOURCE_ORGANIZE_IMPORTS: &'static str = "source.organizeImports";
+}
+
+/// Code action request params.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeActionParams {
+    pub text_document: TextDocumentIdentifier,
+    pub range: Range,
+    pub context: CodeActionContext,
+}
+
+/// Context for code actions, including diagnostics.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeActionContext {
+    pub diagnostics: Vec<Diagnostic>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub only: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trigger_kind: Option<CodeActionTriggerKind>,
+}
+
+/// What triggered code actions request.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CodeActionTriggerKind(pub u8);
+
+impl CodeActionTriggerKind {
+    pub const INVOKED: Self = Self(1);
+    pub const AUTOMATIC: Self = Self(2);
+}
+
+/// A code action represents a change that can be performed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeAction {
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diagnostics: Option<Vec<Diagnostic>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_preferred: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disabled: Option<CodeActionDisabled>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edit: Option<WorkspaceEdit>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<Command>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+/// Reason why a code action is disabled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodeActionDisabled {
+    pub reason: String,
+}
+
+/// An LSP Command (not to be confused with a code action).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Command {
+    pub title: String,
+    pub command: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<Vec<serde_json::Value>>,
+}
+
+/// Workspace edit - a collection of changes to documents.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceEdit {
+    /// Map of file URI to list of text edits.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changes: Option<std::collections::HashMap<String, Vec<TextEdit>>>,
+    /// More complex document changes (may include create/rename/delete).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document_changes: Option<Vec<DocumentChange>>,
+}
+
+/// A document change can be a text edit or resource operation.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum DocumentChange {
+    Edit(TextDocumentEdit),
+    Create(CreateFile),
+    Rename(RenameFile),
+    Delete(DeleteFile),
+}
+
+// Resource operations are told apart by `kind`: create and delete have the
+// same fields, so untagged deserialization can't distinguish them.
+impl<'de> Deserialize<'de> for DocumentChange {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+        let change = match value.get("kind").and_then(|k| k.as_str()) {
+            Some("create") => serde_json::from_value(value).map(Self::Create),
+            Some("rename") => serde_json::from_value(value).map(Self::Rename),
+            Some("delete") => serde_json::from_value(value).map(Self::Delete),
+            _ => serde_json::from_value(value).map(Self::Edit),
+        };
+        change.map_err(serde::de::Error::custom)
+    }
+}
+
+/// A text edit on a specific versioned document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextDocumentEdit {
+    pub text_document: OptionalVersionedTextDocumentIdentifier,
+    pub edits: Vec<TextEditOrAnnotated>,
+}
+
+/// Text document identifier with optional version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptionalVersionedTextDocumentIdentifier {
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<i32>,
+}
+
+/// Text edit or annotated text edit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TextEditOrAnnotated {
+    TextEdit(TextEdit),
+    Annotated(AnnotatedTextEdit),
+}
+
+/// A text edit with an annotation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnotatedTextEdit {
+    pub range: Range,
+    pub new_text: String,
+    pub annotation_id: String,
+}
+
+/// Create file operation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateFile {
+    pub kind: String, // "create"
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<CreateFileOptions>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateFileOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overwrite: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ignore_if_exists: Option<bool>,
+}
+
+/// Rename file operation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameFile {
+    pub kind: String, // "rename"
+    pub old_uri: String,
+    pub new_uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<RenameFileOptions>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameFileOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overwrite: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ignore_if_exists: Option<bool>,
+}
+
+/// Delete file operation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteFile {
+    pub kind: String, // "delete"
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<DeleteFileOptions>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteFileOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recursive: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ignore_if_not_exists: Option<bool>,
+}
+
+/// Response from code action request - can be CodeAction or Command.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CodeActionResponse {
+    Actions(Vec<CodeActionOrCommand>),
+}
+
+/// Code action response item - either a full CodeAction or just a Command.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
+pub enum CodeActionOrCommand {
+    CodeAction(CodeAction),
+    Command(Command),
+}
+
+impl CodeActionResponse {
+    /// Extract code actions from the response.
+    pub fn into_actions(self) -> Vec<CodeAction> {
+        match self {
+            Self::Actions(items) => items
+                .into_iter()
+                .map(|item| match item {
+                    CodeActionOrCommand::CodeAction(action) => action,
+                    CodeActionOrCommand::Command(cmd) => CodeAction {
+                        title: cmd.title.clone(),
+                        kind: None,
+                        diagnostics: None,
+                        is_preferred: None,
+                        disabled: None,
+                        edit: None,
+                        command: Some(cmd),
+                        data: None,
+                    },
+                })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Shapes the frontend sends and receives positions and edits in.
+
+use super::protocol::{
+    DocumentChange, OptionalVersionedTextDocumentIdentifier, Position, Range, TextDocumentEdit,
+    TextEdit, TextEditOrAnnotated, WorkspaceEdit,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SerializedRange {
+    pub start: SerializedPosition,
+    pub end: SerializedPosition,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SerializedPosition {
+    pub line: u32,
+    pub character: u32,
+}
+
+impl From<Range> for SerializedRange {
+    fn from(range: Range) -> Self {
+        Self {
+            start: SerializedPosition {
+                line: range.start.line,
+                character: range.start.character,
+            },
+            end: SerializedPosition {
+                line: range.end.line,
+                character: range.end.character,
+            },
+        }
+    }
+}
+
+impl From<SerializedRange> for Range {
+    fn from(range: SerializedRange) -> Self {
+        Self {
+            start: Position {
+                line: range.start.line,
+                character: range.start.character,
+            },
+            end: Position {
+                line: range.end.line,
+                character: range.end.character,
+            },
+        }
+    }
+}
+
+/// Serialized text edit for frontend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedTextEdit {
+    pub range: SerializedRange,
+    pub new_text: String,
+}
+
+impl From<TextEdit> for SerializedTextEdit {
+    fn from(edit: TextEdit) -> Self {
+        Self {
+            range: SerializedRange {
+                start: SerializedPosition {
+                    line: edit.range.start.line,
+                    character: edit.range.start.character,
+                },
+                end: SerializedPosition {
+                    line: edit.range.end.line,
+                    character: edit.range.end.character,
+                },
+            },
+            new_text: edit.new_text,
+        }
+    }
+}
+
+impl From<SerializedTextEdit> for TextEdit {
+    fn from(edit: SerializedTextEdit) -> Self {
+        Self {
+            range: edit.range.into(),
+            new_text: edit.new_text,
+        }
+    }
+}
+
+/// Serialized workspace edit for frontend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedWorkspaceEdit {
+    /// Map of file URI to list of text edits.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changes: Option<std::collections::HashMap<String, Vec<SerializedTextEdit>>>,
+    /// Document changes (for more complex edits).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document_changes: Option<Vec<SerializedDocumentChange>>,
+}
+
+/// Serialized document change for frontend: edits to a document, or a
+/// create, rename or delete, which keep their LSP shape.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SerializedDocumentChange {
+    Edit {
+        /// The document to edit.
+        uri: String,
+        /// The edits to apply.
+        edits: Vec<SerializedTextEdit>,
+    },
+    Operation(DocumentChange),
+}
+
+impl From<WorkspaceEdit> for SerializedWorkspaceEdit {
+    fn from(edit: WorkspaceEdit) -> Self {
+        let changes = edit.changes.map(|c| {
+            c.into_iter()
+                .map(|(uri, edits)| {
+                    (
+                        uri,
+                        edits.into_iter().map(SerializedTextEdit::from).collect(),
+                    )
+                })
+                .collect()
+        });
+
+        let document_changes = edit.document_changes.map(|dc| {
+            dc.into_iter()
+                .map(|change| match change {
+                    DocumentChange::Edit(edit) => SerializedDocumentChange::Edit {
+                        uri: edit.text_document.uri,
+                        edits: edit
+                            .edits
+                            .into_iter()
+                            .map(|e| match e {
+                                TextEditOrAnnotated::TextEdit(te) => SerializedTextEdit::from(te),
+                                TextEditOrAnnotated::Annotated(ate) => SerializedTextEdit {
+                                    range: SerializedRange {
+                                        start: SerializedPosition {
+                                            line: ate.range.start.line,
+                                            character: ate.range.start.character,
+                                        },
+                                        end: SerializedPosition {
+                                            line: ate.range.end.line,
+                                            character: ate.range.end.character,
+                                        },
+                                    },
+                                    new_text: ate.new_text,
+                                },
+                            })
+                            .collect(),
+                    },
+                    operation => SerializedDocumentChange::Operation(operation),
+                })
+                .collect()
+        });
+
+        Self {
+            changes,
+            document_changes,
+        }
+    }
+}
+
+impl From<SerializedWorkspaceEdit> for WorkspaceEdit {
+    fn from(edit: SerializedWorkspaceEdit) -> Self {
+        let changes = edit.changes.map(|c| {
+            c.into_iter()
+                .map(|(uri, edits)| (uri, edits.into_iter().map(TextEdit::from).collect()))
+                .collect()
+        });
+        let document_changes = edit.document_changes.map(|dc| {
+            dc.into_iter()
+                .map(|change| match change {
+                    SerializedDocumentChange::Edit { uri, edits } => {
+                        DocumentChange::Edit(TextDocumentEdit {
+                            text_document: OptionalVersionedTextDocumentIdentifier {
+                                uri,
+                                version: None,
+                            },
+                            edits: edits
+                                .into_iter()
+                                .map(|e| TextEditOrAnnotated::TextEdit(e.into()))
+                                .collect(),
+                        })
+                    }
+                    SerializedDocumentChange::Operation(operation) => operation,
+                })
+                .collect()
+        });
+        Self {
+            changes,
+            document_changes,
+        }
+    }
+}
@@ -0,0 +1,285 @@
+//! The directories file commands may touch.
+//!
+//! File commands only accept paths inside an allowed root, so a script in the
+//! webview can't read or overwrite arbitrary files. Roots are added when the
+//! user picks a project folder in a native dialog, or approves a path in a
+//! native confirmation dialog. Approvals are remembered across restarts, so
+//! reopening a known project doesn't ask again.
+//!
+//! Paths are canonicalized before they're checked, so `..` components and
+//! symlinks pointing outside a root are caught.
+
+use crate::file::atomic_write;
+use directories::ProjectDirs;
+use parking_lot::{Mutex, RwLock};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A path outside every allowed root.
+#[derive(Debug, Clone)]
+pub struct OutOfScope {
+    pub path: String,
+}
+
+impl fmt::Display for OutOfScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Permission denied: '{}' is outside the open projects",
+            self.path
+        )
+    }
+}
+
+impl From<OutOfScope> for String {
+    fn from(e: OutOfScope) -> Self {
+        e.to_string()
+    }
+}
+
+/// Allowed roots for this session, plus roots the user approved in any session.
+pub struct PathScope {
+    /// Canonical roots file commands may access
+    roots: RwLock<Vec<PathBuf>>,
+    /// Canonical roots the user has approved, persisted
+    approved: Mutex<Vec<PathBuf>>,
+}
+
+impl Default for PathScope {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PathScope {
+    pub fn new() -> Self {
+        let approved = fs::read(approved_path())
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        Self {
+            roots: RwLock::new(Vec::new()),
+            approved: Mutex::new(approved),
+        }
+    }
+
+    /// Resolve `path` and check it's inside an allowed root. Returns the
+    /// canonical path, which callers should use from then on.
+    pub fn check(&self, path: impl AsRef<Path>) -> Result<PathBuf, OutOfScope> {
+        let path = path.as_ref();
+        let out_of_scope = || OutOfScope {
+            path: path.to_string_lossy().to_string(),
+        };
+        let resolved = resolve(path).ok_or_else(out_of_scope)?;
+        if self.contains(&resolved) {
+            Ok(resolved)
+        } else {
+            Err(out_of_scope())
+        }
+    }
+
+    /// Like `check`, for commands acting on an entry itself, such as renaming
+    /// or deleting it: a symlink at the end of the path is the entry, so it
+    /// isn't followed.
+    pub fn check_entry(&self, path: impl AsRef<Path>) -> Result<PathBuf, OutOfScope> {
+        let path = path.as_ref();
+        let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+            return self.check(path);
+        };
+        let out_of_scope = || OutOfScope {
+            path: path.to_string_lossy().to_string(),
+        };
+        let resolved = resolve(parent).ok_or_else(out_of_scope)?.join(name);
+        if self.contains(&resolved) {
+            Ok(resolved)
+        } else {
+            Err(out_of_scope())
+        }
+    }
+
+    fn contains(&self, resolved: &Path) -> bool {
+        self.roots
+            .read()
+            .iter()
+            .any(|root| resolved.starts_with(root))
+    }
+
+    /// Allow access to `root` this session if the user approved it before.
+    pub fn allow_if_approved(&self, root: &Path) -> bool {
+        if !self.approved.lock().iter().any(|p| p == root) {
+            return false;
+        }
+        self.allow(root);
+        true
+    }
+
+    fn allow(&self, root: &Path) {
+        let mut roots = self.roots.write();
+        if !roots.iter().any(|r| r == root) {
+            roots.push(root.to_path_buf());
+        }
+    }
+
+    /// Allow access to `root` and remember the approval.
+    pub fn approve(&self, root: &Path) -> Result<(), String> {
+        self.allow(root);
+        let mut approved = self.approved.lock();
+        if !approved.iter().any(|p| p == root) {
+            approved.push(root.to_path_buf());
+        }
+        save_approved(&approved)
+    }
+
+    /// Withdraw access to `root` and forget its approval.
+    pub fn revoke(&self, root: &Path) -> Result<(), String> {
+        self.roots.write().retain(|r| r != root);
+        let mut approved = self.approved.lock();
+        approved.retain(|p| p != root);
+        save_approved(&approved)
+    }
+}
+
+/// Canonicalize a path that may not exist yet: the longest existing prefix is
+/// canonicalized and the remaining components appended. None if the path is
+/// relative, or the remaining components include `..` or a dangling symlink,
+/// which could point anywhere once created.
+fn resolve(path: &Path) -> Option<PathBuf> {
+    if !path.is_absolute() {
+        return None;
+    }
+    let mut existing = path;
+    let mut missing = Vec::new();
+    loop {
+        match fs::canonicalize(existing) {
+            Ok(canonical) => {
+                return Some(missing.iter().rev().fold(canonical, |p, name| p.join(name)));
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                if fs::symlink_metadata(existing).is_ok() {
+                    return None;
+                }
+                // None for `..` and `.`
+                missing.push(existing.file_name()?);
+                existing = existing.parent()?;
+            }
+            Err(_) => return None,
+        }
+    }
+}
+
+fn approved_path() -> PathBuf {
+    if let Some(proj_dirs) = ProjectDirs::from("com", "innocencelabs", "raven") {
+        proj_dirs.data_dir().join("allowed-roots.json")
+    } else {
+        std::env::temp_dir().join("raven-allowed-roots.json")
+    }
+}
+
+fn save_approved(approved: &[PathBuf]) -> Result<(), String> {
+    let path = approved_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+    }
+    let json = serde_json::to_vec(approved)
+        .map_err(|e| format!("Failed to serialize allowed roots: {}", e))?;
+    atomic_write(&path, &json).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+}
+
+/// Canonicalize a path, which must be a directory.
+pub fn canonical_dir(path: &str) -> Result<PathBuf, String> {
+    let canonical =
+        fs::canonicalize(path).map_err(|e| format!("Failed to open '{}': {}", path, e))?;
+    if !canonical.is_dir() {
+        return Err(format!("Not a directory: {}", path));
+    }
+    Ok(canonical)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+    use std::os::unix::fs::symlink;
+
+    fn scope_of(root: &Path) -> PathScope {
+        PathScope {
+            roots: RwLock::new(vec![root.to_path_buf()]),
+            approved: Mutex::new(Vec::new()),
+        }
+    }
+
+    #[test]
+    fn test_resolve() {
+        let dir = TempDir::new();
+        let file = dir.write("src/main.rs", "");
+        assert_eq!(resolve(&file), Some(file.clone()));
+        assert_eq!(
+            resolve(&dir.path().join("src/../src/main.rs")),
+            Some(file.clone())
+        );
+        // Missing components are appended to the existing prefix
+        assert_eq!(
+            resolve(&dir.path().join("src/new/mod.rs")),
+            Some(dir.path().join("src/new/mod.rs"))
+        );
+        assert_eq!(resolve(Path::new("src/main.rs")), None);
+        // `..` after a missing component could go anywhere once it exists
+        assert_eq!(resolve(&dir.path().join("new/../../etc")), None);
+    }
+
+    #[test]
+    fn test_resolve_symlinks() {
+        let dir = TempDir::new();
+        let file = dir.write("real/a.txt", "");
+        symlink(dir.path().join("real"), dir.path().join("link")).unwrap();
+        assert_eq!(resolve(&dir.path().join("link/a.txt")), Some(file));
+        assert_eq!(
+            resolve(&dir.path().join("link/b.txt")),
+            Some(dir.path().join("real/b.txt"))
+        );
+
+        symlink(dir.path().join("missing"), dir.path().join("dangling")).unwrap();
+        assert_eq!(resolve(&dir.path().join("dangling")), None);
+        assert_eq!(resolve(&dir.path().join("dangling/a.txt")), None);
+    }
+
+    #[test]
+    fn test_check() {
+        let root = TempDir::new();
+        let outside = TempDir::new();
+        let secret = outside.write("secret", "");
+        symlink(&secret, root.path().join("escape")).unwrap();
+        let scope = scope_of(root.path());
+
+        let file = root.write("a.txt", "");
+        assert_eq!(scope.check(&file).unwrap(), file);
+        assert!(scope.check(&secret).is_err());
+        assert!(scope.check(root.path().join("escape")).is_err());
+        assert!(scope.check(root.path().join("../escape")).is_err());
+    }
+
+    #[test]
+    fn test_check_entry() {
+        let root = TempDir::new();
+        let outside = TempDir::new();
+        symlink(outside.path(), root.path().join("link")).unwrap();
+        symlink(root.path().join("missing"), root.path().join("dangling")).unwrap();
+        let scope = scope_of(root.path());
+
+        // The link itself is in the project, wherever it points
+        assert_eq!(
+            scope.check_entry(root.path().join("link")).unwrap(),
+            root.path().join("link")
+        );
+        assert_eq!(
+            scope.check_entry(root.path().join("dangling")).unwrap(),
+            root.path().join("dangling")
+        );
+        // But not what's behind it
+        assert!(scope.check_entry(root.path().join("link/file")).is_err());
+        assert_eq!(scope.check_entry(root.path()).unwrap(), root.path());
+    }
+}
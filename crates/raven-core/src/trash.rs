@@ -0,0 +1,132 @@
+//! Raven's trash, which deleted paths are moved into so they can be restored.
+//! It's a directory managed by Raven, not the system trash.
+
+use crate::file::move_path;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Trashed items older than this are deleted for good
+const TRASH_MAX_AGE_MS: u128 = 30 * 24 * 60 * 60 * 1000;
+/// Name of the metadata file in each trash entry
+const TRASH_META: &str = "entry.json";
+/// Name of the trashed file or directory in each trash entry
+const TRASH_ITEM: &str = "item";
+
+/// A deleted path that can be restored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrashEntry {
+    /// Pass to `restore_path`
+    pub id: String,
+    pub original_path: String,
+    /// Milliseconds since the Unix epoch
+    pub deleted_at: u64,
+}
+
+/// Move a path to the trash, returning the entry to restore it from.
+pub fn trash_path(path: &Path) -> Result<TrashEntry, String> {
+    fs::symlink_metadata(path)
+        .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+
+    purge_old_trash();
+
+    let deleted_at = now_millis();
+    let entry = TrashEntry {
+        id: format!("{:013}-{}", deleted_at, uuid::Uuid::new_v4().simple()),
+        original_path: path.to_string_lossy().to_string(),
+        deleted_at: deleted_at as u64,
+    };
+
+    let dir = trash_dir().join(&entry.id);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create trash directory: {}", e))?;
+    let json = serde_json::to_vec(&entry).map_err(|e| e.to_string())?;
+    fs::write(dir.join(TRASH_META), json)
+        .map_err(|e| format!("Failed to write trash entry: {}", e))?;
+
+    if let Err(e) = move_path(path, &dir.join(TRASH_ITEM)) {
+        let _ = fs::remove_dir_all(&dir);
+        return Err(format!("Failed to delete '{}': {}", path.display(), e));
+    }
+    Ok(entry)
+}
+
+/// The trash entry with an ID.
+pub fn entry(id: &str) -> Result<TrashEntry, String> {
+    let dir = trash_entry_dir(id)?;
+    fs::read(dir.join(TRASH_META))
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .ok_or_else(|| format!("No trash entry: {}", id))
+}
+
+/// Move a trash entry's item back to `original`, removing the entry.
+pub fn untrash(id: &str, original: &Path) -> Result<(), String> {
+    let dir = trash_entry_dir(id)?;
+    if fs::symlink_metadata(original).is_ok() {
+        return Err(format!("Already exists: {}", original.display()));
+    }
+    if let Some(parent) = original.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory '{}': {}", parent.display(), e))?;
+    }
+    move_path(&dir.join(TRASH_ITEM), original)
+        .map_err(|e| format!("Failed to restore '{}': {}", original.display(), e))?;
+    let _ = fs::remove_dir_all(&dir);
+    Ok(())
+}
+
+/// Trashed paths, most recently deleted first.
+pub fn list() -> Vec<TrashEntry> {
+    let Ok(dirs) = fs::read_dir(trash_dir()) else {
+        return Vec::new();
+    };
+    let mut entries: Vec<TrashEntry> = dirs
+        .filter_map(|e| e.ok())
+        .filter_map(|e| fs::read(e.path().join(TRASH_META)).ok())
+        .filter_map(|bytes| serde_json::from_slice(&bytes).ok())
+        .collect();
+    entries.sort_by(|a, b| b.id.cmp(&a.id));
+    entries
+}
+
+/// Permanently delete trash entries past their maximum age.
+fn purge_old_trash() {
+    let Ok(dirs) = fs::read_dir(trash_dir()) else {
+        return;
+    };
+    let now = now_millis();
+    for dir in dirs.filter_map(|e| e.ok()) {
+        // IDs start with the deletion time in milliseconds
+        let name = dir.file_name().to_string_lossy().to_string();
+        let deleted_at = name.split('-').next().and_then(|t| t.parse::<u128>().ok());
+        if deleted_at.is_some_and(|t| now.saturating_sub(t) > TRASH_MAX_AGE_MS) {
+            let _ = fs::remove_dir_all(dir.path());
+        }
+    }
+}
+
+fn trash_dir() -> PathBuf {
+    if let Some(proj_dirs) = ProjectDirs::from("com", "innocencelabs", "raven") {
+        proj_dirs.data_dir().join("trash")
+    } else {
+        std::env::temp_dir().join("raven-trash")
+    }
+}
+
+fn trash_entry_dir(id: &str) -> Result<PathBuf, String> {
+    // IDs are generated by us; reject anything that could escape the directory
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("Invalid trash ID: {}", id));
+    }
+    Ok(trash_dir().join(id))
+}
+
+fn now_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0)
+}
@@ -0,0 +1,308 @@
+//! Filtering and classifying filesystem changes in a project.
+//!
+//! Paths `list_files` would skip (hidden or gitignored) are dropped, and raw
+//! notify events are collapsed into one change per path.
+
+use crate::file::project_walk;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use notify_debouncer_full::notify::event::{ModifyKind, RenameMode};
+use notify_debouncer_full::notify::EventKind;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FsChangeKind {
+    Created,
+    Modified,
+    Removed,
+    Renamed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FsChange {
+    pub path: String,
+    pub kind: FsChangeKind,
+    /// Previous path, for renames
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+}
+
+/// Turn raw notify events into changes, dropping ignored paths and
+/// collapsing repeated changes to the same path.
+pub fn collect_changes<'a>(
+    events: impl Iterator<Item = (&'a EventKind, &'a [PathBuf])>,
+    filter: &IgnoreFilter,
+) -> Vec<FsChange> {
+    let mut changes: Vec<FsChange> = Vec::new();
+    let mut push = |path: &Path, kind: FsChangeKind, old_path: Option<&Path>| {
+        if filter.is_ignored(path) {
+            return;
+        }
+        let path = path.to_string_lossy().to_string();
+        let old_path = old_path.map(|p| p.to_string_lossy().to_string());
+
+        if let Some(existing) = changes.iter_mut().find(|c| c.path == path) {
+            // A file created and then written in the same batch is still just created
+            if !(existing.kind == FsChangeKind::Created && kind == FsChangeKind::Modified) {
+                existing.kind = kind;
+                existing.old_path = old_path;
+            }
+            return;
+        }
+        changes.push(FsChange {
+            path,
+            kind,
+            old_path,
+        });
+    };
+
+    for (kind, paths) in events {
+        match kind {
+            EventKind::Create(_) => paths
+                .iter()
+                .for_each(|p| push(p, FsChangeKind::Created, None)),
+            EventKind::Remove(_) => paths
+                .iter()
+                .for_each(|p| push(p, FsChangeKind::Removed, None)),
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if paths.len() == 2 => {
+                // Renaming an ignored file into the project is a creation, and vice versa
+                match (filter.is_ignored(&paths[0]), filter.is_ignored(&paths[1])) {
+                    (false, false) => push(&paths[1], FsChangeKind::Renamed, Some(&paths[0])),
+                    (true, false) => push(&paths[1], FsChangeKind::Created, None),
+                    (false, true) => push(&paths[0], FsChangeKind::Removed, None),
+                    (true, true) => {}
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => paths
+                .iter()
+                .for_each(|p| push(p, FsChangeKind::Removed, None)),
+            EventKind::Modify(ModifyKind::Name(_)) => paths
+                .iter()
+                .for_each(|p| push(p, FsChangeKind::Created, None)),
+            // Metadata-only changes (permissions, timestamps) don't change contents
+            EventKind::Modify(ModifyKind::Metadata(_)) => {}
+            EventKind::Modify(_) => paths
+                .iter()
+                .for_each(|p| push(p, FsChangeKind::Modified, None)),
+            EventKind::Access(_) | EventKind::Any | EventKind::Other => {}
+        }
+    }
+
+    changes
+}
+
+/// Split changes into (created, changed, deleted) paths for language servers.
+pub fn lsp_changes(changes: &[FsChange]) -> (Vec<PathBuf>, Vec<PathBuf>, Vec<PathBuf>) {
+    let mut created = Vec::new();
+    let mut changed = Vec::new();
+    let mut deleted = Vec::new();
+    for change in changes {
+        let path = PathBuf::from(&change.path);
+        match change.kind {
+            FsChangeKind::Created => created.push(path),
+            FsChangeKind::Modified => changed.push(path),
+            FsChangeKind::Removed => deleted.push(path),
+            FsChangeKind::Renamed => {
+                if let Some(old_path) = &change.old_path {
+                    deleted.push(PathBuf::from(old_path));
+                }
+                created.push(path);
+            }
+        }
+    }
+    (created, changed, deleted)
+}
+
+/// `dir` and the directories under it that `list_files` would enter. Empty if
+/// `dir` doesn't exist.
+pub fn project_dirs(dir: &Path) -> Vec<PathBuf> {
+    project_walk(dir)
+        .build()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_dir()))
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+/// Matches paths against the same rules `list_files` walks with: hidden files,
+/// every `.gitignore` in the project, `.git/info/exclude` and the global gitignore.
+pub struct IgnoreFilter {
+    root: PathBuf,
+    /// Deepest directories first, so nested rules take precedence
+    gitignores: Vec<Gitignore>,
+    exclude: Gitignore,
+    global: Gitignore,
+}
+
+impl IgnoreFilter {
+    pub fn new(root: &Path) -> Self {
+        let mut gitignores: Vec<Gitignore> = project_dirs(root)
+            .into_iter()
+            .map(|dir| dir.join(".gitignore"))
+            .filter(|path| path.is_file())
+            .map(|path| Gitignore::new(path).0)
+            .collect();
+        gitignores.sort_by_key(|g| std::cmp::Reverse(g.path().components().count()));
+
+        let mut exclude = GitignoreBuilder::new(root);
+        exclude.add(root.join(".git/info/exclude"));
+
+        Self {
+            root: root.to_path_buf(),
+            gitignores,
+            exclude: exclude.build().unwrap_or_else(|_| Gitignore::empty()),
+            global: Gitignore::global().0,
+        }
+    }
+
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return true;
+        };
+        let hidden = relative
+            .components()
+            .any(|c| c.as_os_str().to_string_lossy().starts_with('.'));
+        if hidden {
+            return true;
+        }
+
+        // Removed paths can't be checked on disk, so they're treated as files
+        let is_dir = path.is_dir();
+        let matchers = self
+            .gitignores
+            .iter()
+            .map(|g| (g, g.path()))
+            .chain([(&self.exclude, &*self.root), (&self.global, &*self.root)]);
+        for (gitignore, base) in matchers {
+            let Ok(relative) = path.strip_prefix(base) else {
+                continue;
+            };
+            match matched_with_parents(gitignore, relative, is_dir) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {}
+            }
+        }
+        false
+    }
+}
+
+/// Match a path, or failing that any of its parent directories (so files
+/// inside an ignored directory are ignored too).
+fn matched_with_parents<'a>(
+    gitignore: &'a Gitignore,
+    relative: &Path,
+    is_dir: bool,
+) -> Match<&'a ignore::gitignore::Glob> {
+    let mut is_dir = is_dir;
+    for path in relative.ancestors() {
+        if path.as_os_str().is_empty() {
+            break;
+        }
+        let m = gitignore.matched(path, is_dir);
+        if !m.is_none() {
+            return m;
+        }
+        is_dir = true;
+    }
+    Match::None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+    use notify_debouncer_full::notify::event::CreateKind;
+
+    /// A git repository with root and nested ignore files.
+    fn project() -> TempDir {
+        let dir = TempDir::new();
+        dir.write(".git/info/exclude", "excluded.txt\n");
+        dir.write(".gitignore", "target/\n*.log\n");
+        dir.write("src/.gitignore", "generated.rs\n!keep.log\n");
+        dir.write("src/main.rs", "");
+        dir.write("target/debug/app", "");
+        dir
+    }
+
+    #[test]
+    fn test_ignore_filter_gitignore() {
+        let dir = project();
+        let filter = IgnoreFilter::new(dir.path());
+        let root = dir.path();
+
+        assert!(!filter.is_ignored(&root.join("src/main.rs")));
+        assert!(filter.is_ignored(&root.join("target")));
+        assert!(filter.is_ignored(&root.join("target/debug/app")));
+        assert!(filter.is_ignored(&root.join("build.log")));
+        assert!(filter.is_ignored(&root.join("excluded.txt")));
+        // Nested rules apply under their directory only
+        assert!(filter.is_ignored(&root.join("src/generated.rs")));
+        assert!(!filter.is_ignored(&root.join("generated.rs")));
+        // and take precedence over the root's
+        assert!(!filter.is_ignored(&root.join("src/keep.log")));
+        assert!(filter.is_ignored(&root.join("src/other.log")));
+    }
+
+    #[test]
+    fn test_ignore_filter_hidden_and_outside() {
+        let dir = project();
+        let filter = IgnoreFilter::new(dir.path());
+
+        assert!(filter.is_ignored(&dir.path().join(".git/HEAD")));
+        assert!(filter.is_ignored(&dir.path().join("src/.env")));
+        assert!(filter.is_ignored(Path::new("/elsewhere/main.rs")));
+    }
+
+    #[test]
+    fn test_project_dirs_skip_ignored() {
+        let dir = project();
+        let mut dirs = project_dirs(dir.path());
+        dirs.sort();
+        assert_eq!(dirs, vec![dir.path().to_path_buf(), dir.path().join("src")]);
+        assert!(project_dirs(&dir.path().join("missing")).is_empty());
+    }
+
+    #[test]
+    fn test_collect_changes() {
+        let dir = project();
+        let filter = IgnoreFilter::new(dir.path());
+        let root = dir.path();
+
+        let create = EventKind::Create(CreateKind::File);
+        let modify = EventKind::Modify(ModifyKind::Any);
+        let rename = EventKind::Modify(ModifyKind::Name(RenameMode::Both));
+        let created = [root.join("src/new.rs")];
+        let ignored = [root.join("build.log")];
+        let renamed = [root.join("out.log"), root.join("src/lib.rs")];
+        let events = [
+            (&create, &created[..]),
+            (&modify, &created[..]),
+            (&create, &ignored[..]),
+            (&rename, &renamed[..]),
+        ];
+
+        let changes = collect_changes(events.into_iter(), &filter);
+        let summary: Vec<(&str, FsChangeKind)> = changes
+            .iter()
+            .map(|c| {
+                (
+                    c.path.strip_prefix(&*root.to_string_lossy()).unwrap(),
+                    c.kind,
+                )
+            })
+            .collect();
+        // Created then written is still created, and a rename out of an ignored
+        // path is a creation
+        assert_eq!(
+            summary,
+            vec![
+                ("/src/new.rs", FsChangeKind::Created),
+                ("/src/lib.rs", FsChangeKind::Created),
+            ]
+        );
+    }
+}
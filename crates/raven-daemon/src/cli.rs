@@ -0,0 +1,416 @@
+//! Command-line subcommands for inspecting and controlling a running daemon.
+//!
+//! Each subcommand is a plain client of the daemon socket and speaks the same
+//! protocol as the app.
+
+use crate::client::Client;
+use crate::protocol::{ClientMessage, LogEntry, ServerMessage, SessionInfo};
+use clap::{Parser, Subcommand};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::ExitCode;
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::sync::mpsc;
+
+/// Key that detaches an interactive `attach` (Ctrl-])
+const DETACH_KEY: u8 = 0x1d;
+
+#[derive(Parser)]
+#[command(name = "raven-daemon", about = "Raven terminal session daemon")]
+pub struct Cli {
+    /// Print machine-readable JSON instead of text
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Run the daemon if no subcommand is given
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// List sessions
+    List,
+    /// Attach this terminal to a session (Ctrl-] detaches)
+    Attach { id: String },
+    /// Kill a session
+    Kill { id: String },
+    /// Show daemon log entries
+    Logs {
+        /// Only entries at or after this RFC 3339 timestamp
+        #[arg(long)]
+        since: Option<String>,
+        /// Least severe level to include (error, warn, info, debug, trace)
+        #[arg(long)]
+        level: Option<String>,
+    },
+    /// Check whether the daemon is running
+    Status,
+    /// Write text to a session's input
+    Send {
+        id: String,
+        text: String,
+        /// Append a carriage return, as if Enter was pressed
+        #[arg(long)]
+        enter: bool,
+    },
+}
+
+/// Run a subcommand, returning the process exit code.
+pub async fn run(command: Command, json: bool, socket_path: &Path) -> anyhow::Result<ExitCode> {
+    if let Command::Status = command {
+        return status(socket_path, json).await;
+    }
+
+    let mut client = Client::connect(socket_path).await?;
+    match command {
+        Command::List => match client.request(&ClientMessage::List).await? {
+            ServerMessage::Sessions { sessions } => {
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&sessions)?);
+                } else {
+                    print_sessions(&sessions);
+                }
+            }
+            other => unexpected(other)?,
+        },
+        Command::Attach { id } => attach(client, id).await?,
+        Command::Kill { id } => {
+            client
+                .request(&ClientMessage::Kill { session_id: id })
+                .await?;
+        }
+        Command::Logs { since, level } => {
+            match client
+                .request(&ClientMessage::Logs { since, level })
+                .await?
+            {
+                ServerMessage::Logs { entries } => {
+                    if json {
+                        println!("{}", serde_json::to_string_pretty(&entries)?);
+                    } else {
+                        entries.iter().for_each(print_log_entry);
+                    }
+                }
+                other => unexpected(other)?,
+            }
+        }
+        Command::Send {
+            id,
+            mut text,
+            enter,
+        } => {
+            if enter {
+                text.push('\r');
+            }
+            client
+                .request(&ClientMessage::Write {
+                    session_id: id,
+                    data: text,
+                })
+                .await?;
+        }
+        Command::Status => unreachable!("Status handled above"),
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn unexpected(response: ServerMessage) -> anyhow::Result<()> {
+    Err(anyhow::anyhow!("Unexpected response: {:?}", response))
+}
+
+/// Print whether the daemon is running; the exit code is failure if it isn't.
+async fn status(socket_path: &Path, json: bool) -> anyhow::Result<ExitCode> {
+    let sessions = match Client::connect(socket_path).await {
+        Ok(mut client) => {
+            client.request(&ClientMessage::Ping).await?;
+            match client.request(&ClientMessage::List).await? {
+                ServerMessage::Sessions { sessions } => Some(sessions),
+                other => {
+                    unexpected(other)?;
+                    None
+                }
+            }
+        }
+        Err(_) => None,
+    };
+
+    if json {
+        let status = serde_json::json!({
+            "running": sessions.is_some(),
+            "socket": socket_path,
+            "sessions": sessions.as_ref().map(|s| s.len()),
+            "alive": sessions.as_ref().map(|s| s.iter().filter(|s| s.alive).count()),
+        });
+        println!("{}", serde_json::to_string_pretty(&status)?);
+    } else if let Some(sessions) = &sessions {
+        let alive = sessions.iter().filter(|s| s.alive).count();
+        println!("Daemon running at {}", socket_path.display());
+        println!("{} sessions ({} alive)", sessions.len(), alive);
+    } else {
+        println!(
+            "Daemon not running (no socket at {})",
+            socket_path.display()
+        );
+    }
+
+    Ok(if sessions.is_some() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn print_sessions(sessions: &[SessionInfo]) {
+    if sessions.is_empty() {
+        println!("No sessions");
+        return;
+    }
+
+    println!(
+        "{:<24} {:<6} {:<8} {:>8} {:>10} {:>6}  CWD",
+        "ID", "ALIVE", "SIZE", "CPU", "RSS", "PROCS"
+    );
+    for s in sessions {
+        let (cpu, rss, procs) = match &s.usage {
+            Some(u) => (
+                format!("{:.1}s", u.cpu_time_ms as f64 / 1000.0),
+                format_bytes(u.rss_bytes),
+                u.process_count.to_string(),
+            ),
+            None => ("-".to_string(), "-".to_string(), "-".to_string()),
+        };
+        println!(
+            "{:<24} {:<6} {:<8} {:>8} {:>10} {:>6}  {}",
+            s.id,
+            if s.alive { "yes" } else { "no" },
+            format!("{}x{}", s.cols, s.rows),
+            cpu,
+            rss,
+            procs,
+            s.cwd.as_deref().unwrap_or("-"),
+        );
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+fn print_log_entry(entry: &LogEntry) {
+    let fields: String = entry
+        .fields
+        .iter()
+        .map(|(k, v)| match v {
+            serde_json::Value::String(s) => format!(" {}={}", k, s),
+            other => format!(" {}={}", k, other),
+        })
+        .collect();
+    println!(
+        "{} {:>5} {}: {}{}",
+        entry.timestamp, entry.level, entry.target, entry.message, fields
+    );
+}
+
+/// Puts the controlling terminal into raw mode until dropped.
+struct RawMode {
+    original: libc::termios,
+}
+
+impl RawMode {
+    /// Returns None if stdin isn't a terminal (e.g. input is piped in).
+    fn enable() -> Option<Self> {
+        // SAFETY: termios is plain data, and tcgetattr/tcsetattr only access the struct we pass
+        unsafe {
+            if libc::isatty(libc::STDIN_FILENO) == 0 {
+                return None;
+            }
+            let mut termios: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut termios) != 0 {
+                return None;
+            }
+            let original = termios;
+            libc::cfmakeraw(&mut termios);
+            if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) != 0 {
+                return None;
+            }
+            Some(Self { original })
+        }
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        // SAFETY: restores the settings read in `enable`
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original);
+        }
+    }
+}
+
+/// Size of the local terminal, if stdout is one.
+fn terminal_size() -> Option<libc::winsize> {
+    // SAFETY: TIOCGWINSZ writes a winsize into the struct we pass
+    unsafe {
+        let mut size: libc::winsize = std::mem::zeroed();
+        if libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) != 0 || size.ws_row == 0 {
+            return None;
+        }
+        Some(size)
+    }
+}
+
+fn resize_message(session_id: &str) -> Option<ClientMessage> {
+    terminal_size().map(|size| ClientMessage::Resize {
+        session_id: session_id.to_string(),
+        rows: size.ws_row,
+        cols: size.ws_col,
+        pixel_width: size.ws_xpixel,
+        pixel_height: size.ws_ypixel,
+    })
+}
+
+/// Take the text from the start of `bytes`, leaving a character that's cut
+/// off at the end for the next read to complete. Invalid bytes are replaced.
+fn take_utf8(bytes: &mut Vec<u8>) -> String {
+    let mut text = String::new();
+    let mut start = 0;
+    while start < bytes.len() {
+        match std::str::from_utf8(&bytes[start..]) {
+            Ok(valid) => {
+                text.push_str(valid);
+                start = bytes.len();
+            }
+            Err(e) => {
+                let valid_end = start + e.valid_up_to();
+                // Checked valid by from_utf8
+                text.push_str(std::str::from_utf8(&bytes[start..valid_end]).unwrap());
+                match e.error_len() {
+                    Some(len) => {
+                        text.push(char::REPLACEMENT_CHARACTER);
+                        start = valid_end + len;
+                    }
+                    None => {
+                        start = valid_end;
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    bytes.drain(..start);
+    text
+}
+
+/// Interactive client: relay stdin to the session and its output to stdout
+/// until the detach key, end of input, or the session goes away.
+async fn attach(mut client: Client, session_id: String) -> anyhow::Result<()> {
+    let buffer = match client
+        .request(&ClientMessage::Attach {
+            session_id: session_id.clone(),
+        })
+        .await?
+    {
+        ServerMessage::Attached { buffer, .. } => buffer,
+        other => return unexpected(other),
+    };
+
+    let raw_mode = RawMode::enable();
+    let mut stdout = std::io::stdout();
+    if raw_mode.is_some() {
+        eprint!("[attached to {}, press Ctrl-] to detach]\r\n", session_id);
+    }
+    stdout.write_all(buffer.as_bytes())?;
+    stdout.flush()?;
+
+    if let Some(msg) = resize_message(&session_id) {
+        client.send(&msg).await?;
+    }
+
+    // Blocking stdin reads live on their own thread so the loop below stays cancel safe
+    let (input_tx, mut input_rx) = mpsc::channel::<Vec<u8>>(16);
+    std::thread::spawn(move || {
+        let mut stdin = std::io::stdin();
+        let mut buf = [0u8; 4096];
+        loop {
+            match stdin.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    if input_tx.blocking_send(buf[..n].to_vec()).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+
+    let mut window_changes = signal(SignalKind::window_change())?;
+    // Input read so far, less what's been sent: a character split across reads
+    let mut pending = Vec::new();
+
+    loop {
+        tokio::select! {
+            input = input_rx.recv() => {
+                let Some(input) = input else {
+                    break;
+                };
+                let (data, detach) = match input.iter().position(|&b| b == DETACH_KEY) {
+                    Some(pos) => (&input[..pos], true),
+                    None => (&input[..], false),
+                };
+                pending.extend_from_slice(data);
+                let data = take_utf8(&mut pending);
+                if !data.is_empty() {
+                    client
+                        .send(&ClientMessage::Write {
+                            session_id: session_id.clone(),
+                            data,
+                        })
+                        .await?;
+                }
+                if detach {
+                    break;
+                }
+            }
+            msg = client.recv() => match msg? {
+                Some(ServerMessage::Output { data, .. }) => {
+                    stdout.write_all(data.as_bytes())?;
+                    stdout.flush()?;
+                }
+                Some(ServerMessage::Exited { .. }) | None => break,
+                Some(ServerMessage::LimitExceeded { limit, value, max, .. }) => {
+                    eprint!("\r\n[{:?} limit exceeded: {} > {}]\r\n", limit, value, max);
+                }
+                Some(ServerMessage::Error { message }) => {
+                    eprint!("\r\n[error: {}]\r\n", message);
+                }
+                // Acknowledgements of our writes and resizes
+                Some(_) => {}
+            },
+            _ = window_changes.recv() => {
+                if let Some(msg) = resize_message(&session_id) {
+                    client.send(&msg).await?;
+                }
+            }
+        }
+    }
+
+    let _ = client.send(&ClientMessage::Detach { session_id }).await;
+    if raw_mode.is_some() {
+        drop(raw_mode);
+        eprintln!("\n[detached]");
+    }
+    Ok(())
+}
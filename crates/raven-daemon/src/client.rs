@@ -0,0 +1,54 @@
+//! Client side of the daemon socket, used by the CLI subcommands.
+
+use crate::protocol::{ClientMessage, ServerMessage};
+use std::path::Path;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::unix::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::UnixStream;
+
+pub struct Client {
+    lines: Lines<BufReader<OwnedReadHalf>>,
+    writer: OwnedWriteHalf,
+}
+
+impl Client {
+    pub async fn connect(socket_path: &Path) -> anyhow::Result<Self> {
+        let stream = UnixStream::connect(socket_path).await.map_err(|e| {
+            anyhow::anyhow!("Failed to connect to daemon at {:?}: {}", socket_path, e)
+        })?;
+        let (reader, writer) = stream.into_split();
+        Ok(Self {
+            lines: BufReader::new(reader).lines(),
+            writer,
+        })
+    }
+
+    pub async fn send(&mut self, msg: &ClientMessage) -> anyhow::Result<()> {
+        let json = serde_json::to_string(msg)?;
+        self.writer.write_all(json.as_bytes()).await?;
+        self.writer.write_all(b"\n").await?;
+        self.writer.flush().await?;
+        Ok(())
+    }
+
+    /// Read the next message, or None if the daemon closed the connection.
+    /// Cancel safe, so it can be used in `select!`.
+    pub async fn recv(&mut self) -> anyhow::Result<Option<ServerMessage>> {
+        match self.lines.next_line().await? {
+            Some(line) => Ok(Some(serde_json::from_str(&line)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Send a request and wait for its response, turning `Error` responses into errors.
+    ///
+    /// Only for connections with no attached sessions, where the next message is the response.
+    pub async fn request(&mut self, msg: &ClientMessage) -> anyhow::Result<ServerMessage> {
+        self.send(msg).await?;
+        match self.recv().await? {
+            Some(ServerMessage::Error { message }) => Err(anyhow::anyhow!(message)),
+            Some(response) => Ok(response),
+            None => Err(anyhow::anyhow!("Daemon closed the connection")),
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! Structured daemon logging.
+//!
+//! The app spawns the daemon with stdout/stderr redirected to null, so logs are
+//! written as JSON lines to a daily-rotated file in the data dir, where they can
+//! be read back with a `Logs` query.
+
+use crate::protocol::LogEntry;
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tracing::Level;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, EnvFilter};
+
+const LOG_FILE_PREFIX: &str = "daemon";
+const LOG_FILE_SUFFIX: &str = "log";
+const MAX_LOG_FILES: usize = 7; // One week of daily logs
+const MAX_QUERY_ENTRIES: usize = 1000;
+
+/// Environment variable holding the log filter (e.g. `debug` or `raven_daemon::server=trace`)
+const LOG_FILTER_ENV: &str = "RAVEN_LOG";
+
+/// Install the global subscriber: JSON lines to the rotating log file, plain text to stdout.
+pub fn init(log_dir: &Path) -> anyhow::Result<()> {
+    fs::create_dir_all(log_dir)?;
+
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix(LOG_FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(log_dir)?;
+
+    let filter = EnvFilter::try_from_env(LOG_FILTER_ENV).unwrap_or_else(|_| EnvFilter::new("info"));
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer().json().with_writer(appender))
+        .with(fmt::layer().with_writer(std::io::stdout))
+        .try_init()?;
+
+    Ok(())
+}
+
+/// A log line as written by the JSON formatter
+#[derive(Deserialize)]
+struct RawEntry {
+    timestamp: String,
+    level: String,
+    target: String,
+    #[serde(default)]
+    fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl From<RawEntry> for LogEntry {
+    fn from(mut raw: RawEntry) -> Self {
+        let message = match raw.fields.remove("message") {
+            Some(serde_json::Value::String(s)) => s,
+            Some(other) => other.to_string(),
+            None => String::new(),
+        };
+        LogEntry {
+            timestamp: raw.timestamp,
+            level: raw.level,
+            target: raw.target,
+            message,
+            fields: raw.fields,
+        }
+    }
+}
+
+/// Read back log entries, oldest first.
+///
+/// `since` is an RFC 3339 timestamp; entries before it are skipped.
+/// `level` is the least severe level to include (e.g. "warn" returns warnings and errors).
+/// At most the newest `MAX_QUERY_ENTRIES` entries are returned.
+pub fn query(
+    log_dir: &Path,
+    since: Option<&str>,
+    level: Option<&str>,
+) -> Result<Vec<LogEntry>, String> {
+    let max_level = level
+        .map(|l| Level::from_str(l).map_err(|_| format!("Invalid log level: {}", l)))
+        .transpose()?;
+    let since = since
+        .map(|s| parse_timestamp(s).ok_or_else(|| format!("Invalid timestamp: {}", s)))
+        .transpose()?;
+
+    let mut entries = VecDeque::new();
+    for (path, date) in log_files(log_dir)? {
+        // A file holds one UTC day, so it's all older than `since` if the
+        // next day starts by then
+        if let (Some(since), Some(date)) = (since, date) {
+            if date + NANOS_PER_DAY <= since {
+                continue;
+            }
+        }
+        let Ok(file) = fs::File::open(&path) else {
+            continue;
+        };
+
+        for line in BufReader::new(file).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            let Ok(raw) = serde_json::from_str::<RawEntry>(&line) else {
+                continue;
+            };
+
+            if let Some(since) = since {
+                if parse_timestamp(&raw.timestamp).is_none_or(|t| t < since) {
+                    continue;
+                }
+            }
+
+            if let Some(max_level) = max_level {
+                match Level::from_str(&raw.level) {
+                    Ok(entry_level) if entry_level <= max_level => {}
+                    _ => continue,
+                }
+            }
+
+            if entries.len() == MAX_QUERY_ENTRIES {
+                entries.pop_front();
+            }
+            entries.push_back(LogEntry::from(raw));
+        }
+    }
+
+    Ok(entries.into())
+}
+
+const NANOS_PER_DAY: i128 = 86_400 * 1_000_000_000;
+
+/// Parse an RFC 3339 timestamp (e.g. `2026-01-01T12:00:00.5+01:00`) into
+/// nanoseconds since the Unix epoch.
+fn parse_timestamp(s: &str) -> Option<i128> {
+    fn number(s: &str, range: std::ops::RangeInclusive<i64>) -> Option<i64> {
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        s.parse().ok().filter(|n| range.contains(n))
+    }
+
+    // So fields can be sliced by byte
+    if !s.is_ascii() {
+        return None;
+    }
+    let (date, time) = s.split_once(['T', 't', ' '])?;
+    if date.len() != 10 || &date[4..5] != "-" || &date[7..8] != "-" {
+        return None;
+    }
+    let year = number(&date[..4], 0..=9999)?;
+    let month = number(&date[5..7], 1..=12)?;
+    let day = number(&date[8..], 1..=days_in_month(year, month))?;
+
+    if time.len() < 8 || &time[2..3] != ":" || &time[5..6] != ":" {
+        return None;
+    }
+    let hour = number(&time[..2], 0..=23)?;
+    let minute = number(&time[3..5], 0..=59)?;
+    // Allows for leap seconds
+    let second = number(&time[6..8], 0..=60)?;
+    let mut rest = &time[8..];
+
+    let mut nanos = 0;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits = fraction
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(fraction.len());
+        if digits == 0 {
+            return None;
+        }
+        // Digits past nanoseconds are dropped
+        let kept = &fraction[..digits.min(9)];
+        nanos = number(kept, 0..=999_999_999)? * 10_i64.pow(9 - kept.len() as u32);
+        rest = &fraction[digits..];
+    }
+
+    let offset = match rest {
+        "Z" | "z" => 0,
+        _ => {
+            let sign = match rest.as_bytes().first()? {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            if rest.len() != 6 || &rest[3..4] != ":" {
+                return None;
+            }
+            sign * (number(&rest[1..3], 0..=23)? * 3600 + number(&rest[4..], 0..=59)? * 60)
+        }
+    };
+
+    let seconds =
+        days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60 + second - offset;
+    Some(seconds as i128 * 1_000_000_000 + nanos as i128)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 of a date in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    // Count years from March, so the leap day comes last
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Log files in the directory, oldest first, with the start of the day they
+/// hold if their name says (e.g. `daemon.2026-01-01.log`).
+fn log_files(log_dir: &Path) -> Result<Vec<(PathBuf, Option<i128>)>, String> {
+    let read_dir = match fs::read_dir(log_dir) {
+        Ok(rd) => rd,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read log directory: {}", e)),
+    };
+
+    let prefix = format!("{}.", LOG_FILE_PREFIX);
+    let suffix = format!(".{}", LOG_FILE_SUFFIX);
+    let mut files: Vec<(PathBuf, Option<i128>)> = read_dir
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?;
+            if !name.starts_with(&prefix) || !name.ends_with(&suffix) {
+                return None;
+            }
+            let start = name
+                .get(prefix.len()..name.len() - suffix.len())
+                .and_then(|date| parse_timestamp(&format!("{}T00:00:00Z", date)));
+            Some((path, start))
+        })
+        .collect();
+    files.sort();
+
+    Ok(files)
+}
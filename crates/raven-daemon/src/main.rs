@@ -1,11 +1,16 @@
+mod cli;
+mod client;
+mod logs;
 mod protocol;
+mod resources;
 mod server;
 mod session;
 
+use clap::Parser;
 use directories::ProjectDirs;
 use std::path::PathBuf;
-use tracing::{info, Level};
-use tracing_subscriber::FmtSubscriber;
+use std::process::ExitCode;
+use tracing::info;
 
 fn get_socket_path() -> PathBuf {
     // Allow overriding socket path via environment (for testing)
@@ -22,13 +27,29 @@ fn get_socket_path() -> PathBuf {
     }
 }
 
+fn get_log_dir() -> PathBuf {
+    // Allow overriding log directory via environment (for testing)
+    if let Ok(path) = std::env::var("RAVEN_LOG_DIR") {
+        return PathBuf::from(path);
+    }
+
+    if let Some(proj_dirs) = ProjectDirs::from("com", "innocencelabs", "raven") {
+        proj_dirs.data_dir().join("logs")
+    } else {
+        PathBuf::from("/tmp/raven-daemon-logs")
+    }
+}
+
 #[tokio::main]
-async fn main() -> anyhow::Result<()> {
+async fn main() -> anyhow::Result<ExitCode> {
+    let cli = cli::Cli::parse();
+    if let Some(command) = cli.command {
+        return cli::run(command, cli.json, &get_socket_path()).await;
+    }
+
     // Set up logging
-    let subscriber = FmtSubscriber::builder()
-        .with_max_level(Level::INFO)
-        .finish();
-    tracing::subscriber::set_global_default(subscriber)?;
+    let log_dir = get_log_dir();
+    logs::init(&log_dir)?;
 
     let socket_path = get_socket_path();
     info!("Starting raven-daemon at {:?}", socket_path);
@@ -38,5 +59,6 @@ async fn main() -> anyhow::Result<()> {
         std::fs::remove_file(&socket_path)?;
     }
 
-    server::run(socket_path).await
+    server::run(socket_path, log_dir).await?;
+    Ok(ExitCode::SUCCESS)
 }
@@ -10,6 +10,14 @@ pub enum ClientMessage {
         cwd: Option<String>,
         rows: u16,
         cols: u16,
+        /// Size of the terminal's text area in pixels (0 if unknown)
+        #[serde(default)]
+        pixel_width: u16,
+        #[serde(default)]
+        pixel_height: u16,
+        /// Optional resource limits for the session's process tree
+        #[serde(default)]
+        limits: Option<ResourceLimits>,
     },
     /// Write data to a session
     Write { session_id: String, data: String },
@@ -18,7 +26,14 @@ pub enum ClientMessage {
         session_id: String,
         rows: u16,
         cols: u16,
+        /// Size of the terminal's text area in pixels (0 if unknown)
+        #[serde(default)]
+        pixel_width: u16,
+        #[serde(default)]
+        pixel_height: u16,
     },
+    /// Send a signal (e.g. "SIGINT", "SIGTSTP", "SIGKILL") to a session's foreground process group
+    Signal { session_id: String, signal: String },
     /// Attach to an existing session (get current buffer + subscribe to output)
     Attach { session_id: String },
     /// Detach from a session (stop receiving output)
@@ -27,6 +42,13 @@ pub enum ClientMessage {
     Kill { session_id: String },
     /// List all sessions
     List,
+    /// Query the daemon's log file
+    Logs {
+        /// Only entries at or after this RFC 3339 timestamp
+        since: Option<String>,
+        /// Least severe level to include ("error", "warn", "info", "debug", "trace")
+        level: Option<String>,
+    },
     /// Ping (keepalive)
     Ping,
 }
@@ -44,6 +66,14 @@ pub enum ServerMessage {
         session_id: String,
         exit_code: Option<i32>,
     },
+    /// A session's process tree hit one of its resource limits
+    LimitExceeded {
+        session_id: String,
+        limit: LimitKind,
+        /// Usage when the limit was hit (bytes or process count)
+        value: u64,
+        max: u64,
+    },
     /// Attached to session, includes current buffer
     Attached {
         session_id: String,
@@ -53,6 +83,8 @@ pub enum ServerMessage {
     },
     /// List of sessions
     Sessions { sessions: Vec<SessionInfo> },
+    /// Log entries, oldest first
+    Logs { entries: Vec<LogEntry> },
     /// Error occurred
     Error { message: String },
     /// Pong (keepalive response)
@@ -68,4 +100,43 @@ pub struct SessionInfo {
     pub rows: u16,
     pub cols: u16,
     pub alive: bool,
+    /// Resource usage summed over the session's process tree (where measurable)
+    #[serde(default)]
+    pub usage: Option<ResourceUsage>,
+    #[serde(default)]
+    pub limits: Option<ResourceLimits>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    /// User + system CPU time
+    pub cpu_time_ms: u64,
+    /// Resident set size
+    pub rss_bytes: u64,
+    pub process_count: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    pub max_memory_bytes: Option<u64>,
+    pub max_processes: Option<u64>,
+    /// Scheduling priority for the shell (-20 to 19; children inherit it)
+    pub nice: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LimitKind {
+    Memory,
+    Processes,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub timestamp: String,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    /// Structured fields recorded alongside the message
+    pub fields: serde_json::Map<String, serde_json::Value>,
 }
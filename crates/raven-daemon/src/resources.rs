@@ -0,0 +1,424 @@
+//! Resource accounting and limits for session process trees.
+//!
+//! Usage is summed over the shell and all of its descendants by walking /proc,
+//! so it is only available on Linux. Limits are enforced with a cgroup v2 child
+//! group when the daemon's own cgroup allows it, falling back to rlimits. For
+//! its cgroup to enable the memory and pids controllers for session groups,
+//! the daemon may move itself into a `raven-daemon` leaf group.
+
+use crate::protocol::{ResourceLimits, ResourceUsage};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// Current usage of the process tree rooted at `pid`, if it can be measured.
+pub fn usage(pid: u32) -> Option<ResourceUsage> {
+    usages(&[pid]).pop().flatten()
+}
+
+/// Current usage of the process trees rooted at each of `pids`, reading /proc
+/// once for all of them.
+#[cfg(target_os = "linux")]
+pub fn usages(pids: &[u32]) -> Vec<Option<ResourceUsage>> {
+    let stats = linux::all_proc_stats();
+    let ticks_per_sec = linux::clock_ticks_per_sec();
+    let page_size = linux::page_size();
+
+    pids.iter()
+        .map(|&pid| {
+            let tree = linux::process_tree(pid, &stats);
+            if tree.is_empty() {
+                return None;
+            }
+            let cpu_ticks: u64 = tree.iter().map(|s| s.cpu_ticks).sum();
+            let rss_pages: u64 = tree.iter().map(|s| s.rss_pages).sum();
+            Some(ResourceUsage {
+                cpu_time_ms: cpu_ticks * 1000 / ticks_per_sec,
+                rss_bytes: rss_pages * page_size,
+                process_count: tree.len() as u32,
+            })
+        })
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn usages(pids: &[u32]) -> Vec<Option<ResourceUsage>> {
+    vec![None; pids.len()]
+}
+
+/// How limits are being enforced for a session.
+#[derive(Debug)]
+pub enum Enforcement {
+    /// A dedicated cgroup v2 group, which the shell joins before it runs
+    /// anything, so its whole process tree is inside
+    Cgroup(PathBuf),
+    /// Per-process rlimits set on the shell and inherited by its children
+    Rlimit,
+    /// Limits could not be enforced on this platform
+    None,
+}
+
+/// Work out how to enforce `limits` for a session about to be spawned,
+/// creating its cgroup if one can be used.
+pub fn prepare_limits(session_id: &str, limits: &ResourceLimits) -> Enforcement {
+    if limits.max_memory_bytes.is_none() && limits.max_processes.is_none() {
+        return Enforcement::None;
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        match linux::create_cgroup(session_id, limits) {
+            Some(dir) => Enforcement::Cgroup(dir),
+            None => Enforcement::Rlimit,
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        warn!(
+            session_id,
+            "Memory and process limits are not supported on this platform"
+        );
+        Enforcement::None
+    }
+}
+
+/// Apply what's left of `limits` to the freshly spawned shell `pid`: its
+/// priority, and rlimits when there's no cgroup.
+pub fn apply_limits(
+    session_id: &str,
+    pid: u32,
+    limits: &ResourceLimits,
+    enforcement: &Enforcement,
+) {
+    if let Some(nice) = limits.nice {
+        // SAFETY: setpriority has no memory-safety preconditions
+        let result = unsafe { libc::setpriority(libc::PRIO_PROCESS, pid as libc::id_t, nice) };
+        if result != 0 {
+            warn!(session_id, nice, "Failed to set session priority");
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    if let Enforcement::Rlimit = enforcement {
+        linux::set_rlimits(session_id, pid, limits);
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = enforcement;
+}
+
+/// Number of times each limit has been hit according to the cgroup's event counters,
+/// as `(memory, processes)`.
+pub fn cgroup_limit_hits(dir: &Path) -> (u64, u64) {
+    let memory = read_event_counter(&dir.join("memory.events"), "max")
+        + read_event_counter(&dir.join("memory.events"), "oom_kill");
+    let processes = read_event_counter(&dir.join("pids.events"), "max");
+    (memory, processes)
+}
+
+fn read_event_counter(path: &Path, key: &str) -> u64 {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| {
+            content.lines().find_map(|line| {
+                let (k, v) = line.split_once(' ')?;
+                (k == key).then(|| v.trim().parse().ok()).flatten()
+            })
+        })
+        .unwrap_or(0)
+}
+
+/// How long to wait for a cgroup's processes to exit before removing it
+const CGROUP_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Kill whatever is left in a session's cgroup and remove it. A cgroup can only
+/// be removed once it has no live processes, so this waits for them to exit.
+pub fn remove_cgroup(dir: &Path) {
+    if write_control(&dir.join("cgroup.kill"), "1").is_err() {
+        // cgroup.kill needs Linux 5.14; signal each process instead
+        let procs = std::fs::read_to_string(dir.join("cgroup.procs")).unwrap_or_default();
+        for pid in procs
+            .lines()
+            .filter_map(|l| l.trim().parse::<libc::pid_t>().ok())
+        {
+            // SAFETY: kill has no memory-safety preconditions
+            unsafe { libc::kill(pid, libc::SIGKILL) };
+        }
+    }
+
+    let deadline = Instant::now() + CGROUP_DRAIN_TIMEOUT;
+    while read_event_counter(&dir.join("cgroup.events"), "populated") != 0
+        && Instant::now() < deadline
+    {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    if let Err(e) = std::fs::remove_dir(dir) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            warn!(cgroup = %dir.display(), error = %e, "Failed to remove session cgroup");
+        }
+    }
+}
+
+/// Write to an existing control file; cgroup control files can't be created.
+fn write_control(path: &Path, value: &str) -> std::io::Result<()> {
+    OpenOptions::new()
+        .write(true)
+        .open(path)?
+        .write_all(value.as_bytes())
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::{remove_cgroup, write_control};
+    use crate::protocol::ResourceLimits;
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::sync::OnceLock;
+    use tracing::warn;
+
+    const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+    /// Controllers session groups need
+    const CONTROLLERS: [&str; 2] = ["memory", "pids"];
+    /// Leaf group the daemon moves into when its own group has to enable
+    /// controllers for session groups
+    const DAEMON_CGROUP: &str = "raven-daemon";
+    /// Start of session group names
+    const SESSION_CGROUP_PREFIX: &str = "raven-session-";
+
+    pub struct ProcStat {
+        pub pid: u32,
+        pub ppid: u32,
+        /// User + system time, including reaped children
+        pub cpu_ticks: u64,
+        pub rss_pages: u64,
+    }
+
+    fn read_proc_stat(pid: u32) -> Option<ProcStat> {
+        let content = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+        // The command name is in parentheses and may contain spaces
+        let rest = content.get(content.rfind(')')? + 2..)?;
+        let fields: Vec<&str> = rest.split_whitespace().collect();
+        let field = |n: usize| -> Option<u64> { fields.get(n - 3)?.parse().ok() };
+
+        Some(ProcStat {
+            pid,
+            ppid: field(4)? as u32,
+            cpu_ticks: field(14)? + field(15)? + field(16)? + field(17)?,
+            rss_pages: field(24)?,
+        })
+    }
+
+    pub fn all_proc_stats() -> Vec<ProcStat> {
+        let Ok(entries) = fs::read_dir("/proc") else {
+            return Vec::new();
+        };
+        entries
+            .filter_map(|e| e.ok())
+            .filter_map(|e| e.file_name().to_str()?.parse::<u32>().ok())
+            .filter_map(read_proc_stat)
+            .collect()
+    }
+
+    /// The process `root` and all of its descendants.
+    pub fn process_tree(root: u32, stats: &[ProcStat]) -> Vec<&ProcStat> {
+        let mut children: HashMap<u32, Vec<&ProcStat>> = HashMap::new();
+        let mut root_stat = None;
+        for stat in stats {
+            if stat.pid == root {
+                root_stat = Some(stat);
+            }
+            children.entry(stat.ppid).or_default().push(stat);
+        }
+
+        let mut tree: Vec<&ProcStat> = root_stat.into_iter().collect();
+        let mut i = 0;
+        while i < tree.len() {
+            if let Some(kids) = children.get(&tree[i].pid) {
+                tree.extend(kids);
+            }
+            i += 1;
+        }
+        tree
+    }
+
+    pub fn clock_ticks_per_sec() -> u64 {
+        // SAFETY: sysconf has no memory-safety preconditions
+        let ticks = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+        if ticks > 0 {
+            ticks as u64
+        } else {
+            100
+        }
+    }
+
+    pub fn page_size() -> u64 {
+        // SAFETY: sysconf has no memory-safety preconditions
+        let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+        if size > 0 {
+            size as u64
+        } else {
+            4096
+        }
+    }
+
+    /// The cgroup session groups are created in, with the memory and pids
+    /// controllers enabled for its children. Worked out on first use; `None`
+    /// (with a warning saying why) if limits can't use cgroups.
+    fn sessions_parent() -> Option<&'static Path> {
+        static PARENT: OnceLock<Option<PathBuf>> = OnceLock::new();
+        PARENT
+            .get_or_init(|| match delegate() {
+                Ok(parent) => {
+                    remove_stale_cgroups(&parent);
+                    Some(parent)
+                }
+                Err(reason) => {
+                    warn!(
+                        %reason,
+                        "cgroup limits aren't available; falling back to per-process rlimits"
+                    );
+                    None
+                }
+            })
+            .as_deref()
+    }
+
+    /// Enable the memory and pids controllers for children of the daemon's
+    /// cgroup, returning it.
+    fn delegate() -> Result<PathBuf, String> {
+        let own = fs::read_to_string("/proc/self/cgroup")
+            .map_err(|e| format!("can't read /proc/self/cgroup: {}", e))?;
+        let relative = own
+            .lines()
+            .find_map(|l| l.strip_prefix("0::"))
+            .ok_or("cgroup v2 isn't in use")?;
+        let mut parent = Path::new(CGROUP_ROOT).join(relative.trim_start_matches('/'));
+        // Already moved into its leaf by an earlier call in this process
+        if parent.ends_with(DAEMON_CGROUP) {
+            parent.pop();
+        }
+        let available = fs::read_to_string(parent.join("cgroup.controllers"))
+            .map_err(|_| format!("{} isn't a cgroup v2 group", parent.display()))?;
+        for controller in CONTROLLERS {
+            if !available.split_whitespace().any(|c| c == controller) {
+                return Err(format!(
+                    "the {} controller isn't delegated to {}",
+                    controller,
+                    parent.display()
+                ));
+            }
+        }
+
+        let subtree_control = parent.join("cgroup.subtree_control");
+        let enable = CONTROLLERS.map(|c| format!("+{}", c)).join(" ");
+        if write_control(&subtree_control, &enable).is_err() {
+            // A group with processes of its own can't enable controllers for
+            // its children, so the daemon moves into a leaf group first
+            let leaf = parent.join(DAEMON_CGROUP);
+            match fs::create_dir(&leaf) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(format!("can't create {}: {}", leaf.display(), e)),
+            }
+            write_control(&leaf.join("cgroup.procs"), &std::process::id().to_string())
+                .map_err(|e| format!("can't move the daemon into {}: {}", leaf.display(), e))?;
+            write_control(&subtree_control, &enable).map_err(|e| {
+                format!(
+                    "can't enable the memory and pids controllers in {}: {}",
+                    parent.display(),
+                    e
+                )
+            })?;
+        }
+        Ok(parent)
+    }
+
+    /// Remove session groups left behind by an earlier daemon.
+    fn remove_stale_cgroups(parent: &Path) {
+        let Ok(entries) = fs::read_dir(parent) else {
+            return;
+        };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let name = entry.file_name();
+            if name.to_string_lossy().starts_with(SESSION_CGROUP_PREFIX) {
+                remove_cgroup(&entry.path());
+            }
+        }
+    }
+
+    /// Create and configure a cgroup for a session. The shell joins it
+    /// itself before running anything. Returns None if cgroups can't be used.
+    pub fn create_cgroup(session_id: &str, limits: &ResourceLimits) -> Option<PathBuf> {
+        let parent = sessions_parent()?;
+
+        let name: String = session_id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        // Unique per spawn, so a killed session's group being cleaned up in
+        // the background can't be mistaken for a respawned one's
+        let dir = parent.join(format!(
+            "{}{}-{}",
+            SESSION_CGROUP_PREFIX,
+            name,
+            uuid::Uuid::new_v4().simple()
+        ));
+        if let Err(e) = fs::create_dir(&dir) {
+            warn!(session_id, cgroup = %dir.display(), error = %e, "Failed to create session cgroup");
+            return None;
+        }
+
+        let configure = || -> std::io::Result<()> {
+            if let Some(max) = limits.max_memory_bytes {
+                write_control(&dir.join("memory.max"), &max.to_string())?;
+            }
+            if let Some(max) = limits.max_processes {
+                write_control(&dir.join("pids.max"), &max.to_string())?;
+            }
+            Ok(())
+        };
+
+        match configure() {
+            Ok(()) => Some(dir),
+            Err(e) => {
+                warn!(session_id, cgroup = %dir.display(), error = %e, "Failed to configure session cgroup");
+                let _ = fs::remove_dir(&dir);
+                None
+            }
+        }
+    }
+
+    /// Set rlimits on the shell. Note these are weaker than a cgroup: the memory limit
+    /// is per process and the process limit counts all of the user's processes.
+    pub fn set_rlimits(session_id: &str, pid: u32, limits: &ResourceLimits) {
+        let set = |resource, max: u64| {
+            let limit = libc::rlimit {
+                rlim_cur: max as libc::rlim_t,
+                rlim_max: max as libc::rlim_t,
+            };
+            // SAFETY: `limit` is a valid rlimit and the old-limit pointer may be null
+            unsafe { libc::prlimit(pid as libc::pid_t, resource, &limit, std::ptr::null_mut()) }
+        };
+
+        if let Some(max) = limits.max_memory_bytes {
+            if set(libc::RLIMIT_AS, max) != 0 {
+                warn!(session_id, "Failed to set memory rlimit");
+            }
+        }
+        if let Some(max) = limits.max_processes {
+            if set(libc::RLIMIT_NPROC, max) != 0 {
+                warn!(session_id, "Failed to set process rlimit");
+            }
+        }
+    }
+}
@@ -1,12 +1,14 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::net::{UnixListener, UnixStream};
 use tokio::sync::Mutex;
 use tracing::{error, info, warn};
+use crate::logs;
 use crate::protocol::{ClientMessage, ServerMessage};
-use crate::session::SessionManager;
+use crate::session::{SessionEvent, SessionManager};
+use portable_pty::PtySize;
 use tokio::sync::mpsc;
 
 /// Per-client state tracking which sessions they're attached to
@@ -23,9 +25,10 @@ impl ClientState {
     }
 }
 
-pub async fn run(socket_path: PathBuf) -> anyhow::Result<()> {
+pub async fn run(socket_path: PathBuf, log_dir: PathBuf) -> anyhow::Result<()> {
     let listener = UnixListener::bind(&socket_path)?;
     let manager = Arc::new(SessionManager::new());
+    let log_dir = Arc::new(log_dir);
 
     info!("Daemon listening on {:?}", socket_path);
 
@@ -33,8 +36,9 @@ pub async fn run(socket_path: PathBuf) -> anyhow::Result<()> {
         match listener.accept().await {
             Ok((stream, _)) => {
                 let manager = manager.clone();
+                let log_dir = log_dir.clone();
                 tokio::spawn(async move {
-                    if let Err(e) = handle_client(stream, manager).await {
+                    if let Err(e) = handle_client(stream, manager, log_dir).await {
                         error!("Client error: {}", e);
                     }
                 });
@@ -46,7 +50,11 @@ pub async fn run(socket_path: PathBuf) -> anyhow::Result<()> {
     }
 }
 
-async fn handle_client(stream: UnixStream, manager: Arc<SessionManager>) -> anyhow::Result<()> {
+async fn handle_client(
+    stream: UnixStream,
+    manager: Arc<SessionManager>,
+    log_dir: Arc<PathBuf>,
+) -> anyhow::Result<()> {
     let (reader, writer) = stream.into_split();
     let mut reader = BufReader::new(reader);
     let writer = Arc::new(Mutex::new(writer));
@@ -128,10 +136,20 @@ async fn handle_client(stream: UnixStream, manager: Arc<SessionManager>) -> anyh
                                     // Process output from PTY
                                     result = rx.recv() => {
                                         match result {
-                                            Ok(data) => {
-                                                let msg = ServerMessage::Output {
-                                                    session_id: session_id_clone.clone(),
-                                                    data,
+                                            Ok(event) => {
+                                                let msg = match event {
+                                                    SessionEvent::Output(data) => ServerMessage::Output {
+                                                        session_id: session_id_clone.clone(),
+                                                        data,
+                                                    },
+                                                    SessionEvent::LimitExceeded { limit, value, max } => {
+                                                        ServerMessage::LimitExceeded {
+                                                            session_id: session_id_clone.clone(),
+                                                            limit,
+                                                            value,
+                                                            max,
+                                                        }
+                                                    }
                                                 };
                                                 let mut w = writer.lock().await;
                                                 if send_message(&mut w, &msg).await.is_err() {
@@ -174,7 +192,7 @@ async fn handle_client(stream: UnixStream, manager: Arc<SessionManager>) -> anyh
             continue;
         }
 
-        let response = handle_message(msg, &manager).await;
+        let response = handle_message(msg, &manager, &log_dir).await;
         let mut w = writer.lock().await;
         send_message(&mut w, &response).await?;
     }
@@ -188,17 +206,32 @@ async fn handle_client(stream: UnixStream, manager: Arc<SessionManager>) -> anyh
     Ok(())
 }
 
-async fn handle_message(msg: ClientMessage, manager: &SessionManager) -> ServerMessage {
+async fn handle_message(
+    msg: ClientMessage,
+    manager: &SessionManager,
+    log_dir: &Path,
+) -> ServerMessage {
     match msg {
         ClientMessage::Spawn {
             session_id,
             cwd,
             rows,
             cols,
-        } => match manager.spawn(session_id.clone(), cwd, rows, cols) {
-            Ok(()) => ServerMessage::Spawned { session_id },
-            Err(e) => ServerMessage::Error { message: e },
-        },
+            pixel_width,
+            pixel_height,
+            limits,
+        } => {
+            let size = PtySize {
+                rows,
+                cols,
+                pixel_width,
+                pixel_height,
+            };
+            match manager.spawn(session_id.clone(), cwd, size, limits) {
+                Ok(()) => ServerMessage::Spawned { session_id },
+                Err(e) => ServerMessage::Error { message: e },
+            }
+        }
         ClientMessage::Write { session_id, data } => match manager.write(&session_id, &data) {
             Ok(()) => ServerMessage::Ok,
             Err(e) => ServerMessage::Error { message: e },
@@ -207,10 +240,26 @@ async fn handle_message(msg: ClientMessage, manager: &SessionManager) -> ServerM
             session_id,
             rows,
             cols,
-        } => match manager.resize(&session_id, rows, cols) {
-            Ok(()) => ServerMessage::Ok,
-            Err(e) => ServerMessage::Error { message: e },
-        },
+            pixel_width,
+            pixel_height,
+        } => {
+            let size = PtySize {
+                rows,
+                cols,
+                pixel_width,
+                pixel_height,
+            };
+            match manager.resize(&session_id, size) {
+                Ok(()) => ServerMessage::Ok,
+                Err(e) => ServerMessage::Error { message: e },
+            }
+        }
+        ClientMessage::Signal { session_id, signal } => {
+            match manager.signal(&session_id, &signal) {
+                Ok(()) => ServerMessage::Ok,
+                Err(e) => ServerMessage::Error { message: e },
+            }
+        }
         // Attach and Detach are handled specially in handle_client
         ClientMessage::Attach { .. } | ClientMessage::Detach { .. } => {
             unreachable!("Attach/Detach handled in handle_client")
@@ -222,6 +271,12 @@ async fn handle_message(msg: ClientMessage, manager: &SessionManager) -> ServerM
         ClientMessage::List => ServerMessage::Sessions {
             sessions: manager.list(),
         },
+        ClientMessage::Logs { since, level } => {
+            match logs::query(log_dir, since.as_deref(), level.as_deref()) {
+                Ok(entries) => ServerMessage::Logs { entries },
+                Err(e) => ServerMessage::Error { message: e },
+            }
+        }
         ClientMessage::Ping => ServerMessage::Pong,
     }
 }
@@ -2,13 +2,28 @@ use parking_lot::Mutex;
 use portable_pty::{native_pty_system, CommandBuilder, MasterPty, PtySize};
 use std::collections::HashMap;
 use std::io::{Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::broadcast;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
-use crate::protocol::SessionInfo;
+use crate::protocol::{LimitKind, ResourceLimits, SessionInfo};
+use crate::resources::{self, Enforcement};
 
 const BUFFER_SIZE: usize = 64 * 1024; // 64KB scrollback per session
+const LIMIT_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Events broadcast to clients attached to a session
+#[derive(Debug, Clone)]
+pub enum SessionEvent {
+    Output(String),
+    LimitExceeded {
+        limit: LimitKind,
+        value: u64,
+        max: u64,
+    },
+}
 
 pub struct Session {
     pub id: String,
@@ -16,34 +31,59 @@ pub struct Session {
     pub rows: u16,
     pub cols: u16,
     pub alive: bool,
+    pid: Option<u32>,
+    limits: Option<ResourceLimits>,
+    enforcement: Arc<Enforcement>,
     master: Box<dyn MasterPty + Send>,
     writer: Box<dyn Write + Send>,
     buffer: Arc<Mutex<String>>,
-    output_tx: broadcast::Sender<String>,
+    output_tx: broadcast::Sender<SessionEvent>,
+    /// Set when the session is dropped to stop the limit monitor
+    closed: Arc<AtomicBool>,
 }
 
 impl Session {
-    pub fn spawn(id: String, cwd: Option<String>, rows: u16, cols: u16) -> Result<Self, String> {
+    pub fn spawn(
+        id: String,
+        cwd: Option<String>,
+        size: PtySize,
+        limits: Option<ResourceLimits>,
+    ) -> Result<Self, String> {
         let pty_system = native_pty_system();
 
-        let pair = pty_system
-            .openpty(PtySize {
-                rows,
-                cols,
-                pixel_width: 0,
-                pixel_height: 0,
-            })
-            .map_err(|e| e.to_string())?;
+        let pair = pty_system.openpty(size).map_err(|e| e.to_string())?;
 
         let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/zsh".to_string());
-        let mut cmd = CommandBuilder::new(&shell);
-        cmd.arg("-l");
+        let enforcement = match &limits {
+            Some(limits) => resources::prepare_limits(&id, limits),
+            None => Enforcement::None,
+        };
+        let mut cmd = shell_command(&shell, &enforcement);
 
         if let Some(ref dir) = cwd {
             cmd.cwd(dir);
         }
 
-        let mut child = pair.slave.spawn_command(cmd).map_err(|e| e.to_string())?;
+        let mut child = match pair.slave.spawn_command(cmd) {
+            Ok(child) => child,
+            Err(e) => {
+                if let Enforcement::Cgroup(dir) = &enforcement {
+                    resources::remove_cgroup(dir);
+                }
+                return Err(e.to_string());
+            }
+        };
+        let pid = child.process_id();
+
+        match (&limits, pid) {
+            (Some(limits), Some(pid)) => resources::apply_limits(&id, pid, limits, &enforcement),
+            (Some(_), None) => {
+                warn!(session_id = %id, "Cannot apply limits without a child pid");
+            }
+            _ => {}
+        }
+        let enforcement = Arc::new(enforcement);
+        let closed = Arc::new(AtomicBool::new(false));
 
         let writer = pair.master.take_writer().map_err(|e| e.to_string())?;
         let mut reader = pair.master.try_clone_reader().map_err(|e| e.to_string())?;
@@ -81,7 +121,7 @@ impl Session {
                         }
 
                         // Broadcast to attached clients
-                        let _ = output_tx_clone.send(data);
+                        let _ = output_tx_clone.send(SessionEvent::Output(data));
                     }
                     Err(e) => {
                         error!("Read error for session {}: {}", id_clone, e);
@@ -103,16 +143,32 @@ impl Session {
             }
         });
 
+        // Spawn thread to report limit violations
+        if let (Some(limits), Some(pid)) = (limits.clone(), pid) {
+            if limits.max_memory_bytes.is_some() || limits.max_processes.is_some() {
+                let enforcement = enforcement.clone();
+                let output_tx = output_tx.clone();
+                let closed = closed.clone();
+                std::thread::spawn(move || {
+                    monitor_limits(pid, &limits, &enforcement, &output_tx, &closed)
+                });
+            }
+        }
+
         Ok(Session {
             id,
             cwd,
-            rows,
-            cols,
+            rows: size.rows,
+            cols: size.cols,
             alive: true,
+            pid,
+            limits,
+            enforcement,
             master: pair.master,
             writer,
             buffer,
             output_tx,
+            closed,
         })
     }
 
@@ -124,25 +180,41 @@ impl Session {
         Ok(())
     }
 
-    pub fn resize(&self, rows: u16, cols: u16) -> Result<(), String> {
-        self.master
-            .resize(PtySize {
-                rows,
-                cols,
-                pixel_width: 0,
-                pixel_height: 0,
-            })
-            .map_err(|e| e.to_string())
+    pub fn resize(&self, size: PtySize) -> Result<(), String> {
+        self.master.resize(size).map_err(|e| e.to_string())
+    }
+
+    /// Send a signal to the session's foreground process group (falling back to the shell),
+    /// so programs that put the tty in raw mode can still be interrupted.
+    pub fn signal(&self, signal: &str) -> Result<(), String> {
+        let signum = signal_number(signal)?;
+        let pgrp = self
+            .master
+            .process_group_leader()
+            .or(self.pid.map(|pid| pid as libc::pid_t))
+            .ok_or("Session has no process to signal")?;
+
+        // SAFETY: killpg has no memory-safety preconditions
+        if unsafe { libc::killpg(pgrp, signum) } != 0 {
+            return Err(format!(
+                "Failed to send {}: {}",
+                signal,
+                std::io::Error::last_os_error()
+            ));
+        }
+        Ok(())
     }
 
     pub fn get_buffer(&self) -> String {
         self.buffer.lock().clone()
     }
 
-    pub fn subscribe(&self) -> broadcast::Receiver<String> {
+    pub fn subscribe(&self) -> broadcast::Receiver<SessionEvent> {
         self.output_tx.subscribe()
     }
 
+    /// The session's info, without its usage, which takes a walk of /proc to
+    /// measure: see `with_usage`.
     pub fn info(&self) -> SessionInfo {
         SessionInfo {
             id: self.id.clone(),
@@ -150,6 +222,147 @@ impl Session {
             rows: self.rows,
             cols: self.cols,
             alive: self.alive,
+            usage: None,
+            limits: self.limits.clone(),
+        }
+    }
+}
+
+/// Fill in the usage of sessions' process trees, given with their shells'
+/// pids. Done outside the sessions lock, as it reads all of /proc.
+fn with_usage(sessions: Vec<(SessionInfo, Option<u32>)>) -> Vec<SessionInfo> {
+    let pids: Vec<u32> = sessions.iter().filter_map(|(_, pid)| *pid).collect();
+    let mut usages = resources::usages(&pids).into_iter();
+    sessions
+        .into_iter()
+        .map(|(mut info, pid)| {
+            if pid.is_some() {
+                info.usage = usages.next().flatten();
+            }
+            info
+        })
+        .collect()
+}
+
+/// Drop a session taken out of the sessions map. With a cgroup this waits for
+/// its processes to exit, so it's done off the async runtime's threads.
+fn close_in_background(session: Session) {
+    tokio::task::spawn_blocking(move || drop(session));
+}
+
+/// The command starting a session's shell. With a cgroup, a wrapper moves
+/// itself into it and then runs the shell, so nothing the shell starts can
+/// escape the limits; if it can't join, the session exits instead.
+fn shell_command(shell: &str, enforcement: &Enforcement) -> CommandBuilder {
+    match enforcement {
+        Enforcement::Cgroup(dir) => {
+            let mut cmd = CommandBuilder::new("/bin/sh");
+            cmd.args([
+                "-c",
+                r#"{ echo $$ > "$1/cgroup.procs"; } 2>/dev/null || { echo "raven: couldn't apply the session's resource limits" >&2; exit 1; }; exec "$0" -l"#,
+                shell,
+            ]);
+            cmd.arg(dir);
+            cmd
+        }
+        _ => {
+            let mut cmd = CommandBuilder::new(shell);
+            cmd.arg("-l");
+            cmd
+        }
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::SeqCst);
+        // The shell is still running here, so this kills the session's processes
+        // before removing the cgroup; the exit thread reaps the shell
+        if let Enforcement::Cgroup(dir) = self.enforcement.as_ref() {
+            resources::remove_cgroup(dir);
+        }
+    }
+}
+
+/// Parse a signal name, with or without the `SIG` prefix (e.g. "SIGINT" or "int").
+fn signal_number(name: &str) -> Result<libc::c_int, String> {
+    let upper = name.to_ascii_uppercase();
+    let signum = match upper.strip_prefix("SIG").unwrap_or(&upper) {
+        "HUP" => libc::SIGHUP,
+        "INT" => libc::SIGINT,
+        "QUIT" => libc::SIGQUIT,
+        "KILL" => libc::SIGKILL,
+        "TERM" => libc::SIGTERM,
+        "STOP" => libc::SIGSTOP,
+        "TSTP" => libc::SIGTSTP,
+        "CONT" => libc::SIGCONT,
+        "WINCH" => libc::SIGWINCH,
+        "USR1" => libc::SIGUSR1,
+        "USR2" => libc::SIGUSR2,
+        _ => return Err(format!("Unknown signal: {}", name)),
+    };
+    Ok(signum)
+}
+
+/// Poll the session's process tree and broadcast `LimitExceeded` when a limit is hit.
+///
+/// With a cgroup the kernel's event counters say when a limit was enforced. With
+/// rlimits the tree as a whole can still go over, so usage is compared directly and
+/// an event is sent each time it crosses a limit.
+fn monitor_limits(
+    pid: u32,
+    limits: &ResourceLimits,
+    enforcement: &Enforcement,
+    events: &broadcast::Sender<SessionEvent>,
+    closed: &AtomicBool,
+) {
+    let mut memory_hits = 0;
+    let mut process_hits = 0;
+    let mut over_memory = false;
+    let mut over_processes = false;
+
+    while !closed.load(Ordering::SeqCst) {
+        std::thread::sleep(LIMIT_CHECK_INTERVAL);
+
+        let usage = resources::usage(pid);
+        let rss_bytes = usage.as_ref().map_or(0, |u| u.rss_bytes);
+        let process_count = usage.as_ref().map_or(0, |u| u.process_count as u64);
+
+        let (memory_exceeded, processes_exceeded) = match enforcement {
+            Enforcement::Cgroup(dir) => {
+                let (memory, processes) = resources::cgroup_limit_hits(dir);
+                let exceeded = (memory > memory_hits, processes > process_hits);
+                memory_hits = memory;
+                process_hits = processes;
+                exceeded
+            }
+            _ => {
+                let now_over_memory = limits.max_memory_bytes.is_some_and(|max| rss_bytes > max);
+                let now_over_processes =
+                    limits.max_processes.is_some_and(|max| process_count > max);
+                let exceeded = (
+                    now_over_memory && !over_memory,
+                    now_over_processes && !over_processes,
+                );
+                over_memory = now_over_memory;
+                over_processes = now_over_processes;
+                exceeded
+            }
+        };
+
+        if let (true, Some(max)) = (memory_exceeded, limits.max_memory_bytes) {
+            let _ = events.send(SessionEvent::LimitExceeded {
+                limit: LimitKind::Memory,
+                value: rss_bytes,
+                max,
+            });
+        }
+        if let (true, Some(max)) = (processes_exceeded, limits.max_processes) {
+            let _ = events.send(SessionEvent::LimitExceeded {
+                limit: LimitKind::Processes,
+                value: process_count,
+                max,
+            });
         }
     }
 }
@@ -169,11 +382,15 @@ impl SessionManager {
         &self,
         id: String,
         cwd: Option<String>,
-        rows: u16,
-        cols: u16,
+        size: PtySize,
+        limits: Option<ResourceLimits>,
     ) -> Result<(), String> {
-        let session = Session::spawn(id.clone(), cwd, rows, cols)?;
-        self.sessions.lock().insert(id, session);
+        let session = Session::spawn(id.clone(), cwd, size, limits)?;
+        info!(session_id = %id, rows = size.rows, cols = size.cols, "Spawned session");
+        let replaced = self.sessions.lock().insert(id, session);
+        if let Some(replaced) = replaced {
+            close_in_background(replaced);
+        }
         Ok(())
     }
 
@@ -183,10 +400,16 @@ impl SessionManager {
         session.write(data)
     }
 
-    pub fn resize(&self, id: &str, rows: u16, cols: u16) -> Result<(), String> {
+    pub fn resize(&self, id: &str, size: PtySize) -> Result<(), String> {
         let sessions = self.sessions.lock();
         let session = sessions.get(id).ok_or("Session not found")?;
-        session.resize(rows, cols)
+        session.resize(size)
+    }
+
+    pub fn signal(&self, id: &str, signal: &str) -> Result<(), String> {
+        let sessions = self.sessions.lock();
+        let session = sessions.get(id).ok_or("Session not found")?;
+        session.signal(signal)
     }
 
     pub fn get_buffer(&self, id: &str) -> Result<String, String> {
@@ -195,25 +418,35 @@ impl SessionManager {
         Ok(session.get_buffer())
     }
 
-    pub fn subscribe(&self, id: &str) -> Result<broadcast::Receiver<String>, String> {
+    pub fn subscribe(&self, id: &str) -> Result<broadcast::Receiver<SessionEvent>, String> {
         let sessions = self.sessions.lock();
         let session = sessions.get(id).ok_or("Session not found")?;
         Ok(session.subscribe())
     }
 
     pub fn get_info(&self, id: &str) -> Result<SessionInfo, String> {
-        let sessions = self.sessions.lock();
-        let session = sessions.get(id).ok_or("Session not found")?;
-        Ok(session.info())
+        let session = {
+            let sessions = self.sessions.lock();
+            let session = sessions.get(id).ok_or("Session not found")?;
+            (session.info(), session.pid)
+        };
+        Ok(with_usage(vec![session]).remove(0))
     }
 
     pub fn kill(&self, id: &str) -> Result<(), String> {
-        let mut sessions = self.sessions.lock();
-        sessions.remove(id).ok_or("Session not found")?;
+        let session = self.sessions.lock().remove(id).ok_or("Session not found")?;
+        close_in_background(session);
+        info!(session_id = %id, "Killed session");
         Ok(())
     }
 
     pub fn list(&self) -> Vec<SessionInfo> {
-        self.sessions.lock().values().map(|s| s.info()).collect()
+        let sessions: Vec<(SessionInfo, Option<u32>)> = self
+            .sessions
+            .lock()
+            .values()
+            .map(|s| (s.info(), s.pid))
+            .collect();
+        with_usage(sessions)
     }
 }
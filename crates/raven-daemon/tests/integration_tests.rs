@@ -7,7 +7,7 @@
 use std::io::{BufRead, BufReader, Write};
 use std::os::unix::net::UnixStream;
 use std::path::PathBuf;
-use std::process::{Child, Command, Stdio};
+use std::process::{Child, Command, Output, Stdio};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::thread;
 use std::time::Duration;
@@ -25,6 +25,9 @@ pub enum ClientMessage {
         cwd: Option<String>,
         rows: u16,
         cols: u16,
+        pixel_width: u16,
+        pixel_height: u16,
+        limits: Option<ResourceLimits>,
     },
     Write {
         session_id: String,
@@ -34,6 +37,12 @@ pub enum ClientMessage {
         session_id: String,
         rows: u16,
         cols: u16,
+        pixel_width: u16,
+        pixel_height: u16,
+    },
+    Signal {
+        session_id: String,
+        signal: String,
     },
     Attach {
         session_id: String,
@@ -45,6 +54,10 @@ pub enum ClientMessage {
         session_id: String,
     },
     List,
+    Logs {
+        since: Option<String>,
+        level: Option<String>,
+    },
     Ping,
 }
 
@@ -62,6 +75,12 @@ pub enum ServerMessage {
         session_id: String,
         exit_code: Option<i32>,
     },
+    LimitExceeded {
+        session_id: String,
+        limit: LimitKind,
+        value: u64,
+        max: u64,
+    },
     Attached {
         session_id: String,
         buffer: String,
@@ -71,6 +90,9 @@ pub enum ServerMessage {
     Sessions {
         sessions: Vec<SessionInfo>,
     },
+    Logs {
+        entries: Vec<LogEntry>,
+    },
     Error {
         message: String,
     },
@@ -85,15 +107,55 @@ pub struct SessionInfo {
     pub rows: u16,
     pub cols: u16,
     pub alive: bool,
+    pub usage: Option<ResourceUsage>,
+    pub limits: Option<ResourceLimits>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    pub cpu_time_ms: u64,
+    pub rss_bytes: u64,
+    pub process_count: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    pub max_memory_bytes: Option<u64>,
+    pub max_processes: Option<u64>,
+    pub nice: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LimitKind {
+    Memory,
+    Processes,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub timestamp: String,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    pub fields: serde_json::Map<String, serde_json::Value>,
 }
 
 /// Test harness for the daemon
 struct DaemonTestHarness {
     daemon: Child,
     socket_path: PathBuf,
+    log_dir: PathBuf,
     test_id: u64,
 }
 
+/// Find the daemon binary
+fn daemon_path() -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target/debug/raven-daemon");
+    assert!(path.exists(), "Daemon binary not found at {:?}", path);
+    path
+}
+
 impl DaemonTestHarness {
     fn new() -> Self {
         let test_id = TEST_COUNTER.fetch_add(1, Ordering::SeqCst);
@@ -103,22 +165,20 @@ impl DaemonTestHarness {
             test_id
         ));
 
-        // Clean up any stale socket
-        let _ = std::fs::remove_file(&socket_path);
-
-        // Find daemon binary
-        let daemon_path =
-            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("target/debug/raven-daemon");
+        let log_dir = PathBuf::from(format!(
+            "/tmp/raven-daemon-test-{}-{}-logs",
+            std::process::id(),
+            test_id
+        ));
 
-        assert!(
-            daemon_path.exists(),
-            "Daemon binary not found at {:?}",
-            daemon_path
-        );
+        // Clean up any stale socket and logs
+        let _ = std::fs::remove_file(&socket_path);
+        let _ = std::fs::remove_dir_all(&log_dir);
 
         // Set socket path via environment
-        let daemon = Command::new(&daemon_path)
+        let mut daemon = Command::new(daemon_path())
             .env("RAVEN_SOCKET_PATH", &socket_path)
+            .env("RAVEN_LOG_DIR", &log_dir)
             .stdin(Stdio::null())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
@@ -134,6 +194,7 @@ impl DaemonTestHarness {
                         return Self {
                             daemon,
                             socket_path,
+                            log_dir,
                             test_id,
                         };
                     }
@@ -144,6 +205,8 @@ impl DaemonTestHarness {
             }
         }
 
+        let _ = daemon.kill();
+        let _ = daemon.wait();
         panic!("Daemon failed to start at {:?}", socket_path);
     }
 
@@ -151,6 +214,16 @@ impl DaemonTestHarness {
         TestConnection::connect(&self.socket_path).expect("Failed to connect to daemon")
     }
 
+    /// Run a CLI subcommand against this daemon
+    fn cli(&self, args: &[&str]) -> Output {
+        Command::new(daemon_path())
+            .args(args)
+            .env("RAVEN_SOCKET_PATH", &self.socket_path)
+            .stdin(Stdio::null())
+            .output()
+            .expect("Failed to run CLI")
+    }
+
     /// Generate a unique session ID for this test
     fn session_id(&self, name: &str) -> String {
         format!("test-{}-{}", self.test_id, name)
@@ -162,6 +235,7 @@ impl Drop for DaemonTestHarness {
         let _ = self.daemon.kill();
         let _ = self.daemon.wait();
         let _ = std::fs::remove_file(&self.socket_path);
+        let _ = std::fs::remove_dir_all(&self.log_dir);
     }
 }
 
@@ -239,6 +313,9 @@ fn test_spawn_session() {
             cwd: Some("/tmp".to_string()),
             rows: 24,
             cols: 80,
+            pixel_width: 0,
+            pixel_height: 0,
+            limits: None,
         })
         .unwrap();
 
@@ -275,6 +352,9 @@ fn test_spawn_duplicate_session() {
             cwd: None,
             rows: 24,
             cols: 80,
+            pixel_width: 0,
+            pixel_height: 0,
+            limits: None,
         })
         .unwrap();
 
@@ -285,6 +365,9 @@ fn test_spawn_duplicate_session() {
             cwd: None,
             rows: 30,
             cols: 100,
+            pixel_width: 0,
+            pixel_height: 0,
+            limits: None,
         })
         .unwrap();
 
@@ -296,6 +379,212 @@ fn test_spawn_duplicate_session() {
     }
 }
 
+#[test]
+fn test_spawn_with_limits() {
+    let harness = DaemonTestHarness::new();
+    let mut conn = harness.connect();
+    let session_id = harness.session_id("limited");
+
+    let response = conn
+        .send_recv(&ClientMessage::Spawn {
+            session_id: session_id.clone(),
+            cwd: None,
+            rows: 24,
+            cols: 80,
+            pixel_width: 0,
+            pixel_height: 0,
+            limits: Some(ResourceLimits {
+                max_memory_bytes: None,
+                max_processes: Some(4096),
+                nice: Some(5),
+            }),
+        })
+        .unwrap();
+    assert!(matches!(response, ServerMessage::Spawned { .. }));
+
+    let response = conn.send_recv(&ClientMessage::List).unwrap();
+    match response {
+        ServerMessage::Sessions { sessions } => {
+            let session = sessions.iter().find(|s| s.id == session_id).unwrap();
+            let limits = session.limits.as_ref().unwrap();
+            assert_eq!(limits.max_processes, Some(4096));
+            assert_eq!(limits.nice, Some(5));
+
+            // Usage is only measurable where /proc exists
+            if cfg!(target_os = "linux") {
+                let usage = session.usage.as_ref().unwrap();
+                assert!(usage.process_count >= 1);
+                assert!(usage.rss_bytes > 0);
+            }
+        }
+        _ => panic!("Unexpected response: {:?}", response),
+    }
+}
+
+/// Whether the tests run as root (checked via /proc, so Linux only)
+#[cfg(target_os = "linux")]
+fn running_as_root() -> bool {
+    std::fs::read_to_string("/proc/self/status")
+        .map(|status| {
+            status
+                .lines()
+                .any(|l| l.starts_with("Uid:") && l.split_whitespace().nth(1) == Some("0"))
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_process_limit_exceeded() {
+    // Without a cgroup the process limit is a per-user rlimit, which stops a non-root
+    // user's shell from forking at all; root is exempt, so the monitor sees the overrun
+    if !running_as_root() {
+        return;
+    }
+
+    let harness = DaemonTestHarness::new();
+    let mut conn = harness.connect();
+    let session_id = harness.session_id("over-processes");
+
+    let _ = conn
+        .send_recv(&ClientMessage::Spawn {
+            session_id: session_id.clone(),
+            cwd: None,
+            rows: 24,
+            cols: 80,
+            pixel_width: 0,
+            pixel_height: 0,
+            limits: Some(ResourceLimits {
+                max_processes: Some(1),
+                ..Default::default()
+            }),
+        })
+        .unwrap();
+
+    let response = conn
+        .send_recv(&ClientMessage::Attach {
+            session_id: session_id.clone(),
+        })
+        .unwrap();
+    assert!(matches!(response, ServerMessage::Attached { .. }));
+
+    // The shell plus `sleep` is two processes
+    conn.send(&ClientMessage::Write {
+        session_id: session_id.clone(),
+        data: "sleep 3\n".to_string(),
+    })
+    .unwrap();
+
+    for _ in 0..100 {
+        match conn.recv().unwrap() {
+            ServerMessage::LimitExceeded {
+                session_id: id,
+                limit,
+                max,
+                ..
+            } => {
+                assert_eq!(id, session_id);
+                assert_eq!(limit, LimitKind::Processes);
+                assert_eq!(max, 1);
+                return;
+            }
+            ServerMessage::Output { .. } | ServerMessage::Ok => {}
+            other => panic!("Unexpected message: {:?}", other),
+        }
+    }
+
+    panic!("No LimitExceeded event received");
+}
+
+/// The cgroups the daemon has for a session, if the daemon runs under cgroup v2
+#[cfg(target_os = "linux")]
+fn session_cgroups(daemon_pid: u32, session_id: &str) -> Vec<PathBuf> {
+    let Ok(own) = std::fs::read_to_string(format!("/proc/{}/cgroup", daemon_pid)) else {
+        return Vec::new();
+    };
+    let Some(relative) = own.lines().find_map(|l| l.strip_prefix("0::")) else {
+        return Vec::new();
+    };
+    let mut parent = PathBuf::from("/sys/fs/cgroup").join(relative.trim_start_matches('/'));
+    // The daemon may have moved into a leaf group to enable controllers
+    if parent.ends_with("raven-daemon") {
+        parent.pop();
+    }
+    let prefix = format!("raven-session-{}-", session_id);
+    std::fs::read_dir(parent)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_name().to_string_lossy().starts_with(&prefix))
+                .map(|e| e.path())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_respawn_limited_session() {
+    let harness = DaemonTestHarness::new();
+    let mut conn = harness.connect();
+    let session_id = harness.session_id("respawn");
+    let cgroup_count = || session_cgroups(harness.daemon.id(), &session_id).len();
+
+    let spawn = ClientMessage::Spawn {
+        session_id: session_id.clone(),
+        cwd: None,
+        rows: 24,
+        cols: 80,
+        pixel_width: 0,
+        pixel_height: 0,
+        limits: Some(ResourceLimits {
+            max_processes: Some(4096),
+            ..Default::default()
+        }),
+    };
+    let kill = ClientMessage::Kill {
+        session_id: session_id.clone(),
+    };
+
+    let response = conn.send_recv(&spawn).unwrap();
+    assert!(matches!(response, ServerMessage::Spawned { .. }));
+    // Only checked where the daemon could create a cgroup
+    let uses_cgroup = cgroup_count() == 1;
+
+    for _ in 0..2 {
+        let response = conn.send_recv(&kill).unwrap();
+        assert!(matches!(response, ServerMessage::Ok));
+
+        let response = conn.send_recv(&spawn).unwrap();
+        assert!(
+            matches!(response, ServerMessage::Spawned { .. }),
+            "Unexpected response: {:?}",
+            response
+        );
+        if uses_cgroup {
+            // The killed session's group is removed in the background
+            let deadline = std::time::Instant::now() + Duration::from_secs(5);
+            while cgroup_count() > 1 && std::time::Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            assert_eq!(
+                cgroup_count(),
+                1,
+                "Cgroup left behind, or respawn fell back to rlimits"
+            );
+        }
+    }
+
+    let response = conn.send_recv(&ClientMessage::List).unwrap();
+    match response {
+        ServerMessage::Sessions { sessions } => {
+            let session = sessions.iter().find(|s| s.id == session_id).unwrap();
+            assert!(session.alive);
+        }
+        _ => panic!("Unexpected response: {:?}", response),
+    }
+}
+
 #[test]
 fn test_attach_to_nonexistent_session() {
     let harness = DaemonTestHarness::new();
@@ -327,6 +616,9 @@ fn test_attach_to_session() {
             cwd: None,
             rows: 24,
             cols: 80,
+            pixel_width: 0,
+            pixel_height: 0,
+            limits: None,
         })
         .unwrap();
 
@@ -384,6 +676,9 @@ fn test_write_to_session() {
             cwd: None,
             rows: 24,
             cols: 80,
+            pixel_width: 0,
+            pixel_height: 0,
+            limits: None,
         })
         .unwrap();
 
@@ -410,6 +705,9 @@ fn test_resize_session() {
             cwd: None,
             rows: 24,
             cols: 80,
+            pixel_width: 0,
+            pixel_height: 0,
+            limits: None,
         })
         .unwrap();
 
@@ -419,12 +717,126 @@ fn test_resize_session() {
             session_id: "test-resize".to_string(),
             rows: 40,
             cols: 120,
+            pixel_width: 960,
+            pixel_height: 800,
         })
         .unwrap();
 
     assert!(matches!(response, ServerMessage::Ok));
 }
 
+#[test]
+fn test_signal_nonexistent_session() {
+    let harness = DaemonTestHarness::new();
+    let mut conn = harness.connect();
+
+    let response = conn
+        .send_recv(&ClientMessage::Signal {
+            session_id: "nonexistent".to_string(),
+            signal: "SIGINT".to_string(),
+        })
+        .unwrap();
+
+    match response {
+        ServerMessage::Error { message } => {
+            assert!(message.contains("not found"));
+        }
+        _ => panic!("Expected error, got: {:?}", response),
+    }
+}
+
+#[test]
+fn test_signal_unknown() {
+    let harness = DaemonTestHarness::new();
+    let mut conn = harness.connect();
+    let session_id = harness.session_id("bad-signal");
+
+    let _ = conn
+        .send_recv(&ClientMessage::Spawn {
+            session_id: session_id.clone(),
+            cwd: None,
+            rows: 24,
+            cols: 80,
+            pixel_width: 0,
+            pixel_height: 0,
+            limits: None,
+        })
+        .unwrap();
+
+    let response = conn
+        .send_recv(&ClientMessage::Signal {
+            session_id,
+            signal: "SIGNOPE".to_string(),
+        })
+        .unwrap();
+
+    match response {
+        ServerMessage::Error { message } => {
+            assert!(message.contains("Unknown signal"));
+        }
+        _ => panic!("Expected error, got: {:?}", response),
+    }
+}
+
+#[test]
+fn test_signal_reaches_foreground_process() {
+    let harness = DaemonTestHarness::new();
+    let mut conn = harness.connect();
+    let session_id = harness.session_id("signal");
+
+    let _ = conn
+        .send_recv(&ClientMessage::Spawn {
+            session_id: session_id.clone(),
+            cwd: None,
+            rows: 24,
+            cols: 80,
+            pixel_width: 0,
+            pixel_height: 0,
+            limits: None,
+        })
+        .unwrap();
+    let _ = conn
+        .send_recv(&ClientMessage::Attach {
+            session_id: session_id.clone(),
+        })
+        .unwrap();
+
+    // A foreground program that traps SIGINT; the markers are split with '' so
+    // the echoed command line doesn't match them
+    conn.send(&ClientMessage::Write {
+        session_id: session_id.clone(),
+        data: "sh -c \"trap 'echo CAUGHT''_INT; exit' INT; echo LOOP''_READY; \
+               while :; do sleep 0.1; done\"\n"
+            .to_string(),
+    })
+    .unwrap();
+
+    let mut output = String::new();
+    let mut signalled = false;
+    for _ in 0..200 {
+        match conn.recv().unwrap() {
+            ServerMessage::Output { data, .. } => {
+                output.push_str(&data);
+                if !signalled && output.contains("LOOP_READY") {
+                    conn.send(&ClientMessage::Signal {
+                        session_id: session_id.clone(),
+                        signal: "int".to_string(),
+                    })
+                    .unwrap();
+                    signalled = true;
+                }
+                if output.contains("CAUGHT_INT") {
+                    return;
+                }
+            }
+            ServerMessage::Ok => {}
+            other => panic!("Unexpected message: {:?}", other),
+        }
+    }
+
+    panic!("Trap never ran, output: {:?}", output);
+}
+
 #[test]
 fn test_kill_session() {
     let harness = DaemonTestHarness::new();
@@ -437,6 +849,9 @@ fn test_kill_session() {
             cwd: None,
             rows: 24,
             cols: 80,
+            pixel_width: 0,
+            pixel_height: 0,
+            limits: None,
         })
         .unwrap();
 
@@ -490,6 +905,9 @@ fn test_detach_from_session() {
             cwd: None,
             rows: 24,
             cols: 80,
+            pixel_width: 0,
+            pixel_height: 0,
+            limits: None,
         })
         .unwrap();
 
@@ -522,6 +940,9 @@ fn test_multiple_sessions() {
                 cwd: None,
                 rows: 24,
                 cols: 80,
+                pixel_width: 0,
+                pixel_height: 0,
+                limits: None,
             })
             .unwrap();
         assert!(matches!(response, ServerMessage::Spawned { .. }));
@@ -568,6 +989,9 @@ fn test_multiple_clients() {
             cwd: None,
             rows: 24,
             cols: 80,
+            pixel_width: 0,
+            pixel_height: 0,
+            limits: None,
         })
         .unwrap();
 
@@ -623,6 +1047,9 @@ fn test_attach_reattach_same_connection() {
             cwd: None,
             rows: 24,
             cols: 80,
+            pixel_width: 0,
+            pixel_height: 0,
+            limits: None,
         })
         .unwrap();
 
@@ -652,6 +1079,193 @@ fn test_attach_reattach_same_connection() {
     assert!(matches!(response, ServerMessage::Ok));
 }
 
+#[test]
+fn test_logs_include_startup() {
+    let harness = DaemonTestHarness::new();
+    let mut conn = harness.connect();
+
+    let response = conn
+        .send_recv(&ClientMessage::Logs {
+            since: None,
+            level: None,
+        })
+        .unwrap();
+
+    match response {
+        ServerMessage::Logs { entries } => {
+            assert!(entries
+                .iter()
+                .any(|e| e.level == "INFO" && e.message.contains("Daemon listening")));
+        }
+        _ => panic!("Unexpected response: {:?}", response),
+    }
+}
+
+#[test]
+fn test_logs_filter_by_level_and_since() {
+    let harness = DaemonTestHarness::new();
+    let mut conn = harness.connect();
+
+    // Only warnings and errors pass a WARN filter
+    let response = conn
+        .send_recv(&ClientMessage::Logs {
+            since: None,
+            level: Some("warn".to_string()),
+        })
+        .unwrap();
+    match response {
+        ServerMessage::Logs { entries } => {
+            assert!(entries
+                .iter()
+                .all(|e| e.level == "WARN" || e.level == "ERROR"));
+        }
+        _ => panic!("Unexpected response: {:?}", response),
+    }
+
+    let response = conn
+        .send_recv(&ClientMessage::Logs {
+            since: Some("9999-01-01T00:00:00Z".to_string()),
+            level: None,
+        })
+        .unwrap();
+    match response {
+        ServerMessage::Logs { entries } => {
+            assert!(entries.is_empty());
+        }
+        _ => panic!("Unexpected response: {:?}", response),
+    }
+}
+
+#[test]
+fn test_logs_since_compares_timestamps() {
+    let harness = DaemonTestHarness::new();
+    let mut conn = harness.connect();
+
+    let entry = |timestamp: &str, message: &str| {
+        serde_json::json!({
+            "timestamp": timestamp,
+            "level": "INFO",
+            "target": "old",
+            "fields": {"message": message},
+        })
+        .to_string()
+    };
+    let write = |name: &str, lines: &[String]| {
+        std::fs::write(harness.log_dir.join(name), lines.join("\n") + "\n").unwrap();
+    };
+    // Files are skipped by the day in their name
+    write(
+        "daemon.2019-12-31.log",
+        &[entry("2020-01-02T00:00:00Z", "misfiled")],
+    );
+    write(
+        "daemon.2020-01-01.log",
+        &[
+            entry("2020-01-01T23:00:00.5Z", "before"),
+            entry("2020-01-01T23:30:00.000001Z", "after"),
+        ],
+    );
+    write(
+        "daemon.2020-01-02.log",
+        &[entry("2020-01-02T00:30:00Z", "next day")],
+    );
+
+    // 23:15 UTC
+    let response = conn
+        .send_recv(&ClientMessage::Logs {
+            since: Some("2020-01-02T00:15:00.25+01:00".to_string()),
+            level: None,
+        })
+        .unwrap();
+    match response {
+        ServerMessage::Logs { entries } => {
+            let messages: Vec<&str> = entries
+                .iter()
+                .filter(|e| e.target == "old")
+                .map(|e| e.message.as_str())
+                .collect();
+            assert_eq!(messages, vec!["after", "next day"]);
+        }
+        _ => panic!("Unexpected response: {:?}", response),
+    }
+
+    let response = conn
+        .send_recv(&ClientMessage::Logs {
+            since: Some("2020-01-01".to_string()),
+            level: None,
+        })
+        .unwrap();
+    match response {
+        ServerMessage::Error { message } => {
+            assert!(message.contains("Invalid timestamp"));
+        }
+        _ => panic!("Expected error, got: {:?}", response),
+    }
+}
+
+#[test]
+fn test_logs_record_session_lifecycle() {
+    let harness = DaemonTestHarness::new();
+    let mut conn = harness.connect();
+    let session_id = harness.session_id("logged");
+
+    let _ = conn
+        .send_recv(&ClientMessage::Spawn {
+            session_id: session_id.clone(),
+            cwd: None,
+            rows: 24,
+            cols: 80,
+            pixel_width: 0,
+            pixel_height: 0,
+            limits: None,
+        })
+        .unwrap();
+    let _ = conn
+        .send_recv(&ClientMessage::Kill {
+            session_id: session_id.clone(),
+        })
+        .unwrap();
+
+    let response = conn
+        .send_recv(&ClientMessage::Logs {
+            since: None,
+            level: Some("info".to_string()),
+        })
+        .unwrap();
+
+    match response {
+        ServerMessage::Logs { entries } => {
+            let messages: Vec<&str> = entries
+                .iter()
+                .filter(|e| e.fields.get("session_id") == Some(&serde_json::json!(session_id)))
+                .map(|e| e.message.as_str())
+                .collect();
+            assert_eq!(messages, vec!["Spawned session", "Killed session"]);
+        }
+        _ => panic!("Unexpected response: {:?}", response),
+    }
+}
+
+#[test]
+fn test_logs_invalid_level() {
+    let harness = DaemonTestHarness::new();
+    let mut conn = harness.connect();
+
+    let response = conn
+        .send_recv(&ClientMessage::Logs {
+            since: None,
+            level: Some("loud".to_string()),
+        })
+        .unwrap();
+
+    match response {
+        ServerMessage::Error { message } => {
+            assert!(message.contains("Invalid log level"));
+        }
+        _ => panic!("Expected error, got: {:?}", response),
+    }
+}
+
 #[test]
 fn test_invalid_json_message() {
     let harness = DaemonTestHarness::new();
@@ -674,6 +1288,105 @@ fn test_invalid_json_message() {
     assert!(matches!(response, ServerMessage::Pong));
 }
 
+// ============================================================================
+// CLI Tests
+// ============================================================================
+
+#[test]
+fn test_cli_status() {
+    let harness = DaemonTestHarness::new();
+
+    let output = harness.cli(&["status", "--json"]);
+    assert!(output.status.success());
+    let status: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(status["running"], true);
+    assert_eq!(status["sessions"], 0);
+}
+
+#[test]
+fn test_cli_status_not_running() {
+    let output = Command::new(daemon_path())
+        .arg("status")
+        .env("RAVEN_SOCKET_PATH", "/tmp/raven-daemon-test-missing.sock")
+        .output()
+        .expect("Failed to run CLI");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("not running"));
+}
+
+#[test]
+fn test_cli_list_send_kill() {
+    let harness = DaemonTestHarness::new();
+    let mut conn = harness.connect();
+    let session_id = harness.session_id("cli");
+
+    let _ = conn
+        .send_recv(&ClientMessage::Spawn {
+            session_id: session_id.clone(),
+            cwd: Some("/tmp".to_string()),
+            rows: 24,
+            cols: 80,
+            pixel_width: 0,
+            pixel_height: 0,
+            limits: None,
+        })
+        .unwrap();
+
+    let output = harness.cli(&["list", "--json"]);
+    assert!(output.status.success());
+    let sessions: Vec<SessionInfo> = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].id, session_id);
+
+    let output = harness.cli(&["list"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains(&session_id));
+
+    // Text sent through the CLI shows up in the session's output
+    let _ = conn
+        .send_recv(&ClientMessage::Attach {
+            session_id: session_id.clone(),
+        })
+        .unwrap();
+    let output = harness.cli(&["send", &session_id, "echo CLI''_SENT", "--enter"]);
+    assert!(output.status.success());
+
+    let mut seen = String::new();
+    for _ in 0..50 {
+        match conn.recv() {
+            Ok(ServerMessage::Output { data, .. }) => {
+                seen.push_str(&data);
+                if seen.contains("CLI_SENT") {
+                    break;
+                }
+            }
+            Ok(_) => {}
+            Err(e) => panic!("Failed to read output: {}", e),
+        }
+    }
+    assert!(seen.contains("CLI_SENT"), "Output was: {:?}", seen);
+
+    let output = harness.cli(&["kill", &session_id]);
+    assert!(output.status.success());
+
+    let output = harness.cli(&["kill", &session_id]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Session not found"));
+}
+
+#[test]
+fn test_cli_logs() {
+    let harness = DaemonTestHarness::new();
+
+    let output = harness.cli(&["logs", "--level", "info"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Daemon listening"));
+
+    let output = harness.cli(&["logs", "--level", "loud"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Invalid log level"));
+}
+
 // ============================================================================
 // Session Manager Unit Tests
 // ============================================================================
@@ -695,6 +1408,9 @@ fn test_client_message_serialization() {
         cwd: Some("/tmp".to_string()),
         rows: 24,
         cols: 80,
+        pixel_width: 0,
+        pixel_height: 0,
+        limits: None,
     };
 
     let json = serde_json::to_string(&msg).unwrap();
@@ -708,6 +1424,7 @@ fn test_client_message_serialization() {
             cwd,
             rows,
             cols,
+            ..
         } => {
             assert_eq!(session_id, "test");
             assert_eq!(cwd, Some("/tmp".to_string()));
@@ -755,6 +1472,9 @@ fn test_all_client_message_variants() {
             cwd: None,
             rows: 1,
             cols: 1,
+            pixel_width: 0,
+            pixel_height: 0,
+            limits: None,
         },
         ClientMessage::Write {
             session_id: "s".to_string(),
@@ -764,6 +1484,12 @@ fn test_all_client_message_variants() {
             session_id: "s".to_string(),
             rows: 1,
             cols: 1,
+            pixel_width: 8,
+            pixel_height: 16,
+        },
+        ClientMessage::Signal {
+            session_id: "s".to_string(),
+            signal: "SIGINT".to_string(),
         },
         ClientMessage::Attach {
             session_id: "s".to_string(),
@@ -775,6 +1501,10 @@ fn test_all_client_message_variants() {
             session_id: "s".to_string(),
         },
         ClientMessage::List,
+        ClientMessage::Logs {
+            since: Some("2026-01-01T00:00:00Z".to_string()),
+            level: Some("warn".to_string()),
+        },
         ClientMessage::Ping,
     ];
 
@@ -798,6 +1528,12 @@ fn test_all_server_message_variants() {
             session_id: "s".to_string(),
             exit_code: Some(0),
         },
+        ServerMessage::LimitExceeded {
+            session_id: "s".to_string(),
+            limit: LimitKind::Memory,
+            value: 2,
+            max: 1,
+        },
         ServerMessage::Attached {
             session_id: "s".to_string(),
             buffer: "b".to_string(),
@@ -811,6 +1547,21 @@ fn test_all_server_message_variants() {
                 rows: 1,
                 cols: 1,
                 alive: true,
+                usage: Some(ResourceUsage {
+                    cpu_time_ms: 1,
+                    rss_bytes: 1,
+                    process_count: 1,
+                }),
+                limits: Some(ResourceLimits::default()),
+            }],
+        },
+        ServerMessage::Logs {
+            entries: vec![LogEntry {
+                timestamp: "2026-01-01T00:00:00.000000Z".to_string(),
+                level: "INFO".to_string(),
+                target: "raven_daemon".to_string(),
+                message: "m".to_string(),
+                fields: serde_json::Map::new(),
             }],
         },
         ServerMessage::Error {
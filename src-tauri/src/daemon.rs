@@ -20,6 +20,9 @@ pub enum ClientMessage {
         cwd: Option<String>,
         rows: u16,
         cols: u16,
+        pixel_width: u16,
+        pixel_height: u16,
+        limits: Option<ResourceLimits>,
     },
     Write {
         session_id: String,
@@ -29,6 +32,12 @@ pub enum ClientMessage {
         session_id: String,
         rows: u16,
         cols: u16,
+        pixel_width: u16,
+        pixel_height: u16,
+    },
+    Signal {
+        session_id: String,
+        signal: String,
     },
     Attach {
         session_id: String,
@@ -40,6 +49,10 @@ pub enum ClientMessage {
         session_id: String,
     },
     List,
+    Logs {
+        since: Option<String>,
+        level: Option<String>,
+    },
     Ping,
 }
 
@@ -58,6 +71,12 @@ pub enum ServerMessage {
         session_id: String,
         exit_code: Option<i32>,
     },
+    LimitExceeded {
+        session_id: String,
+        limit: LimitKind,
+        value: u64,
+        max: u64,
+    },
     Attached {
         session_id: String,
         buffer: String,
@@ -67,6 +86,9 @@ pub enum ServerMessage {
     Sessions {
         sessions: Vec<SessionInfo>,
     },
+    Logs {
+        entries: Vec<LogEntry>,
+    },
     Error {
         message: String,
     },
@@ -81,6 +103,47 @@ pub struct SessionInfo {
     pub rows: u16,
     pub cols: u16,
     pub alive: bool,
+    #[serde(default)]
+    pub usage: Option<ResourceUsage>,
+    #[serde(default)]
+    pub limits: Option<ResourceLimits>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    pub cpu_time_ms: u64,
+    pub rss_bytes: u64,
+    pub process_count: u32,
+}
+
+/// A terminal's cell area in pixels, for programs that size images by it.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct PixelSize {
+    pub width: u16,
+    pub height: u16,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResourceLimits {
+    pub max_memory_bytes: Option<u64>,
+    pub max_processes: Option<u64>,
+    pub nice: Option<i32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LimitKind {
+    Memory,
+    Processes,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogEntry {
+    pub timestamp: String,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    pub fields: serde_json::Map<String, serde_json::Value>,
 }
 
 fn get_socket_path() -> PathBuf {
@@ -230,6 +293,14 @@ struct PtyOutput {
     data: String,
 }
 
+#[derive(Clone, Serialize)]
+struct PtyLimitExceeded {
+    id: String,
+    limit: LimitKind,
+    value: u64,
+    max: u64,
+}
+
 /// Spawn a new terminal session via daemon (does NOT attach - caller must attach separately)
 #[tauri::command]
 pub fn daemon_spawn(
@@ -238,7 +309,10 @@ pub fn daemon_spawn(
     cwd: Option<String>,
     rows: u16,
     cols: u16,
+    pixel_size: Option<PixelSize>,
+    limits: Option<ResourceLimits>,
 ) -> Result<(), String> {
+    let pixel_size = pixel_size.unwrap_or_default();
     let manager = app.state::<DaemonManager>();
     manager.ensure_running()?;
 
@@ -251,6 +325,9 @@ pub fn daemon_spawn(
         cwd,
         rows,
         cols,
+        pixel_width: pixel_size.width,
+        pixel_height: pixel_size.height,
+        limits,
     };
     match conn.send_recv(&msg)? {
         ServerMessage::Spawned { .. } => Ok(()),
@@ -280,7 +357,14 @@ pub fn daemon_write(app: AppHandle, id: String, data: String) -> Result<(), Stri
 
 /// Resize a terminal session via daemon
 #[tauri::command]
-pub fn daemon_resize(app: AppHandle, id: String, rows: u16, cols: u16) -> Result<(), String> {
+pub fn daemon_resize(
+    app: AppHandle,
+    id: String,
+    rows: u16,
+    cols: u16,
+    pixel_size: Option<PixelSize>,
+) -> Result<(), String> {
+    let pixel_size = pixel_size.unwrap_or_default();
     let manager = app.state::<DaemonManager>();
     manager.ensure_running()?;
 
@@ -289,6 +373,27 @@ pub fn daemon_resize(app: AppHandle, id: String, rows: u16, cols: u16) -> Result
         session_id: id,
         rows,
         cols,
+        pixel_width: pixel_size.width,
+        pixel_height: pixel_size.height,
+    };
+
+    match conn.send_recv(&msg)? {
+        ServerMessage::Ok => Ok(()),
+        ServerMessage::Error { message } => Err(message),
+        _ => Err("Unexpected response".to_string()),
+    }
+}
+
+/// Send a signal (e.g. "SIGINT", "SIGTSTP", "SIGKILL") to a session's foreground process group
+#[tauri::command]
+pub fn daemon_signal(app: AppHandle, id: String, signal: String) -> Result<(), String> {
+    let manager = app.state::<DaemonManager>();
+    manager.ensure_running()?;
+
+    let mut conn = DaemonConnection::connect()?;
+    let msg = ClientMessage::Signal {
+        session_id: id,
+        signal,
     };
 
     match conn.send_recv(&msg)? {
@@ -350,6 +455,24 @@ pub fn daemon_list(app: AppHandle) -> Result<Vec<SessionInfo>, String> {
     }
 }
 
+/// Query the daemon's log file (e.g. to surface daemon errors in the app)
+#[tauri::command]
+pub fn daemon_logs(
+    app: AppHandle,
+    since: Option<String>,
+    level: Option<String>,
+) -> Result<Vec<LogEntry>, String> {
+    let manager = app.state::<DaemonManager>();
+    manager.ensure_running()?;
+
+    let mut conn = DaemonConnection::connect()?;
+    match conn.send_recv(&ClientMessage::Logs { since, level })? {
+        ServerMessage::Logs { entries } => Ok(entries),
+        ServerMessage::Error { message } => Err(message),
+        _ => Err("Unexpected response".to_string()),
+    }
+}
+
 /// Attach to an existing session (for reconnection after app restart)
 #[tauri::command]
 pub fn daemon_attach(app: AppHandle, id: String) -> Result<String, String> {
@@ -385,6 +508,19 @@ pub fn daemon_attach(app: AppHandle, id: String) -> Result<String, String> {
                             },
                         );
                     }
+                    Ok(ServerMessage::LimitExceeded {
+                        limit, value, max, ..
+                    }) => {
+                        let _ = app_clone.emit(
+                            &format!("pty-limit-exceeded-{}", id_clone),
+                            PtyLimitExceeded {
+                                id: id_clone.clone(),
+                                limit,
+                                value,
+                                max,
+                            },
+                        );
+                    }
                     Ok(ServerMessage::Exited { exit_code, .. }) => {
                         let _ = app_clone.emit(&format!("pty-exit-{}", id_clone), exit_code);
                         break;
@@ -1,37 +1,116 @@
-use ignore::WalkBuilder;
-use std::fs;
-use std::path::Path;
+use crate::git::{self, GitStatus};
+use crate::lsp::LspManager;
+use crate::scope::{OutOfScope, PathScope};
+use raven_core::encoding::{self, LineEnding, TextFormat, BINARY_CHECK_LEN};
+pub use raven_core::file::{atomic_write, project_walk};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::time::UNIX_EPOCH;
+use tauri::State;
+
+/// Files larger than this aren't loaded into the editor; use `read_file_range`
+/// or `read_file_lines` to view them in parts
+pub const MAX_EDITOR_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Result of `read_file`, tagged by `kind`: decoded text, or just metadata for
+/// binary and very large files.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum FileContents {
+    Text {
+        /// Text with `\n` line endings
+        content: String,
+        #[serde(flatten)]
+        format: TextFormat,
+    },
+    Binary {
+        size: u64,
+        mtime: u64,
+    },
+    Large {
+        size: u64,
+        mtime: u64,
+    },
+}
+
+/// Error from file commands, tagged by `kind` for the frontend.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum FileError {
+    /// The path is outside every allowed root
+    PermissionDenied {
+        path: String,
+        message: String,
+    },
+    Failed {
+        message: String,
+    },
+}
+
+impl From<String> for FileError {
+    fn from(message: String) -> Self {
+        FileError::Failed { message }
+    }
+}
+
+impl From<OutOfScope> for FileError {
+    fn from(e: OutOfScope) -> Self {
+        FileError::PermissionDenied {
+            message: e.to_string(),
+            path: e.path,
+        }
+    }
+}
 
 #[tauri::command]
-pub fn read_file(path: String) -> Result<String, String> {
-    fs::read_to_string(&path).map_err(|e| format!("Failed to read file '{}': {}", path, e))
+pub fn read_file(scope: State<'_, PathScope>, path: String) -> Result<FileContents, FileError> {
+    let read_error = |e: io::Error| format!("Failed to read file '{}': {}", path, e);
+    let mut file = File::open(scope.check(&path)?).map_err(read_error)?;
+    let metadata = file.metadata().map_err(read_error)?;
+    let (size, mtime) = (metadata.len(), mtime_millis(&metadata));
+
+    // Check for binary content before reading the whole file
+    let mut head = Vec::with_capacity(BINARY_CHECK_LEN);
+    (&mut file)
+        .take(BINARY_CHECK_LEN as u64)
+        .read_to_end(&mut head)
+        .map_err(read_error)?;
+    if encoding::looks_binary(&head) {
+        return Ok(FileContents::Binary { size, mtime });
+    }
+    if size > MAX_EDITOR_FILE_SIZE {
+        return Ok(FileContents::Large { size, mtime });
+    }
+
+    let mut bytes = head;
+    file.read_to_end(&mut bytes).map_err(read_error)?;
+    let (content, format) = encoding::decode(&bytes);
+    Ok(FileContents::Text { content, format })
 }
 
 /// List all files in a directory, respecting .gitignore.
 /// Returns paths relative to the given root directory.
 #[tauri::command]
-pub fn list_files(root: String) -> Result<Vec<String>, String> {
-    let root_path = Path::new(&root);
+pub fn list_files(scope: State<'_, PathScope>, root: String) -> Result<Vec<String>, FileError> {
+    let root_path = scope.check(&root)?;
     if !root_path.exists() {
-        return Err(format!("Directory does not exist: {}", root));
+        return Err(format!("Directory does not exist: {}", root).into());
     }
 
     let mut files = Vec::new();
 
-    for entry in WalkBuilder::new(&root)
-        .hidden(true) // skip hidden files
-        .git_ignore(true) // respect .gitignore
-        .git_global(true)
-        .git_exclude(true)
-        .build()
-    {
+    for entry in project_walk(&root_path).build() {
         let entry = entry.map_err(|e| format!("Walk error: {}", e))?;
         let path = entry.path();
 
         // Skip directories, only include files
         if path.is_file() {
             // Get path relative to root
-            if let Ok(relative) = path.strip_prefix(&root) {
+            if let Ok(relative) = path.strip_prefix(&root_path) {
                 if let Some(s) = relative.to_str() {
                     files.push(s.to_string());
                 }
@@ -45,19 +124,303 @@ pub fn list_files(root: String) -> Result<Vec<String>, String> {
     Ok(files)
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryKind {
+    File,
+    Dir,
+    Symlink,
+}
+
+/// One entry of a directory listing.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryEntry {
+    pub name: String,
+    pub path: String,
+    pub kind: EntryKind,
+    /// Size in bytes (0 for directories)
+    pub size: u64,
+    /// Modification time in milliseconds since the Unix epoch
+    pub mtime: u64,
+    /// Excluded by .gitignore, .git/info/exclude or the global gitignore
+    pub ignored: bool,
+    pub hidden: bool,
+    /// None if unchanged or not in a git repository
+    pub git_status: Option<GitStatus>,
+}
+
+/// List one level of a directory for the file tree. Hidden and ignored entries
+/// are left out unless `include_ignored` is set. Directories come first, then
+/// files, each sorted case-insensitively by name.
 #[tauri::command]
-pub fn write_file(path: String, content: String) -> Result<(), String> {
+pub fn list_directory(
+    scope: State<'_, PathScope>,
+    path: String,
+    include_ignored: Option<bool>,
+) -> Result<Vec<DirectoryEntry>, FileError> {
+    let dir = scope.check(&path)?;
+    if !dir.is_dir() {
+        return Err(format!("Directory does not exist: {}", path).into());
+    }
+    let include_ignored = include_ignored.unwrap_or(false);
+
+    // Anything the project walk doesn't yield (hidden aside) is ignored
+    let visible: HashSet<PathBuf> = project_walk(&dir)
+        .hidden(false)
+        .max_depth(Some(1))
+        .build()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.into_path())
+        .collect();
+    let statuses = git::status(&dir).unwrap_or_default();
+
+    let read_dir =
+        fs::read_dir(&dir).map_err(|e| format!("Failed to read directory '{}': {}", path, e))?;
+    let mut entries = Vec::new();
+    for entry in read_dir {
+        let entry = entry.map_err(|e| format!("Failed to read directory '{}': {}", path, e))?;
+        let entry_path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name == ".git" && !include_ignored {
+            continue;
+        }
+
+        let hidden = name.starts_with('.');
+        let ignored = !visible.contains(&entry_path);
+        if (hidden || ignored) && !include_ignored {
+            continue;
+        }
+
+        // Don't follow symlinks, so a link is reported as a link
+        let Ok(metadata) = fs::symlink_metadata(&entry_path) else {
+            continue;
+        };
+        let kind = if metadata.is_symlink() {
+            EntryKind::Symlink
+        } else if metadata.is_dir() {
+            EntryKind::Dir
+        } else {
+            EntryKind::File
+        };
+
+        entries.push(DirectoryEntry {
+            git_status: git::status_of(&statuses, &entry_path, kind == EntryKind::Dir),
+            path: entry_path.to_string_lossy().to_string(),
+            name,
+            kind,
+            size: if kind == EntryKind::Dir {
+                0
+            } else {
+                metadata.len()
+            },
+            mtime: mtime_millis(&metadata),
+            ignored,
+            hidden,
+        });
+    }
+
+    entries.sort_by(|a, b| {
+        (b.kind == EntryKind::Dir)
+            .cmp(&(a.kind == EntryKind::Dir))
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+
+    Ok(entries)
+}
+
+/// Version of a file on disk, used to detect changes made since it was loaded.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileVersion {
+    /// Modification time in milliseconds since the Unix epoch
+    pub mtime: u64,
+    /// SHA-256 of the contents, hex encoded
+    pub hash: String,
+}
+
+impl FileVersion {
+    fn of(contents: &[u8], metadata: &fs::Metadata) -> Self {
+        Self {
+            mtime: mtime_millis(metadata),
+            hash: content_hash(contents),
+        }
+    }
+}
+
+/// Modification time in milliseconds since the Unix epoch (0 if unavailable).
+pub fn mtime_millis(metadata: &fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// SHA-256 of file contents, hex encoded.
+pub fn content_hash(contents: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(contents))
+}
+
+/// Error from `write_file`, tagged by `kind` for the frontend.
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum WriteFileError {
+    /// The file changed on disk since it was loaded; nothing was written
+    #[serde(rename_all = "camelCase")]
+    Conflict {
+        path: String,
+        disk_content: String,
+        disk_version: FileVersion,
+    },
+    /// The path is outside every allowed root; nothing was written
+    PermissionDenied {
+        path: String,
+        message: String,
+    },
+    Failed {
+        message: String,
+    },
+}
+
+impl From<String> for WriteFileError {
+    fn from(message: String) -> Self {
+        WriteFileError::Failed { message }
+    }
+}
+
+impl From<OutOfScope> for WriteFileError {
+    fn from(e: OutOfScope) -> Self {
+        WriteFileError::PermissionDenied {
+            message: e.to_string(),
+            path: e.path,
+        }
+    }
+}
+
+#[tauri::command]
+pub fn file_version(scope: State<'_, PathScope>, path: String) -> Result<FileVersion, FileError> {
+    let target = scope.check(&path)?;
+    let contents =
+        fs::read(&target).map_err(|e| format!("Failed to read file '{}': {}", path, e))?;
+    let metadata =
+        fs::metadata(&target).map_err(|e| format!("Failed to read file '{}': {}", path, e))?;
+    Ok(FileVersion::of(&contents, &metadata))
+}
+
+/// How `write_file` saves a file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WriteOptions {
+    /// Only overwrite the file if its content still has this hash
+    pub expected_hash: Option<String>,
+    /// Without a hash, only overwrite it if it was last modified at this time
+    pub expected_mtime: Option<u64>,
+    /// Save with this encoding instead of the file's own
+    pub encoding: Option<String>,
+    /// Save with these line endings instead of the file's own
+    pub line_ending: Option<LineEnding>,
+    /// Add or drop a byte order mark
+    pub bom: Option<bool>,
+}
+
+/// Save a file atomically.
+///
+/// `content` is encoded with the file's existing encoding, line endings and
+/// byte order mark unless `options` say otherwise (new files default to
+/// UTF-8 with `\n` and no BOM).
+///
+/// If the expected hash (or failing that mtime) in `options` no longer
+/// matches the file on disk, returns a conflict with the on-disk content
+/// instead of overwriting it. Returns the new version on success.
+#[tauri::command]
+pub fn write_file(
+    scope: State<'_, PathScope>,
+    path: String,
+    content: String,
+    options: Option<WriteOptions>,
+) -> Result<FileVersion, WriteFileError> {
+    let WriteOptions {
+        expected_hash,
+        expected_mtime,
+        encoding,
+        line_ending,
+        bom,
+    } = options.unwrap_or_default();
+    // Write through symlinks rather than replacing them
+    let target = scope.check(&path)?;
+
     // Ensure parent directory exists
-    if let Some(parent) = Path::new(&path).parent() {
+    if let Some(parent) = target.parent() {
         if !parent.exists() {
             fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
         }
     }
 
-    fs::write(&path, content).map_err(|e| format!("Failed to write file '{}': {}", path, e))
+    let disk = fs::read(&target).ok();
+    let mut format = disk
+        .as_deref()
+        .map(|bytes| encoding::decode(bytes).1)
+        .unwrap_or_default();
+    if let Some(encoding) = encoding {
+        format.encoding = encoding;
+    }
+    if let Some(line_ending) = line_ending {
+        format.line_ending = line_ending;
+    }
+    if let Some(bom) = bom {
+        format.bom = bom;
+    }
+    let bytes = encoding::encode(&content, &format)?;
+
+    if expected_mtime.is_some() || expected_hash.is_some() {
+        // A file deleted since it was loaded has nothing to clobber
+        if let (Some(disk), Ok(metadata)) = (&disk, fs::metadata(&target)) {
+            let disk_version = FileVersion::of(disk, &metadata);
+            let unchanged = match (&expected_hash, expected_mtime) {
+                (Some(hash), _) => *hash == disk_version.hash,
+                (None, Some(mtime)) => mtime == disk_version.mtime,
+                (None, None) => true,
+            };
+            if !unchanged {
+                return Err(WriteFileError::Conflict {
+                    path,
+                    disk_content: encoding::decode(disk).0,
+                    disk_version,
+                });
+            }
+        }
+    }
+
+    atomic_write(&target, &bytes).map_err(|e| format!("Failed to write file '{}': {}", path, e))?;
+
+    let metadata =
+        fs::metadata(&target).map_err(|e| format!("Failed to write file '{}': {}", path, e))?;
+    Ok(FileVersion::of(&bytes, &metadata))
+}
+
+/// Save an editor buffer: like `write_file` keeping the file's encoding,
+/// then tell the language servers with the file open that it was saved.
+#[tauri::command]
+pub fn save_file(
+    lsp: State<'_, LspManager>,
+    scope: State<'_, PathScope>,
+    path: String,
+    content: String,
+    expected_hash: Option<String>,
+) -> Result<FileVersion, WriteFileError> {
+    let options = WriteOptions {
+        expected_hash,
+        ..WriteOptions::default()
+    };
+    let version = write_file(scope, path.clone(), content.clone(), Some(options))?;
+    lsp.did_save(&path, &content);
+    Ok(version)
 }
 
+/// Whether a path exists. Paths outside the allowed roots are reported missing.
 #[tauri::command]
-pub fn file_exists(path: String) -> bool {
-    Path::new(&path).exists()
+pub fn file_exists(scope: State<'_, PathScope>, path: String) -> bool {
+    scope.check(&path).is_ok_and(|path| path.exists())
 }
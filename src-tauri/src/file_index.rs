@@ -0,0 +1,489 @@
+//! Per-project file index for the file finder.
+//!
+//! A project's files are walked once, on the first query, which also starts
+//! watching the project; the index is then kept current from the watcher's
+//! changes instead of walking again. Queries are fuzzy matched and scored
+//! here, with files the user has opened recently or often ranked higher
+//! (frecency).
+
+use crate::file::{atomic_write, project_walk};
+use crate::scope::PathScope;
+use crate::watcher::{FsChange, FsChangeKind, WatcherManager};
+use directories::ProjectDirs;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, State};
+
+const DEFAULT_LIMIT: usize = 100;
+/// Opened files remembered for frecency; the least valuable are dropped beyond this
+const MAX_FRECENCY_ENTRIES: usize = 1000;
+
+const HOUR_MS: u64 = 60 * 60 * 1000;
+const DAY_MS: u64 = 24 * HOUR_MS;
+
+/// A file matching a query. `matches` are UTF-16 offsets into `path` for highlighting.
+#[derive(Debug, Clone, Serialize)]
+pub struct FuzzyMatch {
+    /// Relative to the project root
+    pub path: String,
+    pub score: f64,
+    pub matches: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FrecencyEntry {
+    count: u32,
+    /// Milliseconds since the Unix epoch
+    last_opened: u64,
+}
+
+impl FrecencyEntry {
+    /// Open count weighted by how recently the file was last opened.
+    fn score(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.last_opened);
+        let weight = if age < 4 * HOUR_MS {
+            100.0
+        } else if age < DAY_MS {
+            70.0
+        } else if age < 7 * DAY_MS {
+            50.0
+        } else if age < 30 * DAY_MS {
+            30.0
+        } else {
+            10.0
+        };
+        weight * (self.count as f64).ln_1p()
+    }
+}
+
+/// A project's index, or the changes seen while it's first walked.
+enum ProjectIndex {
+    Building(Vec<FsChange>),
+    Ready(BTreeSet<String>),
+}
+
+/// File indexes by project root, plus frecency of opened files by absolute path.
+pub struct FileIndexManager {
+    indexes: Mutex<HashMap<String, ProjectIndex>>,
+    frecency: Mutex<HashMap<String, FrecencyEntry>>,
+}
+
+impl FileIndexManager {
+    pub fn new() -> Self {
+        let frecency = fs::read(frecency_path())
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+        Self {
+            indexes: Mutex::new(HashMap::new()),
+            frecency: Mutex::new(frecency),
+        }
+    }
+
+    /// Fuzzy match `query` against the project's files, best first. An empty
+    /// query lists recently opened files first.
+    pub fn find(&self, root: &str, query: &str, limit: usize) -> Result<Vec<FuzzyMatch>, String> {
+        let mut indexes = self.indexes.lock();
+        if !matches!(indexes.get(root), Some(ProjectIndex::Ready(_))) {
+            indexes
+                .entry(root.to_string())
+                .or_insert_with(|| ProjectIndex::Building(Vec::new()));
+            // Walk without the lock so other queries aren't held up. Changes
+            // arriving meanwhile are kept and applied afterwards.
+            drop(indexes);
+            let built = build_index(Path::new(root));
+            indexes = self.indexes.lock();
+            match (built, indexes.remove(root)) {
+                // Another query finished walking first
+                (_, Some(ready @ ProjectIndex::Ready(_))) => {
+                    indexes.insert(root.to_string(), ready);
+                }
+                (Ok(mut files), Some(ProjectIndex::Building(changes))) => {
+                    apply_to(&mut files, Path::new(root), &changes);
+                    indexes.insert(root.to_string(), ProjectIndex::Ready(files));
+                }
+                // Invalidated while walking, so the walk answers this query only
+                (Ok(files), None) => return Ok(self.rank(&files, root, query, limit)),
+                (Err(e), _) => return Err(e),
+            }
+        }
+
+        let Some(ProjectIndex::Ready(files)) = indexes.get(root) else {
+            unreachable!("Index stored above");
+        };
+        Ok(self.rank(files, root, query, limit))
+    }
+
+    /// Match and score a project's files against a query, best first.
+    fn rank(
+        &self,
+        files: &BTreeSet<String>,
+        root: &str,
+        query: &str,
+        limit: usize,
+    ) -> Vec<FuzzyMatch> {
+        let frecency = self.frecency.lock();
+        let now = now_millis();
+        let root_path = Path::new(root);
+
+        let mut results: Vec<FuzzyMatch> = files
+            .iter()
+            .filter_map(|path| {
+                let mut m = fuzzy_match(query, path)?;
+                let absolute = root_path.join(path);
+                if let Some(entry) = frecency.get(absolute.to_string_lossy().as_ref()) {
+                    m.score += entry.score(now) / 5.0;
+                }
+                Some(m)
+            })
+            .collect();
+
+        results.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.path.len().cmp(&b.path.len()))
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        results.truncate(limit);
+        results
+    }
+
+    /// Update a project's index from watcher changes. Does nothing if the
+    /// project hasn't been indexed yet.
+    pub fn apply_changes(&self, root: &str, changes: &[FsChange]) {
+        match self.indexes.lock().get_mut(root) {
+            Some(ProjectIndex::Ready(files)) => apply_to(files, Path::new(root), changes),
+            Some(ProjectIndex::Building(pending)) => pending.extend_from_slice(changes),
+            None => {}
+        }
+    }
+
+    /// Drop a project's index, so the next query walks the project again.
+    pub fn invalidate(&self, root: &str) {
+        self.indexes.lock().remove(root);
+    }
+
+    /// Record that a file was opened, for frecency ranking.
+    pub fn record_open(&self, path: &str) -> Result<(), String> {
+        let mut frecency = self.frecency.lock();
+        let now = now_millis();
+        let entry = frecency.entry(path.to_string()).or_default();
+        entry.count += 1;
+        entry.last_opened = now;
+
+        if frecency.len() > MAX_FRECENCY_ENTRIES {
+            let mut scored: Vec<(String, f64)> = frecency
+                .iter()
+                .map(|(path, entry)| (path.clone(), entry.score(now)))
+                .collect();
+            scored.sort_by(|a, b| a.1.total_cmp(&b.1));
+            for (path, _) in scored.iter().take(frecency.len() - MAX_FRECENCY_ENTRIES) {
+                frecency.remove(path);
+            }
+        }
+
+        let path = frecency_path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+        }
+        let json = serde_json::to_vec(&*frecency)
+            .map_err(|e| format!("Failed to serialize frecency: {}", e))?;
+        atomic_write(&path, &json)
+            .map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+    }
+}
+
+/// Apply watcher changes to an index.
+fn apply_to(files: &mut BTreeSet<String>, root: &Path, changes: &[FsChange]) {
+    for change in changes {
+        let path = Path::new(&change.path);
+        match change.kind {
+            FsChangeKind::Created => add_path(files, root, path),
+            FsChangeKind::Removed => remove_path(files, root, path),
+            FsChangeKind::Renamed => {
+                if let Some(old_path) = &change.old_path {
+                    remove_path(files, root, Path::new(old_path));
+                }
+                add_path(files, root, path);
+            }
+            FsChangeKind::Modified => {}
+        }
+    }
+}
+
+/// Walk a project the same way `list_files` does.
+fn build_index(root: &Path) -> Result<BTreeSet<String>, String> {
+    if !root.is_dir() {
+        return Err(format!("Directory does not exist: {}", root.display()));
+    }
+    let mut files = BTreeSet::new();
+    walk_into(&mut files, root, root);
+    Ok(files)
+}
+
+/// Add the files at or below `path` to the index.
+fn walk_into(files: &mut BTreeSet<String>, root: &Path, path: &Path) {
+    for entry in project_walk(path).build().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        if let Some(relative) = relative_path(root, entry.path()) {
+            files.insert(relative);
+        }
+    }
+}
+
+fn add_path(files: &mut BTreeSet<String>, root: &Path, path: &Path) {
+    if path.is_dir() {
+        // A directory moved into the project brings its files with it
+        walk_into(files, root, path);
+    } else if path.is_file() {
+        if let Some(relative) = relative_path(root, path) {
+            files.insert(relative);
+        }
+    }
+}
+
+/// Remove a file, or everything under a directory, from the index.
+fn remove_path(files: &mut BTreeSet<String>, root: &Path, path: &Path) {
+    let Some(relative) = relative_path(root, path) else {
+        return;
+    };
+    files.remove(&relative);
+
+    let prefix = format!("{}/", relative);
+    let nested: Vec<String> = files
+        .range(prefix.clone()..)
+        .take_while(|p| p.starts_with(&prefix))
+        .cloned()
+        .collect();
+    for path in nested {
+        files.remove(&path);
+    }
+}
+
+fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?.to_str()?;
+    (!relative.is_empty()).then(|| relative.to_string())
+}
+
+/// Match the query's characters in order, case-insensitively. Consecutive
+/// matches, matches at the start of a path segment and matches in the file
+/// name score higher; longer paths score slightly lower.
+pub fn fuzzy_match(query: &str, path: &str) -> Option<FuzzyMatch> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    if query.is_empty() {
+        return Some(FuzzyMatch {
+            path: path.to_string(),
+            score: 0.0,
+            matches: Vec::new(),
+        });
+    }
+
+    let file_name_start = path.rfind('/').map_or(0, |i| i + 1);
+    let mut score = 0.0;
+    let mut matches = Vec::with_capacity(query.len());
+    let mut query_index = 0;
+    let mut consecutive = 0;
+    let mut previous: Option<char> = None;
+    let mut last_match: Option<usize> = None;
+    let mut utf16_offset = 0;
+
+    for (char_index, (byte_index, c)) in path.char_indices().enumerate() {
+        if query_index == query.len() {
+            break;
+        }
+        if c.to_lowercase().eq(std::iter::once(query[query_index])) {
+            matches.push(utf16_offset);
+
+            if last_match.is_some_and(|last| last + 1 == char_index) {
+                consecutive += 1;
+                score += consecutive as f64 * 2.0;
+            } else {
+                consecutive = 0;
+            }
+            if matches!(previous, None | Some('/') | Some('\\')) {
+                score += 10.0;
+            }
+            if byte_index >= file_name_start {
+                score += 5.0;
+            }
+            score += 1.0;
+
+            last_match = Some(char_index);
+            query_index += 1;
+        }
+        previous = Some(c);
+        utf16_offset += c.len_utf16() as u32;
+    }
+
+    if query_index != query.len() {
+        return None;
+    }
+
+    score -= path.len() as f64 * 0.1;
+    Some(FuzzyMatch {
+        path: path.to_string(),
+        score,
+        matches,
+    })
+}
+
+fn frecency_path() -> PathBuf {
+    if let Some(proj_dirs) = ProjectDirs::from("com", "innocencelabs", "raven") {
+        proj_dirs.data_dir().join("frecency.json")
+    } else {
+        std::env::temp_dir().join("raven-frecency.json")
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+// Tauri commands
+
+/// Fuzzy find files in a project, indexing it on first use. The project is
+/// watched, if it isn't already, to keep the index current.
+#[tauri::command]
+pub fn fuzzy_find_files(
+    app: AppHandle,
+    state: State<'_, FileIndexManager>,
+    watcher: State<'_, WatcherManager>,
+    scope: State<'_, PathScope>,
+    root: String,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<FuzzyMatch>, String> {
+    scope.check(&root)?;
+    watcher.watch(&root, app)?;
+    state.find(&root, &query, limit.unwrap_or(DEFAULT_LIMIT))
+}
+
+/// Record that a file was opened, so it ranks higher in `fuzzy_find_files`.
+#[tauri::command]
+pub fn record_file_open(
+    state: State<'_, FileIndexManager>,
+    scope: State<'_, PathScope>,
+    path: String,
+) -> Result<(), String> {
+    scope.check(&path)?;
+    state.record_open(&path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use raven_core::test_util::TempDir;
+
+    fn manager() -> FileIndexManager {
+        FileIndexManager {
+            indexes: Mutex::new(HashMap::new()),
+            frecency: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn change(path: &Path, kind: FsChangeKind, old_path: Option<&Path>) -> FsChange {
+        FsChange {
+            path: path.to_string_lossy().to_string(),
+            kind,
+            old_path: old_path.map(|p| p.to_string_lossy().to_string()),
+        }
+    }
+
+    fn paths(manager: &FileIndexManager, root: &Path) -> Vec<String> {
+        let mut paths: Vec<String> = manager
+            .find(&root.to_string_lossy(), "", usize::MAX)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.path)
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn test_fuzzy_match_offsets() {
+        let m = fuzzy_match("MR", "src/main.rs").unwrap();
+        assert_eq!(m.matches, vec![4, 9]);
+        assert!(fuzzy_match("rm", "src/main.rs").is_some());
+        assert!(fuzzy_match("xyz", "src/main.rs").is_none());
+
+        // Offsets are in UTF-16 code units
+        let m = fuzzy_match("a", "🦀/a.rs").unwrap();
+        assert_eq!(m.matches, vec![3]);
+    }
+
+    #[test]
+    fn test_fuzzy_match_scoring() {
+        let score = |query, path| fuzzy_match(query, path).unwrap().score;
+        // Consecutive matches at a segment start in the file name win
+        assert!(score("main", "src/main.rs") > score("main", "src/mxaxixn.rs"));
+        assert!(score("lib", "src/lib.rs") > score("lib", "lib/src/x.rs"));
+        // Shorter paths win ties
+        assert!(score("a", "a.rs") > score("a", "a_long_name.rs"));
+    }
+
+    #[test]
+    fn test_index_follows_changes() {
+        let dir = TempDir::new();
+        dir.write("src/main.rs", "");
+        dir.write("src/old.rs", "");
+        dir.write("docs/guide.md", "");
+        let manager = manager();
+        let root = dir.path();
+        let root_str = root.to_string_lossy().to_string();
+        assert_eq!(
+            paths(&manager, root),
+            vec!["docs/guide.md", "src/main.rs", "src/old.rs"]
+        );
+
+        let added = dir.write("src/new.rs", "");
+        fs::rename(root.join("src/old.rs"), root.join("src/renamed.rs")).unwrap();
+        fs::remove_dir_all(root.join("docs")).unwrap();
+        manager.apply_changes(
+            &root_str,
+            &[
+                change(&added, FsChangeKind::Created, None),
+                change(
+                    &root.join("src/renamed.rs"),
+                    FsChangeKind::Renamed,
+                    Some(&root.join("src/old.rs")),
+                ),
+                change(&root.join("docs"), FsChangeKind::Removed, None),
+            ],
+        );
+        assert_eq!(
+            paths(&manager, root),
+            vec!["src/main.rs", "src/new.rs", "src/renamed.rs"]
+        );
+    }
+
+    #[test]
+    fn test_changes_during_walk_are_applied() {
+        let dir = TempDir::new();
+        let removed = dir.write("a.rs", "");
+        dir.write("b.rs", "");
+        let manager = manager();
+        let root = dir.path().to_string_lossy().to_string();
+
+        // As if a walk were in progress when the change arrived
+        manager
+            .indexes
+            .lock()
+            .insert(root.clone(), ProjectIndex::Building(Vec::new()));
+        manager.apply_changes(&root, &[change(&removed, FsChangeKind::Removed, None)]);
+
+        // The file is still on disk, but the change seen during the walk wins
+        assert_eq!(paths(&manager, dir.path()), vec!["b.rs"]);
+    }
+}
@@ -0,0 +1,187 @@
+//! Creating, renaming, copying and deleting files and directories.
+//!
+//! Deleting moves the path into a trash directory managed by Raven (not the
+//! system trash), from which `restore_path` can put it back. Renames are
+//! announced to language servers with `workspace/willRenameFiles` first, so
+//! they can update imports and module declarations before the move.
+
+use crate::lsp::{
+    path_to_uri, uri_to_path, workspace_edit_paths, LspManager, PendingFileEdits,
+    SerializedWorkspaceEdit,
+};
+use crate::scope::PathScope;
+use raven_core::file::{copy_recursive, move_path};
+use raven_core::trash::{self, TrashEntry};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenamePathResult {
+    /// Edits language servers made for the rename to files open in the editor,
+    /// keyed by their URI after the rename. Edits to other files are already
+    /// applied on disk.
+    pub buffer_edits: Option<SerializedWorkspaceEdit>,
+}
+
+#[tauri::command]
+pub fn create_directory(scope: State<'_, PathScope>, path: String) -> Result<(), String> {
+    let path = scope.check(&path)?;
+    if path.exists() {
+        return Err(format!("Already exists: {}", path.display()));
+    }
+    fs::create_dir_all(&path)
+        .map_err(|e| format!("Failed to create directory '{}': {}", path.display(), e))
+}
+
+/// Rename or move a file or directory. `open_paths` are files open in the
+/// editor, whose language server edits are returned instead of written.
+#[tauri::command]
+pub fn rename_path(
+    lsp: State<'_, LspManager>,
+    scope: State<'_, PathScope>,
+    from: String,
+    to: String,
+    open_paths: Option<Vec<String>>,
+) -> Result<RenamePathResult, String> {
+    let from = scope.check_entry(&from)?;
+    let to = scope.check(&to)?;
+    let (from, to) = (from.as_path(), to.as_path());
+    let open_paths: HashSet<PathBuf> = open_paths
+        .unwrap_or_default()
+        .into_iter()
+        .map(PathBuf::from)
+        .collect();
+    let metadata = fs::symlink_metadata(from)
+        .map_err(|e| format!("Failed to read '{}': {}", from.display(), e))?;
+    // A dangling symlink at `to` counts, as rename would replace it
+    if fs::symlink_metadata(to).is_ok() {
+        return Err(format!("Already exists: {}", to.display()));
+    }
+    if to.starts_with(from) {
+        return Err(format!("Cannot move '{}' into itself", from.display()));
+    }
+    let is_dir = metadata.is_dir();
+
+    // Edits refer to the files as they are before the move, but are only
+    // written once it has succeeded
+    let mut disk_edits = PendingFileEdits::default();
+    let mut buffer_edits = SerializedWorkspaceEdit {
+        changes: None,
+        document_changes: None,
+    };
+    for edit in lsp.will_rename_files(from, to, is_dir) {
+        for path in workspace_edit_paths(&edit)? {
+            scope.check(&path)?;
+        }
+        for (uri, edits) in disk_edits.add(edit, &open_paths)? {
+            buffer_edits
+                .changes
+                .get_or_insert_with(Default::default)
+                .entry(moved_uri(&uri, from, to))
+                .or_default()
+                .extend(edits);
+        }
+    }
+
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory '{}': {}", parent.display(), e))?;
+    }
+    move_path(from, to).map_err(|e| {
+        format!(
+            "Failed to move '{}' to '{}': {}",
+            from.display(),
+            to.display(),
+            e
+        )
+    })?;
+    disk_edits.move_paths(from, to);
+    disk_edits.write()?;
+
+    lsp.did_rename_files(from, to, is_dir);
+
+    Ok(RenamePathResult {
+        buffer_edits: buffer_edits.changes.is_some().then_some(buffer_edits),
+    })
+}
+
+/// A file URI updated for `from` having moved to `to`.
+fn moved_uri(uri: &str, from: &Path, to: &Path) -> String {
+    let Some(path) = uri_to_path(uri) else {
+        return uri.to_string();
+    };
+    match path.strip_prefix(from) {
+        Ok(rest) if rest.as_os_str().is_empty() => path_to_uri(to),
+        Ok(rest) => path_to_uri(&to.join(rest)),
+        Err(_) => uri.to_string(),
+    }
+}
+
+/// Copy a file or directory (recursively). Symlinks are copied as links.
+#[tauri::command]
+pub fn copy_path(scope: State<'_, PathScope>, from: String, to: String) -> Result<(), String> {
+    let from = scope.check_entry(&from)?;
+    let to = scope.check(&to)?;
+    let (from, to) = (from.as_path(), to.as_path());
+    if to.exists() {
+        return Err(format!("Already exists: {}", to.display()));
+    }
+    if to.starts_with(from) {
+        return Err(format!("Cannot copy '{}' into itself", from.display()));
+    }
+    copy_recursive(from, to).map_err(|e| {
+        format!(
+            "Failed to copy '{}' to '{}': {}",
+            from.display(),
+            to.display(),
+            e
+        )
+    })
+}
+
+/// Move a file or directory to the trash.
+#[tauri::command]
+pub fn delete_path(scope: State<'_, PathScope>, path: String) -> Result<TrashEntry, String> {
+    trash::trash_path(&scope.check_entry(&path)?)
+}
+
+/// Put a trashed path back where it was deleted from. Returns the restored path.
+#[tauri::command]
+pub fn restore_path(scope: State<'_, PathScope>, id: String) -> Result<String, String> {
+    let entry = trash::entry(&id)?;
+    let original = scope.check(&entry.original_path)?;
+    trash::untrash(&id, &original)?;
+    Ok(entry.original_path)
+}
+
+/// Trashed paths, most recently deleted first.
+#[tauri::command]
+pub fn list_trash() -> Vec<TrashEntry> {
+    trash::list()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_moved_uri() {
+        let (from, to) = (Path::new("/p/my src"), Path::new("/p/lib%"));
+        assert_eq!(
+            moved_uri("file:///p/my%20src/a.rs", from, to),
+            "file:///p/lib%25/a.rs"
+        );
+        assert_eq!(
+            moved_uri("file:///p/my%20src", from, to),
+            "file:///p/lib%25"
+        );
+        assert_eq!(
+            moved_uri("file:///p/other.rs", from, to),
+            "file:///p/other.rs"
+        );
+    }
+}
@@ -0,0 +1,173 @@
+//! Git working tree status, read by shelling out to `git`.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GitStatus {
+    Modified,
+    Added,
+    Deleted,
+    Renamed,
+    Untracked,
+    Conflicted,
+}
+
+impl GitStatus {
+    fn from_porcelain(xy: &[u8]) -> Self {
+        let (x, y) = (xy[0], xy[1]);
+        match (x, y) {
+            (b'?', b'?') => GitStatus::Untracked,
+            (b'U', _) | (_, b'U') | (b'A', b'A') | (b'D', b'D') => GitStatus::Conflicted,
+            (b'A', _) => GitStatus::Added,
+            (b'D', _) | (_, b'D') => GitStatus::Deleted,
+            (b'R', _) => GitStatus::Renamed,
+            _ => GitStatus::Modified,
+        }
+    }
+
+    /// How prominently a status should show when summarising a directory.
+    fn priority(self) -> u8 {
+        match self {
+            GitStatus::Untracked => 0,
+            GitStatus::Added | GitStatus::Renamed | GitStatus::Deleted => 1,
+            GitStatus::Modified => 2,
+            GitStatus::Conflicted => 3,
+        }
+    }
+}
+
+/// Status of changed paths under `dir`, keyed by absolute path. Untracked
+/// directories appear as a single entry. Returns None if `dir` isn't in a git
+/// repository or git isn't available.
+pub fn status(dir: &Path) -> Option<HashMap<PathBuf, GitStatus>> {
+    // Where `dir` sits in the repository, e.g. "src/lsp/"
+    let prefix = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["rev-parse", "--show-prefix"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    let prefix = String::from_utf8_lossy(&prefix.stdout).trim().to_string();
+
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args([
+            "status",
+            "--porcelain=v1",
+            "-z",
+            "--untracked-files=normal",
+            "--",
+            ".",
+        ])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+
+    Some(parse_porcelain(dir, &prefix, &output.stdout))
+}
+
+/// Parse `git status --porcelain=v1 -z` output run in `dir`, which is at
+/// `prefix` in the repository.
+fn parse_porcelain(dir: &Path, prefix: &str, output: &[u8]) -> HashMap<PathBuf, GitStatus> {
+    // Entries are "XY path\0", with renames followed by "old path\0"
+    let mut statuses = HashMap::new();
+    let mut records = output.split(|&b| b == 0);
+    while let Some(record) = records.next() {
+        if record.len() < 4 {
+            continue;
+        }
+        let status = GitStatus::from_porcelain(&record[..2]);
+        if record[0] == b'R' || record[0] == b'C' {
+            records.next();
+        }
+        // Paths are relative to the repository root, but all lie under `dir`
+        let path = String::from_utf8_lossy(&record[3..]);
+        if let Some(relative) = path.strip_prefix(prefix) {
+            statuses.insert(dir.join(relative.trim_end_matches('/')), status);
+        }
+    }
+    statuses
+}
+
+/// Status of a single entry. A directory takes the most notable status of
+/// anything inside it.
+pub fn status_of(
+    statuses: &HashMap<PathBuf, GitStatus>,
+    path: &Path,
+    is_dir: bool,
+) -> Option<GitStatus> {
+    if let Some(status) = statuses.get(path) {
+        return Some(*status);
+    }
+    if !is_dir {
+        return None;
+    }
+    statuses
+        .iter()
+        .filter(|(changed, _)| changed.starts_with(path))
+        .map(|(_, status)| *status)
+        .max_by_key(|status| status.priority())
+        .map(|status| match status {
+            // A directory containing added or deleted files is itself just modified
+            GitStatus::Untracked | GitStatus::Conflicted => status,
+            _ => GitStatus::Modified,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_porcelain() {
+        assert_eq!(GitStatus::from_porcelain(b"??"), GitStatus::Untracked);
+        assert_eq!(GitStatus::from_porcelain(b" M"), GitStatus::Modified);
+        assert_eq!(GitStatus::from_porcelain(b"MM"), GitStatus::Modified);
+        assert_eq!(GitStatus::from_porcelain(b"A "), GitStatus::Added);
+        assert_eq!(GitStatus::from_porcelain(b" D"), GitStatus::Deleted);
+        assert_eq!(GitStatus::from_porcelain(b"R "), GitStatus::Renamed);
+        assert_eq!(GitStatus::from_porcelain(b"UU"), GitStatus::Conflicted);
+        assert_eq!(GitStatus::from_porcelain(b"AA"), GitStatus::Conflicted);
+        assert_eq!(GitStatus::from_porcelain(b"DU"), GitStatus::Conflicted);
+    }
+
+    #[test]
+    fn test_parse_porcelain() {
+        let dir = Path::new("/repo/src");
+        let output = b" M src/main.rs\0R  src/new.rs\0src/old.rs\0?? src/scratch/\0 M README.md\0";
+        let statuses = parse_porcelain(dir, "src/", output);
+        assert_eq!(
+            statuses,
+            HashMap::from([
+                (dir.join("main.rs"), GitStatus::Modified),
+                (dir.join("new.rs"), GitStatus::Renamed),
+                (dir.join("scratch"), GitStatus::Untracked),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_status_of_directory() {
+        let statuses = HashMap::from([
+            (PathBuf::from("/repo/a/new.rs"), GitStatus::Added),
+            (PathBuf::from("/repo/a/b/gone.rs"), GitStatus::Deleted),
+            (PathBuf::from("/repo/c/scratch"), GitStatus::Untracked),
+            (PathBuf::from("/repo/d/x.rs"), GitStatus::Untracked),
+            (PathBuf::from("/repo/d/y.rs"), GitStatus::Conflicted),
+        ]);
+        let of = |path: &str, is_dir| status_of(&statuses, Path::new(path), is_dir);
+        assert_eq!(of("/repo/a/new.rs", false), Some(GitStatus::Added));
+        assert_eq!(of("/repo/a", true), Some(GitStatus::Modified));
+        assert_eq!(of("/repo/c", true), Some(GitStatus::Untracked));
+        assert_eq!(of("/repo/d", true), Some(GitStatus::Conflicted));
+        assert_eq!(of("/repo/e", true), None);
+        // Files don't take the status of paths under them
+        assert_eq!(of("/repo/a", false), None);
+    }
+}
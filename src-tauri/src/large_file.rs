@@ -0,0 +1,248 @@
+//! Reading files too large to load into the editor in one go.
+//!
+//! `read_file_range` returns raw bytes from any offset. `read_file_lines`
+//! pages through a file by line number, using a sparse index of line start
+//! offsets that is built with one pass over the file and reused until the
+//! file changes.
+
+use crate::file::mtime_millis;
+use crate::scope::PathScope;
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::sync::Arc;
+use tauri::ipc::Response;
+use tauri::State;
+
+/// Largest range `read_file_range` returns at once
+const MAX_RANGE_LEN: u64 = 16 * 1024 * 1024;
+/// Most lines `read_file_lines` returns at once
+const MAX_PAGE_LINES: usize = 10_000;
+/// Lines longer than this are cut short
+const MAX_LINE_BYTES: usize = 64 * 1024;
+/// The index keeps the start offset of every this many lines
+const LINES_PER_CHECKPOINT: u64 = 1000;
+/// Line indexes kept for recently paged files
+const MAX_INDEXES: usize = 8;
+
+/// Byte offsets of every `LINES_PER_CHECKPOINT`th line of a file, as of a
+/// given size and modification time.
+struct LineIndex {
+    path: String,
+    size: u64,
+    mtime: u64,
+    checkpoints: Vec<u64>,
+    total_lines: u64,
+}
+
+impl LineIndex {
+    fn build(path: &str, file: &mut File, size: u64, mtime: u64) -> io::Result<Self> {
+        let mut checkpoints = vec![0];
+        let mut lines = 0u64;
+        let mut offset = 0u64;
+        let mut last_byte = None;
+        let mut buf = vec![0u8; 1024 * 1024];
+
+        file.seek(SeekFrom::Start(0))?;
+        loop {
+            let n = file.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            for (i, &byte) in buf[..n].iter().enumerate() {
+                if byte == b'\n' {
+                    lines += 1;
+                    if lines.is_multiple_of(LINES_PER_CHECKPOINT) {
+                        checkpoints.push(offset + i as u64 + 1);
+                    }
+                }
+            }
+            offset += n as u64;
+            last_byte = Some(buf[n - 1]);
+        }
+
+        // A last line without a trailing newline still counts
+        if last_byte.is_some_and(|b| b != b'\n') {
+            lines += 1;
+        }
+
+        Ok(Self {
+            path: path.to_string(),
+            size,
+            mtime,
+            checkpoints,
+            total_lines: lines,
+        })
+    }
+}
+
+/// A page of lines from a file. Lines are 0-indexed.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileLines {
+    pub start_line: u64,
+    /// Without line endings; invalid UTF-8 is replaced
+    pub lines: Vec<String>,
+    pub total_lines: u64,
+    /// Some lines were longer than the limit and were cut short
+    pub truncated: bool,
+}
+
+/// Line indexes of recently paged files, most recently used first.
+pub struct LineIndexManager {
+    indexes: Mutex<Vec<Arc<LineIndex>>>,
+}
+
+impl LineIndexManager {
+    pub fn new() -> Self {
+        Self {
+            indexes: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Read `count` lines starting at `start_line`.
+    pub fn read_lines(
+        &self,
+        path: &str,
+        start_line: u64,
+        count: usize,
+    ) -> Result<FileLines, String> {
+        let read_error = |e: io::Error| format!("Failed to read file '{}': {}", path, e);
+        let mut file = File::open(path).map_err(read_error)?;
+        let metadata = file.metadata().map_err(read_error)?;
+        let (size, mtime) = (metadata.len(), mtime_millis(&metadata));
+
+        let index = match self.cached(path, size, mtime) {
+            Some(index) => index,
+            None => {
+                let index =
+                    Arc::new(LineIndex::build(path, &mut file, size, mtime).map_err(read_error)?);
+                self.insert(index.clone());
+                index
+            }
+        };
+
+        let mut lines = Vec::new();
+        let mut truncated = false;
+        if start_line < index.total_lines {
+            let checkpoint = (start_line / LINES_PER_CHECKPOINT) as usize;
+            file.seek(SeekFrom::Start(index.checkpoints[checkpoint]))
+                .map_err(read_error)?;
+            let mut reader = BufReader::with_capacity(256 * 1024, file);
+
+            let skip = start_line - checkpoint as u64 * LINES_PER_CHECKPOINT;
+            for _ in 0..skip {
+                read_line(&mut reader, 0).map_err(read_error)?;
+            }
+            let count = count.min(MAX_PAGE_LINES);
+            while lines.len() < count {
+                let Some((line, cut)) =
+                    read_line(&mut reader, MAX_LINE_BYTES).map_err(read_error)?
+                else {
+                    break;
+                };
+                truncated |= cut;
+                lines.push(line);
+            }
+        }
+
+        Ok(FileLines {
+            start_line,
+            lines,
+            total_lines: index.total_lines,
+            truncated,
+        })
+    }
+
+    /// The cached index for a file, if it's still current.
+    fn cached(&self, path: &str, size: u64, mtime: u64) -> Option<Arc<LineIndex>> {
+        let mut indexes = self.indexes.lock();
+        let position = indexes.iter().position(|i| i.path == path)?;
+        let index = indexes.remove(position);
+        if index.size != size || index.mtime != mtime {
+            return None;
+        }
+        indexes.insert(0, index.clone());
+        Some(index)
+    }
+
+    fn insert(&self, index: Arc<LineIndex>) {
+        let mut indexes = self.indexes.lock();
+        indexes.retain(|i| i.path != index.path);
+        indexes.insert(0, index);
+        indexes.truncate(MAX_INDEXES);
+    }
+}
+
+/// Read one line, keeping at most `max_len` bytes of it. Returns the line
+/// without its ending and whether it was cut short, or None at end of file.
+fn read_line(reader: &mut impl BufRead, max_len: usize) -> io::Result<Option<(String, bool)>> {
+    let mut line = Vec::new();
+    let mut cut = false;
+    let mut read_any = false;
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        read_any = true;
+        let (chunk, found_newline) = match buf.iter().position(|&b| b == b'\n') {
+            Some(i) => (&buf[..i], true),
+            None => (buf, false),
+        };
+        let room = max_len.saturating_sub(line.len());
+        if chunk.len() > room {
+            cut = true;
+        }
+        line.extend_from_slice(&chunk[..chunk.len().min(room)]);
+        let consumed = chunk.len() + found_newline as usize;
+        reader.consume(consumed);
+        if found_newline {
+            break;
+        }
+    }
+
+    if !read_any {
+        return Ok(None);
+    }
+    if line.last() == Some(&b'\r') {
+        line.pop();
+    }
+    Ok(Some((String::from_utf8_lossy(&line).into_owned(), cut)))
+}
+
+// Tauri commands
+
+/// Read up to `length` bytes from `offset`, returned to the frontend as an
+/// ArrayBuffer. Reads past the end of the file return fewer bytes.
+#[tauri::command]
+pub fn read_file_range(
+    scope: State<'_, PathScope>,
+    path: String,
+    offset: u64,
+    length: u64,
+) -> Result<Response, String> {
+    let read_error = |e: io::Error| format!("Failed to read file '{}': {}", path, e);
+    let mut file = File::open(scope.check(&path)?).map_err(read_error)?;
+    file.seek(SeekFrom::Start(offset)).map_err(read_error)?;
+
+    let mut bytes = Vec::new();
+    file.take(length.min(MAX_RANGE_LEN))
+        .read_to_end(&mut bytes)
+        .map_err(read_error)?;
+    Ok(Response::new(bytes))
+}
+
+/// Read a page of lines from a file of any size.
+#[tauri::command]
+pub fn read_file_lines(
+    state: State<'_, LineIndexManager>,
+    scope: State<'_, PathScope>,
+    path: String,
+    start_line: u64,
+    count: usize,
+) -> Result<FileLines, String> {
+    scope.check(&path)?;
+    state.read_lines(&path, start_line, count)
+}
@@ -1,20 +1,42 @@
 mod daemon;
 mod file;
+mod file_index;
+mod file_ops;
+mod git;
+mod large_file;
 mod lsp;
 mod pty;
+mod replace;
+mod scope;
+mod search;
+mod watcher;
 
 use daemon::{
-    daemon_attach, daemon_detach, daemon_kill, daemon_list, daemon_resize, daemon_spawn,
-    daemon_write, DaemonManager,
+    daemon_attach, daemon_detach, daemon_kill, daemon_list, daemon_logs, daemon_resize,
+    daemon_signal, daemon_spawn, daemon_write, DaemonManager,
 };
-use file::{file_exists, list_files, read_file, write_file};
+use file::{
+    file_exists, file_version, list_directory, list_files, read_file, save_file, write_file,
+};
+use file_index::{fuzzy_find_files, record_file_open, FileIndexManager};
+use file_ops::{copy_path, create_directory, delete_path, list_trash, rename_path, restore_path};
+use large_file::{read_file_lines, read_file_range, LineIndexManager};
 use lsp::{
-    lsp_change_document, lsp_close_document, lsp_code_actions, lsp_completion, lsp_find_root,
-    lsp_goto_definition, lsp_hover, lsp_open_document, lsp_references, lsp_resolve_code_action,
-    lsp_start, lsp_stop, LspManager,
+    lsp_apply_edit_result, lsp_apply_workspace_edit, lsp_change_document, lsp_close_document,
+    lsp_code_actions, lsp_completion, lsp_document_symbols, lsp_find_root, lsp_format_document,
+    lsp_format_range, lsp_goto_definition, lsp_hover, lsp_incoming_calls, lsp_inlay_hints,
+    lsp_open_document, lsp_outgoing_calls, lsp_prepare_call_hierarchy, lsp_prepare_rename,
+    lsp_prepare_type_hierarchy, lsp_references, lsp_rename, lsp_resolve_code_action,
+    lsp_resolve_inlay_hint, lsp_semantic_tokens, lsp_semantic_tokens_range, lsp_signature_help,
+    lsp_start, lsp_stop, lsp_subtypes, lsp_supertypes, lsp_will_save, lsp_workspace_symbols,
+    LspManager,
 };
 use pty::{pty_kill, pty_resize, pty_spawn, pty_write, PtyManager};
+use replace::{preview_replace_in_project, replace_in_project, undo_replace};
+use scope::{allow_path, pick_project_directory, revoke_path, PathScope};
+use search::{cancel_search, search_project, SearchManager};
 use tauri::WebviewWindow;
+use watcher::{unwatch_project, watch_project, WatcherManager};
 
 // Window control commands
 #[tauri::command]
@@ -57,6 +79,11 @@ pub fn run() {
         .manage(PtyManager::new())
         .manage(DaemonManager::new())
         .manage(LspManager::new())
+        .manage(WatcherManager::new())
+        .manage(SearchManager::new())
+        .manage(FileIndexManager::new())
+        .manage(LineIndexManager::new())
+        .manage(PathScope::new())
         .setup(|_app| Ok(()))
         .invoke_handler(tauri::generate_handler![
             // Window controls
@@ -74,15 +101,41 @@ pub fn run() {
             daemon_spawn,
             daemon_write,
             daemon_resize,
+            daemon_signal,
             daemon_detach,
             daemon_kill,
             daemon_list,
             daemon_attach,
+            daemon_logs,
             // File operations
+            pick_project_directory,
+            allow_path,
+            revoke_path,
             read_file,
+            read_file_range,
+            read_file_lines,
             write_file,
+            save_file,
+            file_version,
             file_exists,
             list_files,
+            list_directory,
+            fuzzy_find_files,
+            record_file_open,
+            create_directory,
+            rename_path,
+            copy_path,
+            delete_path,
+            restore_path,
+            list_trash,
+            watch_project,
+            unwatch_project,
+            // Search
+            search_project,
+            cancel_search,
+            preview_replace_in_project,
+            replace_in_project,
+            undo_replace,
             // LSP commands
             lsp_find_root,
             lsp_start,
@@ -95,7 +148,27 @@ pub fn run() {
             lsp_completion,
             lsp_references,
             lsp_code_actions,
-            lsp_resolve_code_action
+            lsp_resolve_code_action,
+            lsp_prepare_rename,
+            lsp_rename,
+            lsp_apply_workspace_edit,
+            lsp_document_symbols,
+            lsp_workspace_symbols,
+            lsp_format_document,
+            lsp_format_range,
+            lsp_will_save,
+            lsp_signature_help,
+            lsp_inlay_hints,
+            lsp_resolve_inlay_hint,
+            lsp_semantic_tokens,
+            lsp_semantic_tokens_range,
+            lsp_prepare_call_hierarchy,
+            lsp_incoming_calls,
+            lsp_outgoing_calls,
+            lsp_prepare_type_hierarchy,
+            lsp_supertypes,
+            lsp_subtypes,
+            lsp_apply_edit_result
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
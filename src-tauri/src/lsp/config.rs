@@ -0,0 +1,466 @@
+//! Language server configuration.
+//!
+//! Built-in servers cover the languages Raven knows about. They can be
+//! changed, disabled or added to in a user config file (`lsp.json` in Raven's
+//! config directory):
+//!
+//! ```json
+//! {
+//!   "servers": {
+//!     "rust-analyzer": {
+//!       "formatOnSave": true,
+//!       "initializationOptions": { "check": { "command": "clippy" } },
+//!       "settings": { "rust-analyzer": { "cargo": { "features": "all" } } }
+//!     },
+//!     "yaml-language-server": { "enabled": false },
+//!     "pyright": {
+//!       "command": "pyright-langserver",
+//!       "args": ["--stdio"],
+//!       "languages": ["python"],
+//!       "rootMarkers": ["pyproject.toml", "setup.py"]
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! A project config file (`.raven/lsp.json` in the project or any directory
+//! above it) can tune the `settings` and `initializationOptions` of servers
+//! configured for the user, project settings winning. It comes with the
+//! project, so it can't choose which servers run or how they're started.
+
+use directories::ProjectDirs;
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the config file, in the user config directory or a project's `.raven`
+const CONFIG_FILE: &str = "lsp.json";
+
+/// Configuration for a language server.
+#[derive(Debug, Clone)]
+pub struct LanguageServerConfig {
+    /// Unique name, used as the key in config files
+    pub name: String,
+    pub command: String,
+    pub args: Vec<String>,
+    /// Extra environment variables for the server process
+    pub env: HashMap<String, String>,
+    /// Language IDs the server handles
+    pub languages: Vec<String>,
+    /// Files or directories marking the root of a project the server can open.
+    /// The nearest directory containing any of them is used.
+    pub root_markers: Vec<String>,
+    /// Sent as `initializationOptions` in the initialize request
+    pub initialization_options: Option<JsonValue>,
+    /// Answers `workspace/configuration` requests, looked up by section,
+    /// e.g. `{"rust-analyzer": {...}}`
+    pub settings: Option<JsonValue>,
+    /// Format documents with this server when they're saved
+    pub format_on_save: bool,
+}
+
+impl LanguageServerConfig {
+    fn new(name: &str, command: &str, args: &[&str], languages: &[&str], markers: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            command: command.to_string(),
+            args: args.iter().map(|s| s.to_string()).collect(),
+            env: HashMap::new(),
+            languages: languages.iter().map(|s| s.to_string()).collect(),
+            root_markers: markers.iter().map(|s| s.to_string()).collect(),
+            initialization_options: None,
+            settings: None,
+            format_on_save: false,
+        }
+    }
+
+    /// rust-analyzer configuration.
+    pub fn rust_analyzer() -> Self {
+        Self::new(
+            "rust-analyzer",
+            "rust-analyzer",
+            &[],
+            &["rust"],
+            &["Cargo.toml"],
+        )
+    }
+
+    /// Servers available without any configuration.
+    fn builtin() -> Vec<Self> {
+        vec![
+            Self::rust_analyzer(),
+            Self::new(
+                "typescript-language-server",
+                "typescript-language-server",
+                &["--stdio"],
+                &[
+                    "typescript",
+                    "typescriptreact",
+                    "javascript",
+                    "javascriptreact",
+                ],
+                &["tsconfig.json", "jsconfig.json", "package.json"],
+            ),
+            Self::new(
+                "vscode-json-language-server",
+                "vscode-json-language-server",
+                &["--stdio"],
+                &["json"],
+                &["package.json", ".git"],
+            ),
+            Self::new(
+                "taplo",
+                "taplo",
+                &["lsp", "stdio"],
+                &["toml"],
+                &[
+                    "taplo.toml",
+                    ".taplo.toml",
+                    "Cargo.toml",
+                    "pyproject.toml",
+                    ".git",
+                ],
+            ),
+            Self::new(
+                "yaml-language-server",
+                "yaml-language-server",
+                &["--stdio"],
+                &["yaml"],
+                &[".git"],
+            ),
+        ]
+    }
+
+    /// Whether the server handles a language.
+    pub fn handles(&self, language_id: &str) -> bool {
+        self.languages.iter().any(|l| l == language_id)
+    }
+
+    /// The nearest directory above `file_path` containing one of the root markers.
+    pub fn find_root(&self, file_path: &Path) -> Option<PathBuf> {
+        file_path
+            .ancestors()
+            .skip(1)
+            .find(|dir| self.root_markers.iter().any(|m| dir.join(m).exists()))
+            .map(Path::to_path_buf)
+    }
+
+    /// The settings under a dot-separated section, or all of them without
+    /// one. Null when the section isn't set.
+    pub fn configuration(&self, section: Option<&str>) -> JsonValue {
+        let Some(settings) = &self.settings else {
+            return JsonValue::Null;
+        };
+        section
+            .into_iter()
+            .flat_map(|s| s.split('.'))
+            .try_fold(settings, |value, key| value.get(key))
+            .cloned()
+            .unwrap_or(JsonValue::Null)
+    }
+}
+
+/// A server entry in a config file. Fields left out keep the built-in value,
+/// so an entry can change one setting of a built-in server.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ServerOverride {
+    enabled: Option<bool>,
+    command: Option<String>,
+    args: Option<Vec<String>>,
+    env: Option<HashMap<String, String>>,
+    languages: Option<Vec<String>>,
+    root_markers: Option<Vec<String>>,
+    initialization_options: Option<JsonValue>,
+    settings: Option<JsonValue>,
+    format_on_save: Option<bool>,
+}
+
+impl ServerOverride {
+    /// Fields set that only the user config may set.
+    fn user_only_fields(&self) -> Vec<&'static str> {
+        [
+            ("enabled", self.enabled.is_some()),
+            ("command", self.command.is_some()),
+            ("args", self.args.is_some()),
+            ("env", self.env.is_some()),
+            ("languages", self.languages.is_some()),
+            ("rootMarkers", self.root_markers.is_some()),
+            ("formatOnSave", self.format_on_save.is_some()),
+        ]
+        .into_iter()
+        .filter(|(_, set)| *set)
+        .map(|(field, _)| field)
+        .collect()
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    servers: HashMap<String, ServerOverride>,
+}
+
+/// The language servers to use for files in a directory.
+#[derive(Debug, Clone)]
+pub struct ServerRegistry {
+    servers: Vec<LanguageServerConfig>,
+}
+
+impl ServerRegistry {
+    /// Built-in servers with the user config and the nearest project config
+    /// at or above `dir` applied.
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let mut registry = Self {
+            servers: LanguageServerConfig::builtin(),
+        };
+        if let Some(path) = user_config_path() {
+            registry.apply_file(&path, true)?;
+        }
+        if let Some(path) = dir
+            .ancestors()
+            .map(|d| d.join(".raven").join(CONFIG_FILE))
+            .find(|p| p.is_file())
+        {
+            registry.apply_file(&path, false)?;
+        }
+        Ok(registry)
+    }
+
+    /// Apply a config file. Only a `trusted` file, the user's own, may change
+    /// how servers are run.
+    fn apply_file(&mut self, path: &Path, trusted: bool) -> Result<(), String> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(format!("Failed to read '{}': {}", path.display(), e)),
+        };
+        let config: ConfigFile = serde_json::from_slice(&bytes)
+            .map_err(|e| format!("Invalid language server config '{}': {}", path.display(), e))?;
+
+        // Sorted so servers added by one file keep a stable order
+        let mut overrides: Vec<(String, ServerOverride)> = config.servers.into_iter().collect();
+        overrides.sort_by(|a, b| a.0.cmp(&b.0));
+        for (name, entry) in overrides {
+            let applied = if trusted {
+                self.apply(name, entry)
+            } else {
+                self.apply_untrusted(name, entry)
+            };
+            applied.map_err(|e| {
+                format!("Invalid language server config '{}': {}", path.display(), e)
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Apply an entry from a project config, which may only tune servers the
+    /// user already has.
+    fn apply_untrusted(&mut self, name: String, entry: ServerOverride) -> Result<(), String> {
+        let fields = entry.user_only_fields();
+        if !fields.is_empty() {
+            return Err(format!(
+                "server '{}' sets {}, which only the user config may set",
+                name,
+                fields.join(", ")
+            ));
+        }
+        if !self.servers.iter().any(|s| s.name == name) {
+            return Err(format!("unknown server '{}'", name));
+        }
+        self.apply(name, entry)
+    }
+
+    fn apply(&mut self, name: String, entry: ServerOverride) -> Result<(), String> {
+        if entry.enabled == Some(false) {
+            self.servers.retain(|s| s.name != name);
+            return Ok(());
+        }
+
+        let server = match self.servers.iter_mut().find(|s| s.name == name) {
+            Some(server) => server,
+            None => {
+                let command = entry
+                    .command
+                    .clone()
+                    .ok_or_else(|| format!("server '{}' has no command", name))?;
+                self.servers.push(LanguageServerConfig {
+                    name: name.clone(),
+                    command,
+                    args: Vec::new(),
+                    env: HashMap::new(),
+                    languages: Vec::new(),
+                    root_markers: vec![".git".to_string()],
+                    initialization_options: None,
+                    settings: None,
+                    format_on_save: false,
+                });
+                self.servers.last_mut().unwrap()
+            }
+        };
+
+        if let Some(command) = entry.command {
+            server.command = command;
+        }
+        if let Some(args) = entry.args {
+            server.args = args;
+        }
+        if let Some(env) = entry.env {
+            server.env.extend(env);
+        }
+        if let Some(languages) = entry.languages {
+            server.languages = languages;
+        }
+        if let Some(root_markers) = entry.root_markers {
+            server.root_markers = root_markers;
+        }
+        if entry.initialization_options.is_some() {
+            server.initialization_options = entry.initialization_options;
+        }
+        if entry.settings.is_some() {
+            server.settings = entry.settings;
+        }
+        if let Some(format_on_save) = entry.format_on_save {
+            server.format_on_save = format_on_save;
+        }
+        Ok(())
+    }
+
+    /// Servers for a language, in priority order.
+    pub fn servers_for(&self, language_id: &str) -> Vec<&LanguageServerConfig> {
+        self.servers
+            .iter()
+            .filter(|s| s.handles(language_id))
+            .collect()
+    }
+}
+
+fn user_config_path() -> Option<PathBuf> {
+    ProjectDirs::from("com", "innocencelabs", "raven")
+        .map(|dirs| dirs.config_dir().join(CONFIG_FILE))
+}
+
+/// Detect language ID from file extension.
+pub fn detect_language(file_path: &str) -> String {
+    let path = Path::new(file_path);
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("rs") => "rust".to_string(),
+        Some("ts") | Some("mts") | Some("cts") => "typescript".to_string(),
+        Some("tsx") => "typescriptreact".to_string(),
+        Some("js") | Some("mjs") | Some("cjs") => "javascript".to_string(),
+        Some("jsx") => "javascriptreact".to_string(),
+        Some("json") => "json".to_string(),
+        Some("md") => "markdown".to_string(),
+        Some("toml") => "toml".to_string(),
+        Some("yaml") | Some("yml") => "yaml".to_string(),
+        Some("py") => "python".to_string(),
+        Some("go") => "go".to_string(),
+        _ => "plaintext".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use raven_core::test_util::TempDir;
+
+    fn builtin() -> ServerRegistry {
+        ServerRegistry {
+            servers: LanguageServerConfig::builtin(),
+        }
+    }
+
+    fn server<'a>(registry: &'a ServerRegistry, name: &str) -> Option<&'a LanguageServerConfig> {
+        registry.servers.iter().find(|s| s.name == name)
+    }
+
+    #[test]
+    fn test_user_config() {
+        let dir = TempDir::new();
+        let path = dir.write(
+            "lsp.json",
+            r#"{"servers": {
+                "rust-analyzer": {"args": ["--log"], "formatOnSave": true},
+                "yaml-language-server": {"enabled": false},
+                "pyright": {"command": "pyright-langserver", "languages": ["python"]}
+            }}"#,
+        );
+        let mut registry = builtin();
+        registry.apply_file(&path, true).unwrap();
+
+        let rust = server(&registry, "rust-analyzer").unwrap();
+        assert_eq!(rust.command, "rust-analyzer");
+        assert_eq!(rust.args, vec!["--log"]);
+        assert!(rust.format_on_save);
+        assert!(server(&registry, "yaml-language-server").is_none());
+        let python = registry.servers_for("python");
+        assert_eq!(python.len(), 1);
+        assert_eq!(python[0].command, "pyright-langserver");
+        assert_eq!(python[0].root_markers, vec![".git"]);
+
+        let path = dir.write("bad.json", r#"{"servers": {"new": {"languages": ["go"]}}}"#);
+        assert!(registry.apply_file(&path, true).is_err());
+    }
+
+    #[test]
+    fn test_configuration_sections() {
+        let mut config = LanguageServerConfig::rust_analyzer();
+        assert_eq!(config.configuration(Some("rust-analyzer")), JsonValue::Null);
+
+        config.settings = Some(serde_json::json!({
+            "rust-analyzer": {"cargo": {"features": "all"}, "check": {"command": "clippy"}}
+        }));
+        assert_eq!(
+            config.configuration(Some("rust-analyzer.cargo")),
+            serde_json::json!({"features": "all"})
+        );
+        assert_eq!(
+            config.configuration(Some("rust-analyzer.check.command")),
+            serde_json::json!("clippy")
+        );
+        assert_eq!(config.configuration(None), config.settings.clone().unwrap());
+        assert_eq!(
+            config.configuration(Some("rust-analyzer.missing")),
+            JsonValue::Null
+        );
+        assert_eq!(config.configuration(Some("editor")), JsonValue::Null);
+    }
+
+    #[test]
+    fn test_project_config_tunes_settings() {
+        let dir = TempDir::new();
+        let path = dir.write(
+            ".raven/lsp.json",
+            r#"{"servers": {"rust-analyzer": {
+                "settings": {"rust-analyzer": {"cargo": {"features": "all"}}},
+                "initializationOptions": {"check": {"command": "clippy"}}
+            }}}"#,
+        );
+        let mut registry = builtin();
+        registry.apply_file(&path, false).unwrap();
+
+        let rust = server(&registry, "rust-analyzer").unwrap();
+        assert!(rust.settings.is_some());
+        assert!(rust.initialization_options.is_some());
+    }
+
+    #[test]
+    fn test_project_config_cannot_run_commands() {
+        let dir = TempDir::new();
+        for entry in [
+            r#"{"rust-analyzer": {"command": "/tmp/evil"}}"#,
+            r#"{"rust-analyzer": {"args": ["--evil"]}}"#,
+            r#"{"rust-analyzer": {"env": {"LD_PRELOAD": "/tmp/evil.so"}}}"#,
+            r#"{"evil": {"command": "/tmp/evil", "languages": ["rust"]}}"#,
+            r#"{"evil": {"settings": {}}}"#,
+        ] {
+            let path = dir.write(".raven/lsp.json", format!(r#"{{"servers": {}}}"#, entry));
+            let mut registry = builtin();
+            assert!(registry.apply_file(&path, false).is_err(), "{}", entry);
+            assert_eq!(registry.servers_for("rust").len(), 1);
+            assert_eq!(registry.servers_for("rust")[0].command, "rust-analyzer");
+        }
+    }
+}
@@ -1,96 +1,193 @@
 //! LSP Manager - orchestrates language servers for projects.
 
+use super::config::{detect_language, LanguageServerConfig, ServerRegistry};
+use super::edit::{apply_text_edits, path_to_uri, position_in_bounds, uri_to_path};
 use super::protocol::{
-    CodeAction, CodeActionContext, CodeActionParams, CodeActionResponse, CodeActionTriggerKind,
-    CompletionItem, CompletionResponse, DefinitionResponse, Diagnostic,
-    DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams, Hover,
-    HoverContents, Location, MarkedString, Position, Range, ReferenceContext, ReferenceParams,
-    TextDocumentContentChangeEvent, TextDocumentIdentifier, TextDocumentItem,
-    TextDocumentPositionParams, VersionedTextDocumentIdentifier,
+    ApplyWorkspaceEditResult, CallHierarchyIncomingCall, CallHierarchyOutgoingCall, CodeAction,
+    CodeActionContext, CodeActionParams, CodeActionResponse, CodeActionTriggerKind, CompletionItem,
+    CompletionResponse, DefinitionResponse, Diagnostic, DidChangeTextDocumentParams,
+    DidChangeWatchedFilesParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
+    DidSaveTextDocumentParams, DocumentFormattingParams, DocumentRangeFormattingParams,
+    DocumentSymbol, DocumentSymbolParams, DocumentSymbolResponse, Documentation, FileChangeType,
+    FileEvent, FileRename, FormattingOptions, HierarchyItem, HierarchyItemParams, Hover,
+    HoverContents, InlayHint, InlayHintParams, Location, MarkedString, ParameterLabel, Position,
+    PrepareRenameResponse, Range, ReferenceContext, ReferenceParams, RenameFilesParams,
+    RenameParams, SemanticTokens, SemanticTokensDeltaParams, SemanticTokensDeltaResponse,
+    SemanticTokensParams, SemanticTokensRangeParams, ServerCapabilities, SignatureHelp,
+    SignatureHelpContext, SignatureHelpParams, SignatureHelpTriggerKind, SignatureInformation,
+    SymbolInformation, SymbolKind, TextDocumentContentChangeEvent, TextDocumentIdentifier,
+    TextDocumentItem, TextDocumentPositionParams, TextDocumentSaveReason, TextDocumentSyncKind,
+    TextEdit, VersionedTextDocumentIdentifier, WillSaveTextDocumentParams, WorkspaceEdit,
+    WorkspaceSymbol, WorkspaceSymbolLocation, WorkspaceSymbolParams,
 };
+use super::semantic_tokens::{apply_delta, decode, SerializedSemanticToken};
 use super::server::{spawn_and_initialize, LanguageServer, ServerNotification};
 use parking_lot::Mutex;
+use raven_core::lsp::{
+    SerializedPosition, SerializedRange, SerializedTextEdit, SerializedWorkspaceEdit,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc;
 use std::sync::Arc;
 use std::thread;
+use std::time::Duration;
 use tauri::{AppHandle, Emitter};
 
-/// Configuration for a language server.
-#[derive(Debug, Clone)]
-pub struct LanguageServerConfig {
-    pub command: String,
-    pub args: Vec<String>,
-}
-
-impl LanguageServerConfig {
-    /// rust-analyzer configuration.
-    pub fn rust_analyzer() -> Self {
-        Self {
-            command: "rust-analyzer".to_string(),
-            args: vec![],
-        }
-    }
-}
+/// How long a rename waits for a server's edits before going ahead without them
+const WILL_RENAME_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long a save waits for a server's edits before going ahead without them
+const WILL_SAVE_TIMEOUT: Duration = Duration::from_secs(3);
+/// How long the editor gets to apply an edit a server asked for
+const APPLY_EDIT_TIMEOUT: Duration = Duration::from_secs(30);
 
 /// Tracks an open document.
 #[derive(Debug, Clone)]
 struct OpenDocument {
     version: i32,
+    /// The document as the server has it, with all changes applied
+    text: String,
+    /// The last whole-document semantic tokens, which deltas apply to
+    semantic_tokens: Option<SemanticTokens>,
 }
 
-/// State for a project's LSP connection.
+/// State for a language server running for a project.
 struct ProjectLsp {
+    config: LanguageServerConfig,
+    /// Position among the servers for its languages; lower answers requests first
+    priority: usize,
     server: Arc<LanguageServer>,
     documents: Mutex<HashMap<String, OpenDocument>>,
+    /// The last signature help shown and the URI of its document, sent back
+    /// to the server when it's updated
+    signature_help: Mutex<Option<(String, SignatureHelp)>>,
 }
 
 /// Manages all LSP connections.
 pub struct LspManager {
-    /// Language server configs by language ID
-    configs: Vec<LanguageServerConfig>,
-    /// Active servers by root path
-    servers: Mutex<HashMap<String, Arc<ProjectLsp>>>,
+    /// Active servers by root path and server name
+    servers: Mutex<HashMap<(String, String), Arc<ProjectLsp>>>,
+    /// Edits servers asked for that the editor hasn't answered yet, by ID
+    pending_edits: Arc<Mutex<HashMap<u64, mpsc::Sender<ApplyWorkspaceEditResult>>>>,
+    next_edit_id: Arc<AtomicU64>,
+}
+
+/// Answer an edit the editor hasn't applied in time as not applied, so the
+/// server stops waiting and a late answer from the editor is rejected.
+fn expire_edit(
+    pending_edits: &Mutex<HashMap<u64, mpsc::Sender<ApplyWorkspaceEditResult>>>,
+    id: u64,
+) {
+    if let Some(reply) = pending_edits.lock().remove(&id) {
+        let _ = reply.send(ApplyWorkspaceEditResult {
+            applied: false,
+            failure_reason: Some("Editor did not apply the edit in time".to_string()),
+        });
+    }
 }
 
 impl LspManager {
     pub fn new() -> Self {
         Self {
-            configs: vec![LanguageServerConfig::rust_analyzer()],
             servers: Mutex::new(HashMap::new()),
+            pending_edits: Arc::new(Mutex::new(HashMap::new())),
+            next_edit_id: Arc::new(AtomicU64::new(1)),
+        }
+    }
+
+    /// Answer an edit a server asked the editor to apply.
+    pub fn apply_edit_result(
+        &self,
+        id: u64,
+        applied: bool,
+        failure_reason: Option<String>,
+    ) -> Result<(), String> {
+        let reply = self
+            .pending_edits
+            .lock()
+            .remove(&id)
+            .ok_or_else(|| format!("No edit waiting with ID {}", id))?;
+        let _ = reply.send(ApplyWorkspaceEditResult {
+            applied,
+            failure_reason,
+        });
+        Ok(())
+    }
+
+    /// Find the project root for a file: the root of the first configured
+    /// server for its language that finds one.
+    pub fn find_root(&self, file_path: &str) -> Option<String> {
+        let path = Path::new(file_path);
+        let registry = ServerRegistry::load(path.parent()?).ok()?;
+        registry
+            .servers_for(&detect_language(file_path))
+            .into_iter()
+            .find_map(|config| config.find_root(path))
+            .map(|root| root.to_string_lossy().to_string())
+    }
+
+    /// Start the language servers for a file's language in a project. Succeeds
+    /// if at least one of them starts.
+    pub fn start(&self, root_path: &str, file_path: &str, app: AppHandle) -> Result<(), String> {
+        let language_id = detect_language(file_path);
+        let registry = ServerRegistry::load(Path::new(root_path))?;
+        let configs = registry.servers_for(&language_id);
+        if configs.is_empty() {
+            return Err(format!("No language server configured for {}", language_id));
         }
+
+        let mut errors = Vec::new();
+        for (priority, config) in configs.iter().enumerate() {
+            if let Err(e) = self.start_server(root_path, (*config).clone(), priority, app.clone()) {
+                errors.push(format!("{}: {}", config.name, e));
+            }
+        }
+        if errors.len() == configs.len() {
+            return Err(errors.join("; "));
+        }
+        Ok(())
     }
 
-    /// Start a language server for a project.
-    pub fn start(&self, root_path: &str, app: AppHandle) -> Result<(), String> {
-        let root_uri = format!("file://{}", root_path);
+    /// Start one language server for a project, unless it's already running.
+    fn start_server(
+        &self,
+        root_path: &str,
+        config: LanguageServerConfig,
+        priority: usize,
+        app: AppHandle,
+    ) -> Result<(), String> {
+        let root_uri = path_to_uri(Path::new(&root_path));
+        let key = (root_path.to_string(), config.name.clone());
 
         // Check if already running
         {
             let servers = self.servers.lock();
-            if servers.contains_key(root_path) {
+            if servers.contains_key(&key) {
                 return Ok(());
             }
         }
 
-        // Find config (for now, assume rust-analyzer)
-        let config = &self.configs[0]; // TODO: proper config selection
-
         // Create notification channel
         let (notif_sender, notif_receiver) = mpsc::channel::<ServerNotification>();
 
         // Spawn server
-        let server = spawn_and_initialize(&config.command, &config.args, &root_uri, notif_sender)?;
+        let server = spawn_and_initialize(&config, &root_uri, notif_sender)?;
 
         let project = Arc::new(ProjectLsp {
+            config,
+            priority,
             server,
             documents: Mutex::new(HashMap::new()),
+            signature_help: Mutex::new(None),
         });
 
         // Start notification handler thread
         let app_clone = app.clone();
+        let refresh_root = root_path.to_string();
+        let pending_edits = self.pending_edits.clone();
+        let next_edit_id = self.next_edit_id.clone();
         thread::spawn(move || {
             for notif in notif_receiver {
                 match notif {
@@ -98,6 +195,31 @@ impl LspManager {
                         // Emit to frontend
                         let _ = app_clone.emit("lsp:diagnostics", &params);
                     }
+                    ServerNotification::InlayHintRefresh => {
+                        let _ = app_clone.emit("lsp:inlay-hints-refresh", &refresh_root);
+                    }
+                    ServerNotification::SemanticTokensRefresh => {
+                        let _ = app_clone.emit("lsp:semantic-tokens-refresh", &refresh_root);
+                    }
+                    ServerNotification::ApplyEdit { params, reply } => {
+                        // Answered with apply_edit_result, or expired if the
+                        // editor takes too long
+                        let id = next_edit_id.fetch_add(1, Ordering::SeqCst);
+                        pending_edits.lock().insert(id, reply);
+                        let expiring = pending_edits.clone();
+                        thread::spawn(move || {
+                            thread::sleep(APPLY_EDIT_TIMEOUT);
+                            expire_edit(&expiring, id);
+                        });
+                        let request = SerializedApplyEdit {
+                            id,
+                            label: params.label,
+                            edit: params.edit.into(),
+                        };
+                        if app_clone.emit("lsp:apply-edit", &request).is_err() {
+                            pending_edits.lock().remove(&id);
+                        }
+                    }
                 }
             }
         });
@@ -105,20 +227,25 @@ impl LspManager {
         // Store server
         {
             let mut servers = self.servers.lock();
-            servers.insert(root_path.to_string(), project);
+            servers.insert(key, project);
         }
 
         Ok(())
     }
 
-    /// Stop a language server for a project.
+    /// Stop the language servers for a project.
     pub fn stop(&self, root_path: &str) -> Result<(), String> {
-        let project = {
+        let projects: Vec<Arc<ProjectLsp>> = {
             let mut servers = self.servers.lock();
-            servers.remove(root_path)
+            let keys: Vec<(String, String)> = servers
+                .keys()
+                .filter(|(root, _)| root == root_path)
+                .cloned()
+                .collect();
+            keys.iter().filter_map(|key| servers.remove(key)).collect()
         };
 
-        if let Some(project) = project {
+        for project in projects {
             // Send shutdown request
             let _: () = project.server.request("shutdown", None).unwrap_or_default();
 
@@ -129,10 +256,48 @@ impl LspManager {
         Ok(())
     }
 
-    /// Get the server for a project if running.
-    fn get_project(&self, root_path: &str) -> Option<Arc<ProjectLsp>> {
+    /// Running servers for a project that handle a file's language.
+    fn projects_for(&self, root_path: &str, file_path: &str) -> Vec<Arc<ProjectLsp>> {
+        let language_id = detect_language(file_path);
         let servers = self.servers.lock();
-        servers.get(root_path).cloned()
+        let mut projects: Vec<Arc<ProjectLsp>> = servers
+            .iter()
+            .filter(|((root, _), project)| {
+                root == root_path && project.config.handles(&language_id)
+            })
+            .map(|(_, project)| project.clone())
+            .collect();
+        // Ties broken by name, so changes lock servers' documents in one order
+        projects.sort_by(|a, b| (a.priority, &a.config.name).cmp(&(b.priority, &b.config.name)));
+        projects
+    }
+
+    /// The server answering requests about a file.
+    fn get_project(&self, root_path: &str, file_path: &str) -> Option<Arc<ProjectLsp>> {
+        self.projects_for(root_path, file_path).into_iter().next()
+    }
+
+    /// The first server for a file with a capability. Errors if no server is
+    /// running, or none of them has it.
+    fn project_supporting(
+        &self,
+        root_path: &str,
+        file_path: &str,
+        feature: &str,
+        supports: impl Fn(&ServerCapabilities) -> bool,
+    ) -> Result<Arc<ProjectLsp>, String> {
+        let projects = self.projects_for(root_path, file_path);
+        if projects.is_empty() {
+            return Err("Server not running".to_string());
+        }
+        projects
+            .into_iter()
+            .find(|p| {
+                p.server
+                    .document_capabilities(file_path)
+                    .is_some_and(|c| supports(&c))
+            })
+            .ok_or_else(|| format!("No language server supports {}", feature))
     }
 
     /// Open a document.
@@ -142,26 +307,40 @@ impl LspManager {
         file_path: &str,
         content: &str,
     ) -> Result<(), String> {
-        let project = self
-            .get_project(root_path)
-            .ok_or_else(|| "Server not running".to_string())?;
-
-        let uri = format!("file://{}", file_path);
-        let language_id = self.detect_language(file_path);
+        let projects = self.projects_for(root_path, file_path);
+        if projects.is_empty() {
+            return Err("Server not running".to_string());
+        }
+        for project in projects {
+            Self::open_in(&project, file_path, content)?;
+        }
+        Ok(())
+    }
 
-        // Check if already open
-        {
-            let docs = project.documents.lock();
-            if docs.contains_key(&uri) {
-                return Ok(());
-            }
+    fn open_in(project: &ProjectLsp, file_path: &str, content: &str) -> Result<(), String> {
+        let uri = path_to_uri(Path::new(&file_path));
+        let mut docs = project.documents.lock();
+        if docs.contains_key(&uri) {
+            return Ok(());
         }
+        Self::open_locked(project, &mut docs, file_path, content, 1)
+    }
 
+    /// Open a document with its server and track it, with the server's
+    /// documents already locked.
+    fn open_locked(
+        project: &ProjectLsp,
+        docs: &mut HashMap<String, OpenDocument>,
+        file_path: &str,
+        content: &str,
+        version: i32,
+    ) -> Result<(), String> {
+        let uri = path_to_uri(Path::new(&file_path));
         let params = DidOpenTextDocumentParams {
             text_document: TextDocumentItem {
                 uri: uri.clone(),
-                language_id: language_id.clone(),
-                version: 1,
+                language_id: detect_language(file_path),
+                version,
                 text: content.to_string(),
             },
         };
@@ -171,51 +350,155 @@ impl LspManager {
             Some(serde_json::to_value(&params).unwrap()),
         )?;
 
-        // Track document
-        {
-            let mut docs = project.documents.lock();
-            docs.insert(uri, OpenDocument { version: 1 });
-        }
-
+        docs.insert(
+            uri,
+            OpenDocument {
+                version,
+                text: content.to_string(),
+                semantic_tokens: None,
+            },
+        );
         Ok(())
     }
 
-    /// Update a document's content.
+    /// Apply changes to a document, in order, and send them to its servers.
+    /// If `version` is given it must be the version after the changes, so
+    /// changes made to a stale copy of the document are rejected. Returns
+    /// the new version.
     pub fn change_document(
         &self,
         root_path: &str,
         file_path: &str,
-        content: &str,
-    ) -> Result<(), String> {
-        let project = self
-            .get_project(root_path)
-            .ok_or_else(|| "Server not running".to_string())?;
+        version: Option<i32>,
+        changes: &[TextDocumentContentChangeEvent],
+    ) -> Result<i32, String> {
+        let projects = self.projects_for(root_path, file_path);
+        if projects.is_empty() {
+            return Err("Server not running".to_string());
+        }
+        let uri = path_to_uri(Path::new(&file_path));
+
+        // Every server's copy is locked, which also keeps concurrent changes
+        // in version order, and checked before any is changed. So a bad change
+        // leaves them all as they were, at the same version.
+        let mut locked: Vec<_> = projects.iter().map(|p| p.documents.lock()).collect();
+        let mut texts = Vec::with_capacity(projects.len());
+        for docs in &locked {
+            texts.push(Self::changed_text(
+                docs.get(&uri),
+                file_path,
+                version,
+                changes,
+            )?);
+        }
+        // Resending the whole document brings any server that's out of step
+        // back to the same version as the others
+        let new_version = version.unwrap_or_else(|| {
+            locked
+                .iter()
+                .filter_map(|docs| docs.get(&uri))
+                .map(|doc| doc.version + 1)
+                .max()
+                .unwrap_or(1)
+        });
 
-        let uri = format!("file://{}", file_path);
+        // Every server is sent the change, reporting the first that fails
+        let mut result = Ok(new_version);
+        for ((project, docs), text) in projects.iter().zip(&mut locked).zip(texts) {
+            let sent = Self::change_in(project, docs, file_path, new_version, changes, text);
+            result = result.and_then(|version| sent.map(|()| version));
+        }
+        result
+    }
 
-        // Get and increment version
-        let version = {
-            let mut docs = project.documents.lock();
-            if let Some(doc) = docs.get_mut(&uri) {
-                doc.version += 1;
-                doc.version
-            } else {
-                // Not open, open it first
-                drop(docs);
-                return self.open_document(root_path, file_path, content);
+    /// The text of a document after `changes`, checking they apply to the
+    /// server's copy of it.
+    fn changed_text(
+        doc: Option<&OpenDocument>,
+        file_path: &str,
+        version: Option<i32>,
+        changes: &[TextDocumentContentChangeEvent],
+    ) -> Result<String, String> {
+        let Some(doc) = doc else {
+            // Without a copy of the document, only replacing all of it works
+            return match changes {
+                [TextDocumentContentChangeEvent { range: None, text }] => Ok(text.clone()),
+                _ => Err(format!("Document not open: {}", file_path)),
+            };
+        };
+
+        if let Some(version) = version {
+            if version != doc.version + 1 {
+                return Err(format!(
+                    "Document version mismatch for {}: expected {}, got {}",
+                    file_path,
+                    doc.version + 1,
+                    version
+                ));
             }
+        }
+
+        let mut text = doc.text.clone();
+        for change in changes {
+            text = match change.range {
+                Some(range) => {
+                    if !position_in_bounds(&text, range.start)
+                        || !position_in_bounds(&text, range.end)
+                    {
+                        return Err(format!("Change outside document: {}", file_path));
+                    }
+                    let edit = TextEdit {
+                        range,
+                        new_text: change.text.clone(),
+                    };
+                    apply_text_edits(&text, &[edit])?
+                }
+                None => change.text.clone(),
+            };
+        }
+        Ok(text)
+    }
+
+    /// Send checked changes to a server and update its copy of the document,
+    /// opening it if the server doesn't have it yet.
+    fn change_in(
+        project: &ProjectLsp,
+        docs: &mut HashMap<String, OpenDocument>,
+        file_path: &str,
+        version: i32,
+        changes: &[TextDocumentContentChangeEvent],
+        text: String,
+    ) -> Result<(), String> {
+        let uri = path_to_uri(Path::new(&file_path));
+        let Some(doc) = docs.get_mut(&uri) else {
+            return Self::open_locked(project, docs, file_path, &text, version);
         };
 
-        let params = DidChangeTextDocumentParams {
-            text_document: VersionedTextDocumentIdentifier {
-                uri: uri.clone(),
-                version,
-            },
-            content_changes: vec![TextDocumentContentChangeEvent {
-                text: content.to_string(),
+        let change_kind = project
+            .server
+            .document_capabilities(file_path)
+            .and_then(|c| c.text_document_sync)
+            .map_or(TextDocumentSyncKind::NONE, |sync| sync.change_kind());
+        let content_changes = match change_kind {
+            TextDocumentSyncKind::INCREMENTAL => changes.to_vec(),
+            TextDocumentSyncKind::FULL => vec![TextDocumentContentChangeEvent {
+                range: None,
+                text: text.clone(),
             }],
+            // The server doesn't want changes, but the copy is kept for requests
+            _ => Vec::new(),
         };
+        // The copy is updated even if sending fails, as the server is gone then
+        doc.version = version;
+        doc.text = text;
 
+        if content_changes.is_empty() {
+            return Ok(());
+        }
+        let params = DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier { uri, version },
+            content_changes,
+        };
         project.server.notify(
             "textDocument/didChange",
             Some(serde_json::to_value(&params).unwrap()),
@@ -224,11 +507,18 @@ impl LspManager {
 
     /// Close a document.
     pub fn close_document(&self, root_path: &str, file_path: &str) -> Result<(), String> {
-        let project = self
-            .get_project(root_path)
-            .ok_or_else(|| "Server not running".to_string())?;
+        let projects = self.projects_for(root_path, file_path);
+        if projects.is_empty() {
+            return Err("Server not running".to_string());
+        }
+        for project in projects {
+            Self::close_in(&project, file_path)?;
+        }
+        Ok(())
+    }
 
-        let uri = format!("file://{}", file_path);
+    fn close_in(project: &ProjectLsp, file_path: &str) -> Result<(), String> {
+        let uri = path_to_uri(Path::new(&file_path));
 
         // Remove from tracking
         {
@@ -246,6 +536,219 @@ impl LspManager {
         )
     }
 
+    /// Tell the servers with the document open that want textDocument/willSave
+    /// that it's about to be saved, then get the edits to make to it first:
+    /// formatting from the first server configured to format on save, or
+    /// otherwise the edits the first server taking
+    /// textDocument/willSaveWaitUntil asks for. Servers that are slow to
+    /// answer don't hold up the save.
+    pub fn will_save(
+        &self,
+        root_path: &str,
+        file_path: &str,
+        reason: TextDocumentSaveReason,
+        options: FormattingOptions,
+    ) -> Result<Vec<TextEdit>, String> {
+        let projects = self.projects_for(root_path, file_path);
+        let uri = path_to_uri(Path::new(&file_path));
+        let params = WillSaveTextDocumentParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            reason,
+        };
+        for project in &projects {
+            let wants_will_save = project
+                .server
+                .document_capabilities(file_path)
+                .and_then(|c| c.text_document_sync)
+                .is_some_and(|sync| sync.will_save());
+            if wants_will_save && project.documents.lock().contains_key(&uri) {
+                let _ = project.server.notify(
+                    "textDocument/willSave",
+                    Some(serde_json::to_value(&params).unwrap()),
+                );
+            }
+        }
+
+        let formatter = projects.iter().find(|p| {
+            p.config.format_on_save
+                && p.server.document_capabilities(file_path).is_some_and(|c| {
+                    c.document_formatting_provider
+                        .is_some_and(|f| f.is_supported())
+                })
+        });
+        if let Some(project) = formatter {
+            let params = DocumentFormattingParams {
+                text_document: TextDocumentIdentifier { uri },
+                options,
+            };
+            let edits: Option<Vec<TextEdit>> = project.server.request_with_timeout(
+                "textDocument/formatting",
+                Some(serde_json::to_value(&params).unwrap()),
+                WILL_SAVE_TIMEOUT,
+            )?;
+            return Ok(edits.unwrap_or_default());
+        }
+
+        let Some(project) = projects.iter().find(|p| {
+            p.server
+                .document_capabilities(file_path)
+                .and_then(|c| c.text_document_sync)
+                .is_some_and(|sync| sync.will_save_wait_until())
+        }) else {
+            return Ok(Vec::new());
+        };
+        let edits: Option<Vec<TextEdit>> = project.server.request_with_timeout(
+            "textDocument/willSaveWaitUntil",
+            Some(serde_json::to_value(&params).unwrap()),
+            WILL_SAVE_TIMEOUT,
+        )?;
+        Ok(edits.unwrap_or_default())
+    }
+
+    /// Tell the servers with a document open that it was saved.
+    pub fn did_save(&self, file_path: &str, text: &str) {
+        let uri = path_to_uri(Path::new(&file_path));
+        for project in self.projects_containing(Path::new(file_path)) {
+            if !project.documents.lock().contains_key(&uri) {
+                continue;
+            }
+            let Some(include_text) = project
+                .server
+                .document_capabilities(file_path)
+                .and_then(|c| c.text_document_sync)
+                .and_then(|sync| sync.save())
+            else {
+                continue;
+            };
+            let params = DidSaveTextDocumentParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                text: include_text.then(|| text.to_string()),
+            };
+            let _ = project.server.notify(
+                "textDocument/didSave",
+                Some(serde_json::to_value(&params).unwrap()),
+            );
+        }
+    }
+
+    /// Tell each running server about files changed on disk under its root
+    /// that it registered watchers for.
+    pub fn did_change_watched_files(
+        &self,
+        created: &[PathBuf],
+        changed: &[PathBuf],
+        deleted: &[PathBuf],
+    ) {
+        let projects: Vec<(String, Arc<ProjectLsp>)> = self
+            .servers
+            .lock()
+            .iter()
+            .map(|((root, _), project)| (root.clone(), project.clone()))
+            .collect();
+
+        for (root, project) in projects {
+            let watchers = project.server.file_watchers();
+            if watchers.is_empty() {
+                continue;
+            }
+            let (root, watchers) = (&root, &watchers);
+            let changes: Vec<FileEvent> = [
+                (created, FileChangeType::CREATED),
+                (changed, FileChangeType::CHANGED),
+                (deleted, FileChangeType::DELETED),
+            ]
+            .into_iter()
+            .flat_map(|(paths, change_type)| {
+                paths
+                    .iter()
+                    .filter(move |p| p.starts_with(root) && watchers.matches(p, change_type))
+                    .map(move |p| FileEvent {
+                        uri: path_to_uri(p),
+                        change_type,
+                    })
+            })
+            .collect();
+
+            if changes.is_empty() {
+                continue;
+            }
+
+            let params = DidChangeWatchedFilesParams { changes };
+            let _ = project.server.notify(
+                "workspace/didChangeWatchedFiles",
+                Some(serde_json::to_value(&params).unwrap()),
+            );
+        }
+    }
+
+    /// Running servers whose root contains `path`.
+    fn projects_containing(&self, path: &Path) -> Vec<Arc<ProjectLsp>> {
+        self.servers
+            .lock()
+            .iter()
+            .filter(|((root, _), _)| path.starts_with(root))
+            .map(|(_, project)| project.clone())
+            .collect()
+    }
+
+    /// Ask servers interested in a rename for the edits to make before it
+    /// happens (e.g. updating imports and module declarations). A server that
+    /// fails to answer doesn't block the rename.
+    pub fn will_rename_files(&self, from: &Path, to: &Path, is_dir: bool) -> Vec<WorkspaceEdit> {
+        let params = RenameFilesParams {
+            files: vec![FileRename {
+                old_uri: path_to_uri(from),
+                new_uri: path_to_uri(to),
+            }],
+        };
+
+        self.projects_containing(from)
+            .into_iter()
+            .filter(|project| {
+                project
+                    .server
+                    .capabilities()
+                    .and_then(|c| c.workspace?.file_operations?.will_rename)
+                    .is_some_and(|options| options.matches(from, is_dir))
+            })
+            .filter_map(|project| {
+                project
+                    .server
+                    .request_with_timeout::<Option<WorkspaceEdit>>(
+                        "workspace/willRenameFiles",
+                        Some(serde_json::to_value(&params).unwrap()),
+                        WILL_RENAME_TIMEOUT,
+                    )
+                    .ok()
+                    .flatten()
+            })
+            .collect()
+    }
+
+    /// Tell interested servers that a rename has happened.
+    pub fn did_rename_files(&self, from: &Path, to: &Path, is_dir: bool) {
+        let params = RenameFilesParams {
+            files: vec![FileRename {
+                old_uri: path_to_uri(from),
+                new_uri: path_to_uri(to),
+            }],
+        };
+
+        for project in self.projects_containing(from) {
+            let interested = project
+                .server
+                .capabilities()
+                .and_then(|c| c.workspace?.file_operations?.did_rename)
+                .is_some_and(|options| options.matches(to, is_dir));
+            if interested {
+                let _ = project.server.notify(
+                    "workspace/didRenameFiles",
+                    Some(serde_json::to_value(&params).unwrap()),
+                );
+            }
+        }
+    }
+
     /// Go to definition.
     pub fn goto_definition(
         &self,
@@ -255,12 +758,12 @@ impl LspManager {
         character: u32,
     ) -> Result<Vec<Location>, String> {
         let project = self
-            .get_project(root_path)
+            .get_project(root_path, file_path)
             .ok_or_else(|| "Server not running".to_string())?;
 
         let params = TextDocumentPositionParams {
             text_document: TextDocumentIdentifier {
-                uri: format!("file://{}", file_path),
+                uri: path_to_uri(Path::new(&file_path)),
             },
             position: Position { line, character },
         };
@@ -282,12 +785,12 @@ impl LspManager {
         character: u32,
     ) -> Result<Option<Hover>, String> {
         let project = self
-            .get_project(root_path)
+            .get_project(root_path, file_path)
             .ok_or_else(|| "Server not running".to_string())?;
 
         let params = TextDocumentPositionParams {
             text_document: TextDocumentIdentifier {
-                uri: format!("file://{}", file_path),
+                uri: path_to_uri(Path::new(&file_path)),
             },
             position: Position { line, character },
         };
@@ -307,12 +810,12 @@ impl LspManager {
         character: u32,
     ) -> Result<Vec<CompletionItem>, String> {
         let project = self
-            .get_project(root_path)
+            .get_project(root_path, file_path)
             .ok_or_else(|| "Server not running".to_string())?;
 
         let params = TextDocumentPositionParams {
             text_document: TextDocumentIdentifier {
-                uri: format!("file://{}", file_path),
+                uri: path_to_uri(Path::new(&file_path)),
             },
             position: Position { line, character },
         };
@@ -325,6 +828,89 @@ impl LspManager {
         Ok(response.map(|r| r.into_items()).unwrap_or_default())
     }
 
+    /// Get the signatures of the call at a position. `context` says what
+    /// triggered the request; a typed character the server doesn't trigger
+    /// on returns None, or updates signature help that's already showing.
+    pub fn signature_help(
+        &self,
+        root_path: &str,
+        file_path: &str,
+        line: u32,
+        character: u32,
+        context: Option<SerializedSignatureHelpContext>,
+    ) -> Result<Option<SignatureHelp>, String> {
+        let project = self.project_supporting(root_path, file_path, "signature help", |c| {
+            c.signature_help_provider.is_some()
+        })?;
+        let options = project
+            .server
+            .document_capabilities(file_path)
+            .and_then(|c| c.signature_help_provider)
+            .unwrap_or_default();
+        let uri = path_to_uri(Path::new(&file_path));
+
+        let context = match context {
+            Some(context) => {
+                let mut trigger_kind = SignatureHelpTriggerKind(context.trigger_kind);
+                let mut trigger_character = context.trigger_character;
+                if trigger_kind == SignatureHelpTriggerKind::TRIGGER_CHARACTER {
+                    let triggers = options.trigger_characters.iter().flatten();
+                    let retriggers = options
+                        .retrigger_characters
+                        .iter()
+                        .flatten()
+                        .filter(|_| context.is_retrigger);
+                    let triggered = trigger_character
+                        .as_ref()
+                        .is_some_and(|c| triggers.chain(retriggers).any(|t| t == c));
+                    if !triggered {
+                        if !context.is_retrigger {
+                            return Ok(None);
+                        }
+                        trigger_kind = SignatureHelpTriggerKind::CONTENT_CHANGE;
+                        trigger_character = None;
+                    }
+                }
+
+                let active_signature_help = if context.is_retrigger {
+                    project
+                        .signature_help
+                        .lock()
+                        .as_ref()
+                        .filter(|(shown, _)| *shown == uri)
+                        .map(|(_, help)| SignatureHelp {
+                            active_signature: context.active_signature.or(help.active_signature),
+                            ..help.clone()
+                        })
+                } else {
+                    None
+                };
+
+                Some(SignatureHelpContext {
+                    trigger_kind,
+                    trigger_character,
+                    is_retrigger: context.is_retrigger,
+                    active_signature_help,
+                })
+            }
+            None => None,
+        };
+
+        let params = SignatureHelpParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            position: Position { line, character },
+            context,
+        };
+
+        let help: Option<SignatureHelp> = project.server.request(
+            "textDocument/signatureHelp",
+            Some(serde_json::to_value(&params).unwrap()),
+        )?;
+        let help = help.filter(|h| !h.signatures.is_empty());
+        *project.signature_help.lock() = help.clone().map(|h| (uri, h));
+        Ok(help)
+    }
+
     /// Find references.
     pub fn references(
         &self,
@@ -335,12 +921,12 @@ impl LspManager {
         include_declaration: bool,
     ) -> Result<Vec<Location>, String> {
         let project = self
-            .get_project(root_path)
+            .get_project(root_path, file_path)
             .ok_or_else(|| "Server not running".to_string())?;
 
         let params = ReferenceParams {
             text_document: TextDocumentIdentifier {
-                uri: format!("file://{}", file_path),
+                uri: path_to_uri(Path::new(&file_path)),
             },
             position: Position { line, character },
             context: ReferenceContext {
@@ -356,6 +942,175 @@ impl LspManager {
         Ok(response.unwrap_or_default())
     }
 
+    /// Get the call hierarchy items at a position, to expand with
+    /// `incoming_calls` and `outgoing_calls`.
+    pub fn prepare_call_hierarchy(
+        &self,
+        root_path: &str,
+        file_path: &str,
+        line: u32,
+        character: u32,
+    ) -> Result<Vec<HierarchyItem>, String> {
+        self.prepare_hierarchy(
+            root_path,
+            file_path,
+            Position { line, character },
+            "call hierarchy",
+            "textDocument/prepareCallHierarchy",
+            |c| {
+                c.call_hierarchy_provider
+                    .as_ref()
+                    .is_some_and(|p| p.is_supported())
+            },
+        )
+    }
+
+    /// Find the callers of a call hierarchy item.
+    pub fn incoming_calls(
+        &self,
+        root_path: &str,
+        item: HierarchyItem,
+    ) -> Result<Vec<CallHierarchyIncomingCall>, String> {
+        self.expand_hierarchy(
+            root_path,
+            item,
+            "call hierarchy",
+            "callHierarchy/incomingCalls",
+            |c| {
+                c.call_hierarchy_provider
+                    .as_ref()
+                    .is_some_and(|p| p.is_supported())
+            },
+        )
+    }
+
+    /// Find what a call hierarchy item calls.
+    pub fn outgoing_calls(
+        &self,
+        root_path: &str,
+        item: HierarchyItem,
+    ) -> Result<Vec<CallHierarchyOutgoingCall>, String> {
+        self.expand_hierarchy(
+            root_path,
+            item,
+            "call hierarchy",
+            "callHierarchy/outgoingCalls",
+            |c| {
+                c.call_hierarchy_provider
+                    .as_ref()
+                    .is_some_and(|p| p.is_supported())
+            },
+        )
+    }
+
+    /// Get the type hierarchy items at a position, to expand with
+    /// `supertypes` and `subtypes`.
+    pub fn prepare_type_hierarchy(
+        &self,
+        root_path: &str,
+        file_path: &str,
+        line: u32,
+        character: u32,
+    ) -> Result<Vec<HierarchyItem>, String> {
+        self.prepare_hierarchy(
+            root_path,
+            file_path,
+            Position { line, character },
+            "type hierarchy",
+            "textDocument/prepareTypeHierarchy",
+            |c| {
+                c.type_hierarchy_provider
+                    .as_ref()
+                    .is_some_and(|p| p.is_supported())
+            },
+        )
+    }
+
+    /// Find the supertypes of a type hierarchy item, e.g. a type's traits.
+    pub fn supertypes(
+        &self,
+        root_path: &str,
+        item: HierarchyItem,
+    ) -> Result<Vec<HierarchyItem>, String> {
+        self.expand_hierarchy(
+            root_path,
+            item,
+            "type hierarchy",
+            "typeHierarchy/supertypes",
+            |c| {
+                c.type_hierarchy_provider
+                    .as_ref()
+                    .is_some_and(|p| p.is_supported())
+            },
+        )
+    }
+
+    /// Find the subtypes of a type hierarchy item, e.g. a trait's
+    /// implementors.
+    pub fn subtypes(
+        &self,
+        root_path: &str,
+        item: HierarchyItem,
+    ) -> Result<Vec<HierarchyItem>, String> {
+        self.expand_hierarchy(
+            root_path,
+            item,
+            "type hierarchy",
+            "typeHierarchy/subtypes",
+            |c| {
+                c.type_hierarchy_provider
+                    .as_ref()
+                    .is_some_and(|p| p.is_supported())
+            },
+        )
+    }
+
+    fn prepare_hierarchy(
+        &self,
+        root_path: &str,
+        file_path: &str,
+        position: Position,
+        feature: &str,
+        method: &str,
+        supports: impl Fn(&ServerCapabilities) -> bool,
+    ) -> Result<Vec<HierarchyItem>, String> {
+        let project = self.project_supporting(root_path, file_path, feature, supports)?;
+
+        let params = TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier {
+                uri: path_to_uri(Path::new(&file_path)),
+            },
+            position,
+        };
+
+        let items: Option<Vec<HierarchyItem>> = project
+            .server
+            .request(method, Some(serde_json::to_value(&params).unwrap()))?;
+        Ok(items.unwrap_or_default())
+    }
+
+    /// Expand a hierarchy item, asking the server for the language of the
+    /// item's own file.
+    fn expand_hierarchy<T: serde::de::DeserializeOwned>(
+        &self,
+        root_path: &str,
+        item: HierarchyItem,
+        feature: &str,
+        method: &str,
+        supports: impl Fn(&ServerCapabilities) -> bool,
+    ) -> Result<Vec<T>, String> {
+        let file_path =
+            uri_to_path(&item.uri).ok_or_else(|| format!("Not a file URI: {}", item.uri))?;
+        let project =
+            self.project_supporting(root_path, &file_path.to_string_lossy(), feature, supports)?;
+
+        let params = HierarchyItemParams { item };
+        let result: Option<Vec<T>> = project
+            .server
+            .request(method, Some(serde_json::to_value(&params).unwrap()))?;
+        Ok(result.unwrap_or_default())
+    }
+
     /// Get code actions for a range.
     pub fn code_actions(
         &self,
@@ -368,12 +1123,12 @@ impl LspManager {
         diagnostics: Vec<Diagnostic>,
     ) -> Result<Vec<CodeAction>, String> {
         let project = self
-            .get_project(root_path)
+            .get_project(root_path, file_path)
             .ok_or_else(|| "Server not running".to_string())?;
 
         let params = CodeActionParams {
             text_document: TextDocumentIdentifier {
-                uri: format!("file://{}", file_path),
+                uri: path_to_uri(Path::new(&file_path)),
             },
             range: Range {
                 start: Position {
@@ -404,10 +1159,11 @@ impl LspManager {
     pub fn resolve_code_action(
         &self,
         root_path: &str,
+        file_path: &str,
         code_action: CodeAction,
     ) -> Result<CodeAction, String> {
         let project = self
-            .get_project(root_path)
+            .get_project(root_path, file_path)
             .ok_or_else(|| "Server not running".to_string())?;
 
         let resolved: CodeAction = project.server.request(
@@ -418,43 +1174,390 @@ impl LspManager {
         Ok(resolved)
     }
 
-    /// Detect language ID from file extension.
-    fn detect_language(&self, file_path: &str) -> String {
-        let path = Path::new(file_path);
-        match path.extension().and_then(|e| e.to_str()) {
-            Some("rs") => "rust".to_string(),
-            Some("ts") => "typescript".to_string(),
-            Some("tsx") => "typescriptreact".to_string(),
-            Some("js") => "javascript".to_string(),
-            Some("jsx") => "javascriptreact".to_string(),
-            Some("json") => "json".to_string(),
-            Some("md") => "markdown".to_string(),
-            Some("toml") => "toml".to_string(),
-            Some("yaml") | Some("yml") => "yaml".to_string(),
-            _ => "plaintext".to_string(),
+    /// Check a symbol can be renamed. Returns the range to rename and
+    /// optionally placeholder text, `DefaultBehavior` if the editor should
+    /// pick the word at the position, or None if there's nothing to rename.
+    pub fn prepare_rename(
+        &self,
+        root_path: &str,
+        file_path: &str,
+        line: u32,
+        character: u32,
+    ) -> Result<Option<PrepareRenameResponse>, String> {
+        let project = self.project_supporting(root_path, file_path, "rename", |c| {
+            c.rename_provider.as_ref().is_some_and(|r| r.is_supported())
+        })?;
+
+        let prepare_supported = project
+            .server
+            .document_capabilities(file_path)
+            .and_then(|c| c.rename_provider)
+            .is_some_and(|r| r.prepare_supported());
+        if !prepare_supported {
+            return Ok(Some(PrepareRenameResponse::DefaultBehavior {
+                default_behavior: true,
+            }));
         }
-    }
-}
 
-// Tauri command wrappers
+        let params = TextDocumentPositionParams {
+            text_document: TextDocumentIdentifier {
+                uri: path_to_uri(Path::new(&file_path)),
+            },
+            position: Position { line, character },
+        };
 
-/// Serialized location for frontend.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SerializedLocation {
-    pub uri: String,
-    pub range: SerializedRange,
-}
+        project.server.request(
+            "textDocument/prepareRename",
+            Some(serde_json::to_value(&params).unwrap()),
+        )
+    }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SerializedRange {
-    pub start: SerializedPosition,
-    pub end: SerializedPosition,
-}
+    /// Rename a symbol. Returns the edit to make, or None if the server has
+    /// nothing to change.
+    pub fn rename(
+        &self,
+        root_path: &str,
+        file_path: &str,
+        line: u32,
+        character: u32,
+        new_name: &str,
+    ) -> Result<Option<WorkspaceEdit>, String> {
+        let project = self.project_supporting(root_path, file_path, "rename", |c| {
+            c.rename_provider.as_ref().is_some_and(|r| r.is_supported())
+        })?;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SerializedPosition {
-    pub line: u32,
-    pub character: u32,
+        let params = RenameParams {
+            text_document: TextDocumentIdentifier {
+                uri: path_to_uri(Path::new(&file_path)),
+            },
+            position: Position { line, character },
+            new_name: new_name.to_string(),
+        };
+
+        project.server.request(
+            "textDocument/rename",
+            Some(serde_json::to_value(&params).unwrap()),
+        )
+    }
+
+    /// Format a document.
+    pub fn format_document(
+        &self,
+        root_path: &str,
+        file_path: &str,
+        options: FormattingOptions,
+    ) -> Result<Vec<TextEdit>, String> {
+        let project = self.project_supporting(root_path, file_path, "formatting", |c| {
+            c.document_formatting_provider
+                .as_ref()
+                .is_some_and(|p| p.is_supported())
+        })?;
+
+        let params = DocumentFormattingParams {
+            text_document: TextDocumentIdentifier {
+                uri: path_to_uri(Path::new(&file_path)),
+            },
+            options,
+        };
+
+        let edits: Option<Vec<TextEdit>> = project.server.request(
+            "textDocument/formatting",
+            Some(serde_json::to_value(&params).unwrap()),
+        )?;
+        Ok(edits.unwrap_or_default())
+    }
+
+    /// Format part of a document.
+    pub fn format_range(
+        &self,
+        root_path: &str,
+        file_path: &str,
+        range: Range,
+        options: FormattingOptions,
+    ) -> Result<Vec<TextEdit>, String> {
+        let project = self.project_supporting(root_path, file_path, "range formatting", |c| {
+            c.document_range_formatting_provider
+                .as_ref()
+                .is_some_and(|p| p.is_supported())
+        })?;
+
+        let params = DocumentRangeFormattingParams {
+            text_document: TextDocumentIdentifier {
+                uri: path_to_uri(Path::new(&file_path)),
+            },
+            range,
+            options,
+        };
+
+        let edits: Option<Vec<TextEdit>> = project.server.request(
+            "textDocument/rangeFormatting",
+            Some(serde_json::to_value(&params).unwrap()),
+        )?;
+        Ok(edits.unwrap_or_default())
+    }
+
+    /// Get the symbols in a document, as a tree or a flat list depending on
+    /// the server.
+    pub fn document_symbols(
+        &self,
+        root_path: &str,
+        file_path: &str,
+    ) -> Result<Option<DocumentSymbolResponse>, String> {
+        let project = self.project_supporting(root_path, file_path, "document symbols", |c| {
+            c.document_symbol_provider
+                .as_ref()
+                .is_some_and(|p| p.is_supported())
+        })?;
+
+        let params = DocumentSymbolParams {
+            text_document: TextDocumentIdentifier {
+                uri: path_to_uri(Path::new(&file_path)),
+            },
+        };
+
+        project.server.request(
+            "textDocument/documentSymbol",
+            Some(serde_json::to_value(&params).unwrap()),
+        )
+    }
+
+    /// Get the semantic tokens for a whole document. Asks for a delta from the
+    /// last tokens when the server supports it, and falls back to all tokens
+    /// if the delta doesn't apply.
+    pub fn semantic_tokens(
+        &self,
+        root_path: &str,
+        file_path: &str,
+    ) -> Result<Vec<SerializedSemanticToken>, String> {
+        let project = self.project_supporting(root_path, file_path, "semantic tokens", |c| {
+            c.semantic_tokens_provider
+                .as_ref()
+                .and_then(|p| p.full.as_ref())
+                .is_some_and(|f| f.is_supported())
+        })?;
+        let options = project
+            .server
+            .document_capabilities(file_path)
+            .and_then(|c| c.semantic_tokens_provider)
+            .unwrap_or_default();
+        let uri = path_to_uri(Path::new(&file_path));
+
+        let previous = if options.full.as_ref().is_some_and(|f| f.delta_supported()) {
+            project
+                .documents
+                .lock()
+                .get(&uri)
+                .and_then(|d| d.semantic_tokens.clone())
+        } else {
+            None
+        };
+
+        let from_delta = match previous {
+            Some(SemanticTokens {
+                result_id: Some(previous_result_id),
+                mut data,
+            }) => {
+                let params = SemanticTokensDeltaParams {
+                    text_document: TextDocumentIdentifier { uri: uri.clone() },
+                    previous_result_id,
+                };
+                let response: Result<Option<SemanticTokensDeltaResponse>, String> =
+                    project.server.request(
+                        "textDocument/semanticTokens/full/delta",
+                        Some(serde_json::to_value(&params).unwrap()),
+                    );
+                // Anything but a usable delta falls back to a full request,
+                // e.g. when the server has dropped the previous result
+                match response {
+                    Ok(Some(SemanticTokensDeltaResponse::Tokens(tokens))) => Some(tokens),
+                    Ok(Some(SemanticTokensDeltaResponse::Delta(delta))) => {
+                        apply_delta(&mut data, delta.edits)
+                            .ok()
+                            .map(|()| SemanticTokens {
+                                result_id: delta.result_id,
+                                data,
+                            })
+                    }
+                    Ok(None) | Err(_) => None,
+                }
+            }
+            _ => None,
+        };
+
+        let tokens = match from_delta {
+            Some(tokens) => tokens,
+            None => {
+                let params = SemanticTokensParams {
+                    text_document: TextDocumentIdentifier { uri: uri.clone() },
+                };
+                let tokens: Option<SemanticTokens> = project.server.request(
+                    "textDocument/semanticTokens/full",
+                    Some(serde_json::to_value(&params).unwrap()),
+                )?;
+                tokens.unwrap_or_default()
+            }
+        };
+
+        let decoded = decode(&tokens.data, &options.legend);
+        if let Some(doc) = project.documents.lock().get_mut(&uri) {
+            doc.semantic_tokens = Some(tokens);
+        }
+        Ok(decoded)
+    }
+
+    /// Get the semantic tokens in a range of a document, e.g. the part on
+    /// screen of a large file.
+    pub fn semantic_tokens_range(
+        &self,
+        root_path: &str,
+        file_path: &str,
+        range: Range,
+    ) -> Result<Vec<SerializedSemanticToken>, String> {
+        let project = self.project_supporting(root_path, file_path, "semantic tokens", |c| {
+            c.semantic_tokens_provider
+                .as_ref()
+                .and_then(|p| p.range.as_ref())
+                .is_some_and(|r| r.is_supported())
+        })?;
+        let legend = project
+            .server
+            .document_capabilities(file_path)
+            .and_then(|c| c.semantic_tokens_provider)
+            .unwrap_or_default()
+            .legend;
+
+        let params = SemanticTokensRangeParams {
+            text_document: TextDocumentIdentifier {
+                uri: path_to_uri(Path::new(&file_path)),
+            },
+            range,
+        };
+
+        let tokens: Option<SemanticTokens> = project.server.request(
+            "textDocument/semanticTokens/range",
+            Some(serde_json::to_value(&params).unwrap()),
+        )?;
+        Ok(decode(&tokens.unwrap_or_default().data, &legend))
+    }
+
+    /// Get the inlay hints in a range of a document, e.g. inferred types and
+    /// parameter names.
+    pub fn inlay_hints(
+        &self,
+        root_path: &str,
+        file_path: &str,
+        range: Range,
+    ) -> Result<Vec<InlayHint>, String> {
+        let project = self.project_supporting(root_path, file_path, "inlay hints", |c| {
+            c.inlay_hint_provider
+                .as_ref()
+                .is_some_and(|p| p.is_supported())
+        })?;
+
+        let params = InlayHintParams {
+            text_document: TextDocumentIdentifier {
+                uri: path_to_uri(Path::new(&file_path)),
+            },
+            range,
+        };
+
+        let hints: Option<Vec<InlayHint>> = project.server.request(
+            "textDocument/inlayHint",
+            Some(serde_json::to_value(&params).unwrap()),
+        )?;
+        Ok(hints.unwrap_or_default())
+    }
+
+    /// Fill in an inlay hint's tooltip and edits. Returns it unchanged if the
+    /// server can't resolve hints.
+    pub fn resolve_inlay_hint(
+        &self,
+        root_path: &str,
+        file_path: &str,
+        hint: InlayHint,
+    ) -> Result<InlayHint, String> {
+        let project = self.project_supporting(root_path, file_path, "inlay hints", |c| {
+            c.inlay_hint_provider
+                .as_ref()
+                .is_some_and(|p| p.is_supported())
+        })?;
+
+        let resolve_supported = project
+            .server
+            .document_capabilities(file_path)
+            .and_then(|c| c.inlay_hint_provider)
+            .is_some_and(|p| p.resolve_supported());
+        if !resolve_supported {
+            return Ok(hint);
+        }
+
+        project.server.request(
+            "inlayHint/resolve",
+            Some(serde_json::to_value(&hint).unwrap()),
+        )
+    }
+
+    /// Search symbols across a project, asking every server running for it.
+    /// Fails only if no server could answer.
+    pub fn workspace_symbols(
+        &self,
+        root_path: &str,
+        query: &str,
+    ) -> Result<Vec<WorkspaceSymbol>, String> {
+        let mut projects: Vec<Arc<ProjectLsp>> = self
+            .servers
+            .lock()
+            .iter()
+            .filter(|((root, _), _)| root == root_path)
+            .map(|(_, project)| project.clone())
+            .collect();
+        if projects.is_empty() {
+            return Err("Server not running".to_string());
+        }
+        projects.retain(|p| {
+            p.server
+                .capabilities()
+                .and_then(|c| c.workspace_symbol_provider)
+                .is_some_and(|p| p.is_supported())
+        });
+        projects.sort_by(|a, b| a.config.name.cmp(&b.config.name));
+
+        let params = WorkspaceSymbolParams {
+            query: query.to_string(),
+        };
+        // Results from the servers that answer; an error only if none do
+        let mut symbols = Vec::new();
+        let mut any_ok = false;
+        let mut last_err = "No language server supports workspace symbols".to_string();
+        for project in projects {
+            let response: Result<Option<Vec<WorkspaceSymbol>>, String> = project.server.request(
+                "workspace/symbol",
+                Some(serde_json::to_value(&params).unwrap()),
+            );
+            match response {
+                Ok(found) => {
+                    symbols.extend(found.unwrap_or_default());
+                    any_ok = true;
+                }
+                Err(e) => last_err = e,
+            }
+        }
+
+        if any_ok {
+            Ok(symbols)
+        } else {
+            Err(last_err)
+        }
+    }
+}
+
+// Tauri command wrappers
+
+/// Serialized location for frontend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SerializedLocation {
+    pub uri: String,
+    pub range: SerializedRange,
 }
 
 impl From<Location> for SerializedLocation {
@@ -538,110 +1641,61 @@ impl From<CompletionItem> for SerializedCompletionItem {
     }
 }
 
-/// Serialized text edit for frontend.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A change to an open document from the editor: `text` replaces `range`,
+/// or the whole document if there's no range.
+#[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct SerializedTextEdit {
-    pub range: SerializedRange,
-    pub new_text: String,
+pub struct SerializedContentChange {
+    pub range: Option<SerializedRange>,
+    pub text: String,
 }
 
-impl From<super::protocol::TextEdit> for SerializedTextEdit {
-    fn from(edit: super::protocol::TextEdit) -> Self {
+impl From<SerializedContentChange> for TextDocumentContentChangeEvent {
+    fn from(change: SerializedContentChange) -> Self {
         Self {
-            range: SerializedRange {
-                start: SerializedPosition {
-                    line: edit.range.start.line,
-                    character: edit.range.start.character,
-                },
-                end: SerializedPosition {
-                    line: edit.range.end.line,
-                    character: edit.range.end.character,
-                },
-            },
-            new_text: edit.new_text,
+            range: change.range.map(Range::from),
+            text: change.text,
         }
     }
 }
 
-/// Serialized workspace edit for frontend.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// An edit a server asked the editor to apply, answered with
+/// `lsp_apply_edit_result`.
+#[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
-pub struct SerializedWorkspaceEdit {
-    /// Map of file URI to list of text edits.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub changes: Option<std::collections::HashMap<String, Vec<SerializedTextEdit>>>,
-    /// Document changes (for more complex edits).
+pub struct SerializedApplyEdit {
+    pub id: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub document_changes: Option<Vec<SerializedDocumentChange>>,
+    pub label: Option<String>,
+    pub edit: SerializedWorkspaceEdit,
 }
 
-/// Serialized document change for frontend.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Serialized prepareRename result for frontend. Without a range the editor
+/// should rename the word at the position.
+#[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
-pub struct SerializedDocumentChange {
-    /// The document to edit.
-    pub uri: String,
-    /// The edits to apply.
-    pub edits: Vec<SerializedTextEdit>,
-}
-
-impl From<super::protocol::WorkspaceEdit> for SerializedWorkspaceEdit {
-    fn from(edit: super::protocol::WorkspaceEdit) -> Self {
-        let changes = edit.changes.map(|c| {
-            c.into_iter()
-                .map(|(uri, edits)| {
-                    (
-                        uri,
-                        edits.into_iter().map(SerializedTextEdit::from).collect(),
-                    )
-                })
-                .collect()
-        });
-
-        let document_changes = edit.document_changes.map(|dc| {
-            dc.into_iter()
-                .filter_map(|change| {
-                    match change {
-                        super::protocol::DocumentChange::Edit(edit) => {
-                            Some(SerializedDocumentChange {
-                                uri: edit.text_document.uri,
-                                edits: edit
-                                    .edits
-                                    .into_iter()
-                                    .map(|e| match e {
-                                        super::protocol::TextEditOrAnnotated::TextEdit(te) => {
-                                            SerializedTextEdit::from(te)
-                                        }
-                                        super::protocol::TextEditOrAnnotated::Annotated(ate) => {
-                                            SerializedTextEdit {
-                                                range: SerializedRange {
-                                                    start: SerializedPosition {
-                                                        line: ate.range.start.line,
-                                                        character: ate.range.start.character,
-                                                    },
-                                                    end: SerializedPosition {
-                                                        line: ate.range.end.line,
-                                                        character: ate.range.end.character,
-                                                    },
-                                                },
-                                                new_text: ate.new_text,
-                                            }
-                                        }
-                                    })
-                                    .collect(),
-                            })
-                        }
-                        // For now, skip create/rename/delete operations
-                        _ => None,
-                    }
-                })
-                .collect()
-        });
+pub struct SerializedPrepareRename {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<SerializedRange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+}
 
-        Self {
-            changes,
-            document_changes,
+impl From<PrepareRenameResponse> for SerializedPrepareRename {
+    fn from(response: PrepareRenameResponse) -> Self {
+        match response {
+            PrepareRenameResponse::Range(range) => Self {
+                range: Some(range.into()),
+                placeholder: None,
+            },
+            PrepareRenameResponse::RangeWithPlaceholder { range, placeholder } => Self {
+                range: Some(range.into()),
+                placeholder: Some(placeholder),
+            },
+            PrepareRenameResponse::DefaultBehavior { .. } => Self {
+                range: None,
+                placeholder: None,
+            },
         }
     }
 }
@@ -682,3 +1736,549 @@ impl From<CodeAction> for SerializedCodeAction {
         }
     }
 }
+
+/// Serialized symbol for frontend, from a document outline or a workspace
+/// symbol search.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedSymbol {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub kind: u8,
+    /// Name of the symbol containing this one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
+    pub uri: String,
+    /// The whole symbol, e.g. a function including its body
+    pub range: SerializedRange,
+    /// The part to select when jumping to the symbol, e.g. its name
+    pub selection_range: SerializedRange,
+    /// Symbols nested in this one, in a document outline
+    pub children: Vec<SerializedSymbol>,
+}
+
+impl SerializedSymbol {
+    /// A document's symbols as a tree. Flat lists are nested by range.
+    pub fn outline(uri: &str, response: DocumentSymbolResponse) -> Vec<Self> {
+        match response {
+            DocumentSymbolResponse::Nested(symbols) => symbols
+                .into_iter()
+                .map(|s| Self::from_document_symbol(uri, None, s))
+                .collect(),
+            DocumentSymbolResponse::Flat(symbols) => {
+                nest_by_range(symbols.into_iter().map(Self::from).collect())
+            }
+        }
+    }
+
+    fn from_document_symbol(uri: &str, container: Option<&str>, symbol: DocumentSymbol) -> Self {
+        let children = symbol
+            .children
+            .unwrap_or_default()
+            .into_iter()
+            .map(|child| Self::from_document_symbol(uri, Some(&symbol.name), child))
+            .collect();
+        Self {
+            name: symbol.name,
+            detail: symbol.detail,
+            kind: symbol.kind.0,
+            container: container.map(str::to_string),
+            uri: uri.to_string(),
+            range: symbol.range.into(),
+            selection_range: symbol.selection_range.into(),
+            children,
+        }
+    }
+
+    fn start(&self) -> (u32, u32) {
+        (self.range.start.line, self.range.start.character)
+    }
+
+    fn end(&self) -> (u32, u32) {
+        (self.range.end.line, self.range.end.character)
+    }
+}
+
+/// Nest symbols from a flat list under the symbols whose ranges contain them.
+fn nest_by_range(mut symbols: Vec<SerializedSymbol>) -> Vec<SerializedSymbol> {
+    // Outer symbols before the symbols they contain
+    symbols.sort_by_key(|s| (s.start(), std::cmp::Reverse(s.end())));
+
+    let mut roots = Vec::new();
+    let mut stack: Vec<SerializedSymbol> = Vec::new();
+    for symbol in symbols {
+        while let Some(top) = stack.last() {
+            if top.end() >= symbol.end() {
+                break;
+            }
+            let done = stack.pop().unwrap();
+            attach(&mut stack, &mut roots, done);
+        }
+        stack.push(symbol);
+    }
+    while let Some(done) = stack.pop() {
+        attach(&mut stack, &mut roots, done);
+    }
+    roots
+}
+
+/// Add a symbol to the innermost symbol still open, or to the top level.
+fn attach(
+    stack: &mut [SerializedSymbol],
+    roots: &mut Vec<SerializedSymbol>,
+    symbol: SerializedSymbol,
+) {
+    match stack.last_mut() {
+        Some(parent) => parent.children.push(symbol),
+        None => roots.push(symbol),
+    }
+}
+
+impl From<SymbolInformation> for SerializedSymbol {
+    fn from(symbol: SymbolInformation) -> Self {
+        Self {
+            name: symbol.name,
+            detail: None,
+            kind: symbol.kind.0,
+            container: symbol.container_name,
+            uri: symbol.location.uri,
+            range: symbol.location.range.into(),
+            selection_range: symbol.location.range.into(),
+            children: Vec::new(),
+        }
+    }
+}
+
+impl From<WorkspaceSymbol> for SerializedSymbol {
+    fn from(symbol: WorkspaceSymbol) -> Self {
+        let (uri, range) = match symbol.location {
+            WorkspaceSymbolLocation::Location(location) => (location.uri, location.range),
+            WorkspaceSymbolLocation::Uri { uri } => (uri, Range::default()),
+        };
+        Self {
+            name: symbol.name,
+            detail: None,
+            kind: symbol.kind.0,
+            container: symbol.container_name,
+            uri,
+            range: range.into(),
+            selection_range: range.into(),
+            children: Vec::new(),
+        }
+    }
+}
+
+/// Serialized call or type hierarchy item for frontend. Deserializes back
+/// into a `HierarchyItem` to expand it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedHierarchyItem {
+    pub name: String,
+    pub kind: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<u8>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub uri: String,
+    /// The whole item, e.g. a function including its body
+    pub range: SerializedRange,
+    /// The part to select when jumping to the item, e.g. its name
+    pub selection_range: SerializedRange,
+    /// Data preserved for expanding the item.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+impl From<HierarchyItem> for SerializedHierarchyItem {
+    fn from(item: HierarchyItem) -> Self {
+        Self {
+            name: item.name,
+            kind: item.kind.0,
+            tags: item.tags,
+            detail: item.detail,
+            uri: item.uri,
+            range: item.range.into(),
+            selection_range: item.selection_range.into(),
+            data: item.data,
+        }
+    }
+}
+
+impl From<SerializedHierarchyItem> for HierarchyItem {
+    fn from(item: SerializedHierarchyItem) -> Self {
+        Self {
+            name: item.name,
+            kind: SymbolKind(item.kind),
+            tags: item.tags,
+            detail: item.detail,
+            uri: item.uri,
+            range: item.range.into(),
+            selection_range: item.selection_range.into(),
+            data: item.data,
+        }
+    }
+}
+
+/// A call to or from a hierarchy item, for frontend.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedHierarchyCall {
+    /// The caller for incoming calls, the callee for outgoing ones
+    pub item: SerializedHierarchyItem,
+    /// Where the calls are: in `item`'s document for incoming calls, in the
+    /// expanded item's document for outgoing ones
+    pub ranges: Vec<SerializedRange>,
+}
+
+impl From<CallHierarchyIncomingCall> for SerializedHierarchyCall {
+    fn from(call: CallHierarchyIncomingCall) -> Self {
+        Self {
+            item: call.from.into(),
+            ranges: call
+                .from_ranges
+                .into_iter()
+                .map(SerializedRange::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<CallHierarchyOutgoingCall> for SerializedHierarchyCall {
+    fn from(call: CallHierarchyOutgoingCall) -> Self {
+        Self {
+            item: call.to.into(),
+            ranges: call
+                .from_ranges
+                .into_iter()
+                .map(SerializedRange::from)
+                .collect(),
+        }
+    }
+}
+
+/// Serialized inlay hint for frontend. Deserializes back into an
+/// `InlayHint` for inlayHint/resolve.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedInlayHint {
+    pub position: SerializedPosition,
+    pub label: String,
+    /// An LSP `InlayHintKind`: 1 for types, 2 for parameters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<u8>,
+    /// Markdown
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tooltip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_edits: Option<Vec<SerializedTextEdit>>,
+    pub padding_left: bool,
+    pub padding_right: bool,
+    /// Data preserved for resolve requests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+    /// Whether the tooltip may be filled in by resolving the hint.
+    pub needs_resolve: bool,
+}
+
+impl From<InlayHint> for SerializedInlayHint {
+    fn from(hint: InlayHint) -> Self {
+        let needs_resolve = hint.tooltip.is_none() && hint.data.is_some();
+        Self {
+            position: SerializedPosition {
+                line: hint.position.line,
+                character: hint.position.character,
+            },
+            label: hint.label.text(),
+            kind: hint.kind.map(|k| k.0),
+            tooltip: hint.tooltip.map(documentation_markdown),
+            text_edits: hint
+                .text_edits
+                .map(|edits| edits.into_iter().map(SerializedTextEdit::from).collect()),
+            padding_left: hint.padding_left.unwrap_or(false),
+            padding_right: hint.padding_right.unwrap_or(false),
+            data: hint.data,
+            needs_resolve,
+        }
+    }
+}
+
+/// What triggered a signature help request, from the frontend.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedSignatureHelpContext {
+    /// An LSP `SignatureHelpTriggerKind`
+    pub trigger_kind: u8,
+    pub trigger_character: Option<String>,
+    /// Signature help is already showing
+    #[serde(default)]
+    pub is_retrigger: bool,
+    /// The signature the user picked in the signature help showing
+    pub active_signature: Option<u32>,
+}
+
+/// Serialized signature help for the cursor popup.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedSignatureHelp {
+    pub signatures: Vec<SerializedSignature>,
+    /// Index of the signature to show, always in range
+    pub active_signature: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedSignature {
+    pub label: String,
+    /// Markdown
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<String>,
+    pub parameters: Vec<SerializedParameter>,
+    /// Index of the parameter at the cursor, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_parameter: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedParameter {
+    pub label: String,
+    /// Start and end of the parameter in the signature label, in UTF-16 code
+    /// units, if it could be found there
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label_offsets: Option<[u32; 2]>,
+    /// Markdown
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<String>,
+    /// The cursor is in this parameter
+    pub active: bool,
+}
+
+impl From<SignatureHelp> for SerializedSignatureHelp {
+    fn from(help: SignatureHelp) -> Self {
+        let count = help.signatures.len() as u32;
+        let active_signature = help.active_signature.filter(|&i| i < count).unwrap_or(0);
+        let signatures = help
+            .signatures
+            .into_iter()
+            .map(|signature| SerializedSignature::new(signature, help.active_parameter))
+            .collect();
+        Self {
+            signatures,
+            active_signature,
+        }
+    }
+}
+
+impl SerializedSignature {
+    fn new(signature: SignatureInformation, default_active_parameter: Option<u32>) -> Self {
+        let parameters = signature.parameters.unwrap_or_default();
+        let active_parameter = signature
+            .active_parameter
+            .or(default_active_parameter)
+            .filter(|&i| (i as usize) < parameters.len());
+
+        // String labels are looked for after the previous parameter, so
+        // repeated text maps to the right place
+        let mut search_from = 0;
+        let parameters = parameters
+            .into_iter()
+            .enumerate()
+            .map(|(i, parameter)| {
+                let (label, label_offsets) = match parameter.label {
+                    ParameterLabel::Simple(label) => {
+                        let found = signature.label[search_from..].find(&label);
+                        let offsets = found.map(|start| {
+                            let start = search_from + start;
+                            search_from = start + label.len();
+                            [
+                                utf16_len(&signature.label[..start]),
+                                utf16_len(&signature.label[..search_from]),
+                            ]
+                        });
+                        (label, offsets)
+                    }
+                    ParameterLabel::Offsets([start, end]) => (
+                        utf16_slice(&signature.label, start, end),
+                        Some([start, end]),
+                    ),
+                };
+                SerializedParameter {
+                    label,
+                    label_offsets,
+                    documentation: parameter.documentation.map(documentation_markdown),
+                    active: active_parameter == Some(i as u32),
+                }
+            })
+            .collect();
+
+        Self {
+            label: signature.label,
+            documentation: signature.documentation.map(documentation_markdown),
+            parameters,
+            active_parameter,
+        }
+    }
+}
+
+fn documentation_markdown(documentation: Documentation) -> String {
+    match documentation {
+        Documentation::String(s) => s,
+        Documentation::Markup(m) => m.value,
+    }
+}
+
+fn utf16_len(s: &str) -> u32 {
+    s.chars().map(|c| c.len_utf16() as u32).sum()
+}
+
+/// The part of `s` between two UTF-16 offsets.
+fn utf16_slice(s: &str, start: u32, end: u32) -> String {
+    let mut offset = 0;
+    s.chars()
+        .filter(|c| {
+            let inside = offset >= start && offset < end;
+            offset += c.len_utf16() as u32;
+            inside
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc(version: i32, text: &str) -> OpenDocument {
+        OpenDocument {
+            version,
+            text: text.to_string(),
+            semantic_tokens: None,
+        }
+    }
+
+    fn insert(line: u32, character: u32, text: &str) -> TextDocumentContentChangeEvent {
+        let at = Position { line, character };
+        TextDocumentContentChangeEvent {
+            range: Some(Range { start: at, end: at }),
+            text: text.to_string(),
+        }
+    }
+
+    fn full(text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range: None,
+            text: text.to_string(),
+        }
+    }
+
+    fn symbol(name: &str, start: (u32, u32), end: (u32, u32)) -> SerializedSymbol {
+        let position = |(line, character)| SerializedPosition { line, character };
+        let range = SerializedRange {
+            start: position(start),
+            end: position(end),
+        };
+        SerializedSymbol {
+            name: name.to_string(),
+            detail: None,
+            kind: 12,
+            container: None,
+            uri: "file:///a.rs".to_string(),
+            range: range.clone(),
+            selection_range: range,
+            children: Vec::new(),
+        }
+    }
+
+    /// Symbol names as an indented outline.
+    fn outline(symbols: &[SerializedSymbol], depth: usize, lines: &mut Vec<String>) {
+        for symbol in symbols {
+            lines.push(format!("{}{}", "  ".repeat(depth), symbol.name));
+            outline(&symbol.children, depth + 1, lines);
+        }
+    }
+
+    #[test]
+    fn test_nest_by_range() {
+        // Out of order, as flat lists may be
+        let symbols = vec![
+            symbol("method", (2, 4), (4, 5)),
+            symbol("after", (10, 0), (10, 20)),
+            symbol("Impl", (1, 0), (8, 1)),
+            symbol("field", (3, 8), (3, 12)),
+            symbol("other", (6, 4), (7, 5)),
+            symbol("first", (0, 0), (0, 10)),
+        ];
+        let mut lines = Vec::new();
+        outline(&nest_by_range(symbols), 0, &mut lines);
+        assert_eq!(
+            lines,
+            vec!["first", "Impl", "  method", "    field", "  other", "after"]
+        );
+    }
+
+    #[test]
+    fn test_nest_by_range_same_range() {
+        // A symbol with the same range as an earlier one goes inside it, and one
+        // starting where another ends doesn't
+        let symbols = vec![
+            symbol("inner", (0, 0), (2, 0)),
+            symbol("outer", (0, 0), (2, 0)),
+            symbol("next", (2, 0), (3, 0)),
+        ];
+        let mut lines = Vec::new();
+        outline(&nest_by_range(symbols), 0, &mut lines);
+        assert_eq!(lines, vec!["inner", "  outer", "next"]);
+    }
+
+    #[test]
+    fn test_expired_edit_is_rejected() {
+        let manager = LspManager::new();
+        let (reply, receiver) = mpsc::channel();
+        manager.pending_edits.lock().insert(1, reply);
+
+        expire_edit(&manager.pending_edits, 1);
+        assert!(!receiver.recv().unwrap().applied);
+        // The editor answering late is an error, not a second reply
+        assert!(manager.apply_edit_result(1, true, None).is_err());
+    }
+
+    #[test]
+    fn test_answered_edit_does_not_expire() {
+        let manager = LspManager::new();
+        let (reply, receiver) = mpsc::channel();
+        manager.pending_edits.lock().insert(1, reply);
+
+        manager.apply_edit_result(1, true, None).unwrap();
+        expire_edit(&manager.pending_edits, 1);
+        assert!(receiver.recv().unwrap().applied);
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_changed_text() {
+        let open = doc(3, "fn main() {}\n");
+        let changes = [insert(0, 11, "\n"), insert(1, 0, "    ")];
+        assert_eq!(
+            LspManager::changed_text(Some(&open), "/a.rs", Some(4), &changes).unwrap(),
+            "fn main() {\n    }\n"
+        );
+        assert_eq!(
+            LspManager::changed_text(Some(&open), "/a.rs", None, &[full("x")]).unwrap(),
+            "x"
+        );
+        // Stale versions and changes outside the document are rejected
+        assert!(LspManager::changed_text(Some(&open), "/a.rs", Some(3), &changes).is_err());
+        assert!(
+            LspManager::changed_text(Some(&open), "/a.rs", None, &[insert(5, 0, "x")]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_changed_text_not_open() {
+        assert_eq!(
+            LspManager::changed_text(None, "/a.rs", None, &[full("text")]).unwrap(),
+            "text"
+        );
+        assert!(LspManager::changed_text(None, "/a.rs", Some(2), &[insert(0, 0, "x")]).is_err());
+    }
+}
@@ -3,57 +3,49 @@
 //! This module provides LSP client functionality for communicating with
 //! language servers like rust-analyzer.
 
+mod config;
 mod manager;
-mod protocol;
+mod semantic_tokens;
 mod server;
 mod transport;
 
+pub use edit::{
+    path_to_uri, uri_to_path, workspace_edit_paths, AppliedWorkspaceEdit, PendingFileEdits,
+};
 pub use manager::{
-    LspManager, SerializedCodeAction, SerializedCompletionItem, SerializedHover, SerializedLocation,
+    LspManager, SerializedCodeAction, SerializedCompletionItem, SerializedContentChange,
+    SerializedHierarchyCall, SerializedHierarchyItem, SerializedHover, SerializedInlayHint,
+    SerializedLocation, SerializedPrepareRename, SerializedSignatureHelp,
+    SerializedSignatureHelpContext, SerializedSymbol,
 };
+pub use raven_core::lsp::{SerializedRange, SerializedTextEdit, SerializedWorkspaceEdit};
+pub use semantic_tokens::SerializedSemanticToken;
 
-use std::path::Path;
-use tauri::{AppHandle, State};
-
-/// Find the LSP root directory for a given file.
-/// For Rust, this finds the nearest directory containing Cargo.toml.
-fn find_lsp_root(file_path: &str) -> Option<String> {
-    let path = Path::new(file_path);
-    let mut dir = path.parent()?;
-
-    loop {
-        // Check for Cargo.toml (Rust)
-        if dir.join("Cargo.toml").exists() {
-            return Some(dir.to_string_lossy().to_string());
-        }
-
-        // Check for package.json (JS/TS)
-        if dir.join("package.json").exists() {
-            return Some(dir.to_string_lossy().to_string());
-        }
+use raven_core::lsp::{edit, protocol};
 
-        dir = match dir.parent() {
-            Some(p) => p,
-            None => return None,
-        };
-    }
-}
+use crate::scope::PathScope;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, State};
 
 // Tauri commands
 
-/// Get the LSP root directory for a file.
+/// Get the LSP root directory for a file, e.g. the nearest directory
+/// containing Cargo.toml for a Rust file. None if no server handles the file.
 #[tauri::command]
-pub fn lsp_find_root(file_path: String) -> Option<String> {
-    find_lsp_root(&file_path)
+pub fn lsp_find_root(state: State<'_, LspManager>, file_path: String) -> Option<String> {
+    state.find_root(&file_path)
 }
 
+/// Start the language servers for a file's language in a project.
 #[tauri::command]
 pub fn lsp_start(
     app: AppHandle,
     state: State<'_, LspManager>,
     root_path: String,
+    file_path: String,
 ) -> Result<(), String> {
-    state.start(&root_path, app)
+    state.start(&root_path, &file_path, app)
 }
 
 #[tauri::command]
@@ -71,14 +63,21 @@ pub fn lsp_open_document(
     state.open_document(&root_path, &file_path, &content)
 }
 
+/// Apply edits to an open document. `version` (the document version after
+/// the edits) is checked against the backend's copy when given; on a mismatch
+/// the editor should resend the whole document without one. Returns the new
+/// version.
 #[tauri::command]
 pub fn lsp_change_document(
     state: State<'_, LspManager>,
     root_path: String,
     file_path: String,
-    content: String,
-) -> Result<(), String> {
-    state.change_document(&root_path, &file_path, &content)
+    version: Option<i32>,
+    changes: Vec<SerializedContentChange>,
+) -> Result<i32, String> {
+    let changes: Vec<protocol::TextDocumentContentChangeEvent> =
+        changes.into_iter().map(Into::into).collect();
+    state.change_document(&root_path, &file_path, version, &changes)
 }
 
 #[tauri::command]
@@ -134,6 +133,22 @@ pub fn lsp_completion(
         })
 }
 
+/// Get the signatures of the call at a position, for the cursor popup.
+/// `context` says what triggered the request, e.g. a typed `(`.
+#[tauri::command]
+pub fn lsp_signature_help(
+    state: State<'_, LspManager>,
+    root_path: String,
+    file_path: String,
+    line: u32,
+    character: u32,
+    context: Option<SerializedSignatureHelpContext>,
+) -> Result<Option<SerializedSignatureHelp>, String> {
+    state
+        .signature_help(&root_path, &file_path, line, character, context)
+        .map(|h| h.map(SerializedSignatureHelp::from))
+}
+
 #[tauri::command]
 pub fn lsp_references(
     state: State<'_, LspManager>,
@@ -152,7 +167,7 @@ pub fn lsp_references(
 #[derive(Debug, Clone, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SerializedDiagnostic {
-    pub range: manager::SerializedRange,
+    pub range: SerializedRange,
     pub severity: Option<u8>,
     pub code: Option<serde_json::Value>,
     pub source: Option<String>,
@@ -249,9 +264,287 @@ impl From<CodeActionForResolve> for protocol::CodeAction {
 pub fn lsp_resolve_code_action(
     state: State<'_, LspManager>,
     root_path: String,
+    file_path: String,
     code_action: CodeActionForResolve,
 ) -> Result<SerializedCodeAction, String> {
     state
-        .resolve_code_action(&root_path, code_action.into())
+        .resolve_code_action(&root_path, &file_path, code_action.into())
         .map(SerializedCodeAction::from)
 }
+
+/// Get the call hierarchy items at a position, e.g. the function there. Expand
+/// them with `lsp_incoming_calls` and `lsp_outgoing_calls`.
+#[tauri::command]
+pub fn lsp_prepare_call_hierarchy(
+    state: State<'_, LspManager>,
+    root_path: String,
+    file_path: String,
+    line: u32,
+    character: u32,
+) -> Result<Vec<SerializedHierarchyItem>, String> {
+    state
+        .prepare_call_hierarchy(&root_path, &file_path, line, character)
+        .map(|items| {
+            items
+                .into_iter()
+                .map(SerializedHierarchyItem::from)
+                .collect()
+        })
+}
+
+/// Find the callers of a call hierarchy item.
+#[tauri::command]
+pub fn lsp_incoming_calls(
+    state: State<'_, LspManager>,
+    root_path: String,
+    item: SerializedHierarchyItem,
+) -> Result<Vec<SerializedHierarchyCall>, String> {
+    state.incoming_calls(&root_path, item.into()).map(|calls| {
+        calls
+            .into_iter()
+            .map(SerializedHierarchyCall::from)
+            .collect()
+    })
+}
+
+/// Find what a call hierarchy item calls.
+#[tauri::command]
+pub fn lsp_outgoing_calls(
+    state: State<'_, LspManager>,
+    root_path: String,
+    item: SerializedHierarchyItem,
+) -> Result<Vec<SerializedHierarchyCall>, String> {
+    state.outgoing_calls(&root_path, item.into()).map(|calls| {
+        calls
+            .into_iter()
+            .map(SerializedHierarchyCall::from)
+            .collect()
+    })
+}
+
+/// Get the type hierarchy items at a position, e.g. the type there. Expand
+/// them with `lsp_supertypes` and `lsp_subtypes`.
+#[tauri::command]
+pub fn lsp_prepare_type_hierarchy(
+    state: State<'_, LspManager>,
+    root_path: String,
+    file_path: String,
+    line: u32,
+    character: u32,
+) -> Result<Vec<SerializedHierarchyItem>, String> {
+    state
+        .prepare_type_hierarchy(&root_path, &file_path, line, character)
+        .map(|items| {
+            items
+                .into_iter()
+                .map(SerializedHierarchyItem::from)
+                .collect()
+        })
+}
+
+/// Find the supertypes of a type hierarchy item.
+#[tauri::command]
+pub fn lsp_supertypes(
+    state: State<'_, LspManager>,
+    root_path: String,
+    item: SerializedHierarchyItem,
+) -> Result<Vec<SerializedHierarchyItem>, String> {
+    state.supertypes(&root_path, item.into()).map(|items| {
+        items
+            .into_iter()
+            .map(SerializedHierarchyItem::from)
+            .collect()
+    })
+}
+
+/// Find the subtypes of a type hierarchy item.
+#[tauri::command]
+pub fn lsp_subtypes(
+    state: State<'_, LspManager>,
+    root_path: String,
+    item: SerializedHierarchyItem,
+) -> Result<Vec<SerializedHierarchyItem>, String> {
+    state.subtypes(&root_path, item.into()).map(|items| {
+        items
+            .into_iter()
+            .map(SerializedHierarchyItem::from)
+            .collect()
+    })
+}
+
+/// Get the semantic tokens for a whole document, as absolute spans.
+#[tauri::command]
+pub fn lsp_semantic_tokens(
+    state: State<'_, LspManager>,
+    root_path: String,
+    file_path: String,
+) -> Result<Vec<SerializedSemanticToken>, String> {
+    state.semantic_tokens(&root_path, &file_path)
+}
+
+/// Get the semantic tokens in a range of a document, as absolute spans.
+#[tauri::command]
+pub fn lsp_semantic_tokens_range(
+    state: State<'_, LspManager>,
+    root_path: String,
+    file_path: String,
+    range: SerializedRange,
+) -> Result<Vec<SerializedSemanticToken>, String> {
+    state.semantic_tokens_range(&root_path, &file_path, range.into())
+}
+
+/// Get the inlay hints in a range of a document.
+#[tauri::command]
+pub fn lsp_inlay_hints(
+    state: State<'_, LspManager>,
+    root_path: String,
+    file_path: String,
+    range: SerializedRange,
+) -> Result<Vec<SerializedInlayHint>, String> {
+    state
+        .inlay_hints(&root_path, &file_path, range.into())
+        .map(|hints| hints.into_iter().map(SerializedInlayHint::from).collect())
+}
+
+/// Fill in an inlay hint's tooltip, for hints with `needsResolve` set.
+#[tauri::command]
+pub fn lsp_resolve_inlay_hint(
+    state: State<'_, LspManager>,
+    root_path: String,
+    file_path: String,
+    hint: protocol::InlayHint,
+) -> Result<SerializedInlayHint, String> {
+    state
+        .resolve_inlay_hint(&root_path, &file_path, hint)
+        .map(SerializedInlayHint::from)
+}
+
+/// Format a document. Returns the edits to apply to it.
+#[tauri::command]
+pub fn lsp_format_document(
+    state: State<'_, LspManager>,
+    root_path: String,
+    file_path: String,
+    options: protocol::FormattingOptions,
+) -> Result<Vec<SerializedTextEdit>, String> {
+    state
+        .format_document(&root_path, &file_path, options)
+        .map(|edits| edits.into_iter().map(SerializedTextEdit::from).collect())
+}
+
+/// Format part of a document. Returns the edits to apply to it.
+#[tauri::command]
+pub fn lsp_format_range(
+    state: State<'_, LspManager>,
+    root_path: String,
+    file_path: String,
+    range: SerializedRange,
+    options: protocol::FormattingOptions,
+) -> Result<Vec<SerializedTextEdit>, String> {
+    state
+        .format_range(&root_path, &file_path, range.into(), options)
+        .map(|edits| edits.into_iter().map(SerializedTextEdit::from).collect())
+}
+
+/// Get the edits to apply to a document before saving it, e.g. formatting.
+/// `reason` is an LSP `TextDocumentSaveReason`, manual if not given. Save the
+/// result with `save_file`, which tells the servers it was saved.
+#[tauri::command]
+pub fn lsp_will_save(
+    state: State<'_, LspManager>,
+    root_path: String,
+    file_path: String,
+    reason: Option<u8>,
+    options: protocol::FormattingOptions,
+) -> Result<Vec<SerializedTextEdit>, String> {
+    let reason = reason
+        .map(protocol::TextDocumentSaveReason)
+        .unwrap_or(protocol::TextDocumentSaveReason::MANUAL);
+    state
+        .will_save(&root_path, &file_path, reason, options)
+        .map(|edits| edits.into_iter().map(SerializedTextEdit::from).collect())
+}
+
+/// Get a document's symbols as an outline tree.
+#[tauri::command]
+pub fn lsp_document_symbols(
+    state: State<'_, LspManager>,
+    root_path: String,
+    file_path: String,
+) -> Result<Vec<SerializedSymbol>, String> {
+    let uri = path_to_uri(Path::new(&file_path));
+    state.document_symbols(&root_path, &file_path).map(|r| {
+        r.map(|r| SerializedSymbol::outline(&uri, r))
+            .unwrap_or_default()
+    })
+}
+
+/// Search a project's symbols by name.
+#[tauri::command]
+pub fn lsp_workspace_symbols(
+    state: State<'_, LspManager>,
+    root_path: String,
+    query: String,
+) -> Result<Vec<SerializedSymbol>, String> {
+    state
+        .workspace_symbols(&root_path, &query)
+        .map(|symbols| symbols.into_iter().map(SerializedSymbol::from).collect())
+}
+
+/// Check the symbol at a position can be renamed. None if it can't.
+#[tauri::command]
+pub fn lsp_prepare_rename(
+    state: State<'_, LspManager>,
+    root_path: String,
+    file_path: String,
+    line: u32,
+    character: u32,
+) -> Result<Option<SerializedPrepareRename>, String> {
+    state
+        .prepare_rename(&root_path, &file_path, line, character)
+        .map(|r| r.map(SerializedPrepareRename::from))
+}
+
+/// Get the edit renaming the symbol at a position to `new_name`. Apply it
+/// with `lsp_apply_workspace_edit`.
+#[tauri::command]
+pub fn lsp_rename(
+    state: State<'_, LspManager>,
+    root_path: String,
+    file_path: String,
+    line: u32,
+    character: u32,
+    new_name: String,
+) -> Result<Option<SerializedWorkspaceEdit>, String> {
+    state
+        .rename(&root_path, &file_path, line, character, &new_name)
+        .map(|e| e.map(SerializedWorkspaceEdit::from))
+}
+
+/// Answer an edit a server asked for in an "lsp:apply-edit" event, after
+/// applying it with `lsp_apply_workspace_edit` or failing to.
+#[tauri::command]
+pub fn lsp_apply_edit_result(
+    state: State<'_, LspManager>,
+    id: u64,
+    applied: bool,
+    failure_reason: Option<String>,
+) -> Result<(), String> {
+    state.apply_edit_result(id, applied, failure_reason)
+}
+
+/// Apply a workspace edit to files on disk, except text edits to files in
+/// `open_paths`, which are returned for the editor to apply to its buffers.
+#[tauri::command]
+pub fn lsp_apply_workspace_edit(
+    scope: State<'_, PathScope>,
+    edit: SerializedWorkspaceEdit,
+    open_paths: Vec<String>,
+) -> Result<AppliedWorkspaceEdit, String> {
+    let edit = protocol::WorkspaceEdit::from(edit);
+    for path in workspace_edit_paths(&edit)? {
+        scope.check(&path)?;
+    }
+    let open_paths: HashSet<PathBuf> = open_paths.into_iter().map(PathBuf::from).collect();
+    edit::apply_workspace_edit(edit, &open_paths)
+}
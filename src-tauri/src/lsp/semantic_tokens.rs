@@ -0,0 +1,185 @@
+//! Decoding LSP semantic tokens into absolute spans.
+
+use super::protocol::{SemanticTokensEdit, SemanticTokensLegend};
+use serde::Serialize;
+
+/// A semantic token for the frontend, with its position made absolute and
+/// its type and modifiers named from the server's legend.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SerializedSemanticToken {
+    pub line: u32,
+    /// In UTF-16 code units
+    pub start_character: u32,
+    pub length: u32,
+    pub token_type: String,
+    pub modifiers: Vec<String>,
+}
+
+/// Decode relative-encoded token data. Tokens with a type missing from the
+/// legend are dropped, as are modifier bits past the end of it.
+pub fn decode(data: &[u32], legend: &SemanticTokensLegend) -> Vec<SerializedSemanticToken> {
+    let mut tokens = Vec::with_capacity(data.len() / 5);
+    let mut line: u32 = 0;
+    let mut start_character: u32 = 0;
+    for token in data.chunks_exact(5) {
+        let [delta_line, delta_start, length, token_type, modifier_bits] =
+            [token[0], token[1], token[2], token[3], token[4]];
+        // Saturating, as the data comes from the server
+        if delta_line > 0 {
+            line = line.saturating_add(delta_line);
+            start_character = delta_start;
+        } else {
+            start_character = start_character.saturating_add(delta_start);
+        }
+
+        let Some(token_type) = legend.token_types.get(token_type as usize) else {
+            continue;
+        };
+        let modifiers = legend
+            .token_modifiers
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| *bit < 32 && modifier_bits & (1 << bit) != 0)
+            .map(|(_, modifier)| modifier.clone())
+            .collect();
+
+        tokens.push(SerializedSemanticToken {
+            line,
+            start_character,
+            length,
+            token_type: token_type.clone(),
+            modifiers,
+        });
+    }
+    tokens
+}
+
+/// Apply a delta's edits to the previous token data. Edits all refer to the
+/// previous data, so they're applied from the end back.
+pub fn apply_delta(data: &mut Vec<u32>, mut edits: Vec<SemanticTokensEdit>) -> Result<(), String> {
+    edits.sort_by_key(|e| std::cmp::Reverse(e.start));
+    let mut end = data.len();
+    for edit in edits {
+        let start = edit.start as usize;
+        let delete_end = start + edit.delete_count as usize;
+        if delete_end > end {
+            return Err("Semantic tokens edit out of range".to_string());
+        }
+        data.splice(start..delete_end, edit.data.unwrap_or_default());
+        end = start;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn legend() -> SemanticTokensLegend {
+        SemanticTokensLegend {
+            token_types: vec!["function".to_string(), "variable".to_string()],
+            token_modifiers: vec!["declaration".to_string(), "readonly".to_string()],
+        }
+    }
+
+    fn spans(tokens: &[SerializedSemanticToken]) -> Vec<(u32, u32, u32, &str)> {
+        tokens
+            .iter()
+            .map(|t| (t.line, t.start_character, t.length, t.token_type.as_str()))
+            .collect()
+    }
+
+    fn edit(start: u32, delete_count: u32, data: &[u32]) -> SemanticTokensEdit {
+        SemanticTokensEdit {
+            start,
+            delete_count,
+            data: Some(data.to_vec()),
+        }
+    }
+
+    #[test]
+    fn test_decode_relative_positions() {
+        let data = [
+            1, 3, 4, 0, 0, // line 1, column 3
+            0, 6, 2, 1, 0, // same line, 6 further on
+            2, 1, 5, 1, 0, // two lines down, column resets
+        ];
+        let tokens = decode(&data, &legend());
+        assert_eq!(
+            spans(&tokens),
+            vec![
+                (1, 3, 4, "function"),
+                (1, 9, 2, "variable"),
+                (3, 1, 5, "variable")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_modifiers() {
+        let data = [
+            [0, 0, 1, 0, 0b01],
+            [0, 2, 1, 0, 0b11],
+            [0, 2, 1, 0, 0b100 | 1 << 31],
+        ]
+        .concat();
+        let tokens = decode(&data, &legend());
+        let modifiers: Vec<_> = tokens.iter().map(|t| t.modifiers.clone()).collect();
+        assert_eq!(
+            modifiers,
+            vec![
+                vec!["declaration".to_string()],
+                vec!["declaration".to_string(), "readonly".to_string()],
+                // Bits past the end of the legend are ignored
+                vec![],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_unknown_types_and_overflow() {
+        let data = [
+            // No such type: dropped, but it still moves the position
+            [0, 1, 1, 7, 0],
+            [0, 2, 1, 0, 0],
+            // Saturates rather than overflowing
+            [u32::MAX, 0, 1, 0, 0],
+            [1, 0, 1, 0, 0],
+        ]
+        .concat();
+        // A trailing partial token is ignored
+        let data = [data, vec![0, 0]].concat();
+        let tokens = decode(&data, &legend());
+        assert_eq!(
+            spans(&tokens),
+            vec![
+                (0, 3, 1, "function"),
+                (u32::MAX, 0, 1, "function"),
+                (u32::MAX, 0, 1, "function")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_delta() {
+        let mut data: Vec<u32> = (0..15).collect();
+        // Given in any order; all refer to the original data
+        let edits = vec![
+            edit(10, 5, &[]),
+            edit(0, 0, &[90, 91]),
+            edit(5, 1, &[50, 51]),
+        ];
+        apply_delta(&mut data, edits).unwrap();
+        assert_eq!(data, vec![90, 91, 0, 1, 2, 3, 4, 50, 51, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn test_apply_delta_out_of_range() {
+        let mut data: Vec<u32> = (0..10).collect();
+        assert!(apply_delta(&mut data, vec![edit(8, 5, &[])]).is_err());
+        assert!(apply_delta(&mut data, vec![edit(12, 0, &[1])]).is_err());
+        // Overlapping edits
+        assert!(apply_delta(&mut data, vec![edit(0, 5, &[]), edit(3, 1, &[])]).is_err());
+    }
+}
@@ -2,12 +2,15 @@
 //!
 //! Handles spawning language servers, sending requests, and routing responses.
 
+use super::config::{detect_language, LanguageServerConfig};
+use super::edit::uri_to_path;
 use super::protocol::*;
 use super::transport::*;
+use globset::{GlobBuilder, GlobMatcher};
 use parking_lot::Mutex;
 use serde_json::Value as JsonValue;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::mpsc;
@@ -24,11 +27,13 @@ struct PendingRequest {
 pub struct LanguageServer {
     #[allow(dead_code)]
     process: Child,
-    writer: Mutex<MessageWriter>,
+    /// Shared with reader thread, which answers requests from the server
+    writer: Arc<Mutex<MessageWriter>>,
     next_id: AtomicU64,
     /// Shared with reader thread - must be Arc
     pending: Arc<Mutex<HashMap<u64, PendingRequest>>>,
-    capabilities: Mutex<Option<ServerCapabilities>>,
+    /// Shared with reader thread, which applies registrations
+    capabilities: Arc<Mutex<Capabilities>>,
     /// Channel for server notifications (diagnostics, etc.)
     #[allow(dead_code)]
     notification_sender: mpsc::Sender<ServerNotification>,
@@ -40,6 +45,158 @@ pub struct LanguageServer {
 #[derive(Debug, Clone)]
 pub enum ServerNotification {
     Diagnostics(PublishDiagnosticsParams),
+    /// The server asked for inlay hints to be requested again
+    InlayHintRefresh,
+    /// The server asked for semantic tokens to be requested again
+    SemanticTokensRefresh,
+    /// The server asked for an edit to be applied. The result goes back
+    /// through `reply`, which is answered as not applied if the editor takes
+    /// too long.
+    ApplyEdit {
+        params: ApplyWorkspaceEditParams,
+        reply: mpsc::Sender<ApplyWorkspaceEditResult>,
+    },
+}
+
+/// The server capability field a method registered dynamically enables, as
+/// a path into the capabilities, and whether it's a plain boolean rather than
+/// taking the registration options.
+fn registration_capability(method: &str) -> Option<(&'static [&'static str], bool)> {
+    let capability: (&[&str], bool) = match method {
+        "textDocument/completion" => (&["completionProvider"], false),
+        "textDocument/signatureHelp" => (&["signatureHelpProvider"], false),
+        "textDocument/hover" => (&["hoverProvider"], true),
+        "textDocument/definition" => (&["definitionProvider"], true),
+        "textDocument/references" => (&["referencesProvider"], true),
+        "textDocument/rename" => (&["renameProvider"], false),
+        "textDocument/documentSymbol" => (&["documentSymbolProvider"], false),
+        "workspace/symbol" => (&["workspaceSymbolProvider"], false),
+        "textDocument/formatting" => (&["documentFormattingProvider"], false),
+        "textDocument/rangeFormatting" => (&["documentRangeFormattingProvider"], false),
+        "textDocument/inlayHint" => (&["inlayHintProvider"], false),
+        "textDocument/semanticTokens" => (&["semanticTokensProvider"], false),
+        "textDocument/prepareCallHierarchy" => (&["callHierarchyProvider"], false),
+        "textDocument/prepareTypeHierarchy" => (&["typeHierarchyProvider"], false),
+        "workspace/willRenameFiles" => (&["workspace", "fileOperations", "willRename"], false),
+        "workspace/didRenameFiles" => (&["workspace", "fileOperations", "didRename"], false),
+        _ => return None,
+    };
+    Some(capability)
+}
+
+/// What a server can do: the capabilities it announced at initialization,
+/// with those it has registered since applied.
+#[derive(Default)]
+struct Capabilities {
+    initial: Option<ServerCapabilities>,
+    /// By registration ID
+    registrations: HashMap<String, DynamicRegistration>,
+    /// Capabilities with registrations applied, by the IDs of the
+    /// registrations. Cleared when they change.
+    merged: HashMap<Vec<String>, ServerCapabilities>,
+    watchers: Arc<FileWatchers>,
+}
+
+struct DynamicRegistration {
+    registration: Registration,
+    /// The documents it applies to, or `None` for all of them
+    selector: Option<Vec<DocumentMatcher>>,
+}
+
+/// A compiled `DocumentFilter`. Unset fields match any document.
+struct DocumentMatcher {
+    language: Option<String>,
+    scheme: Option<String>,
+    /// `None` if the glob was invalid, so nothing matches
+    pattern: Option<Option<PathGlob>>,
+}
+
+impl DocumentMatcher {
+    fn matches(&self, path: &Path, language_id: &str) -> bool {
+        self.language.as_deref().is_none_or(|l| l == language_id)
+            && self.scheme.as_deref().is_none_or(|s| s == "file")
+            && self
+                .pattern
+                .as_ref()
+                .is_none_or(|glob| glob.as_ref().is_some_and(|g| g.is_match(path)))
+    }
+}
+
+impl Capabilities {
+    /// The capabilities for a document, given its path and language ID, or
+    /// without one, those that don't depend on the document.
+    fn get(&mut self, document: Option<(&Path, &str)>) -> Option<ServerCapabilities> {
+        let initial = self.initial.as_ref()?;
+        let mut ids: Vec<String> = self
+            .registrations
+            .iter()
+            .filter(|(_, r)| match (&r.selector, document) {
+                (None, _) => true,
+                (Some(filters), Some((path, language_id))) => {
+                    filters.iter().any(|f| f.matches(path, language_id))
+                }
+                (Some(_), None) => false,
+            })
+            .map(|(id, _)| id.clone())
+            .collect();
+        if ids.is_empty() {
+            return Some(initial.clone());
+        }
+        ids.sort();
+        if let Some(merged) = self.merged.get(&ids) {
+            return Some(merged.clone());
+        }
+        let merged = with_registrations(
+            initial.clone(),
+            ids.iter().map(|id| &self.registrations[id].registration),
+        );
+        self.merged.insert(ids, merged.clone());
+        Some(merged)
+    }
+
+    fn register(&mut self, registrations: Vec<Registration>) {
+        for registration in registrations {
+            let selector = registration
+                .register_options
+                .clone()
+                .and_then(|options| {
+                    serde_json::from_value::<TextDocumentRegistrationOptions>(options).ok()
+                })
+                .and_then(|options| options.document_selector)
+                .map(|filters| {
+                    filters
+                        .into_iter()
+                        .map(|filter| DocumentMatcher {
+                            language: filter.language,
+                            scheme: filter.scheme,
+                            pattern: filter.pattern.map(PathGlob::new),
+                        })
+                        .collect()
+                });
+            self.registrations.insert(
+                registration.id.clone(),
+                DynamicRegistration {
+                    registration,
+                    selector,
+                },
+            );
+        }
+        self.changed();
+    }
+
+    fn unregister(&mut self, unregistrations: Vec<Unregistration>) {
+        for unregistration in unregistrations {
+            self.registrations.remove(&unregistration.id);
+        }
+        self.changed();
+    }
+
+    fn changed(&mut self) {
+        self.merged.clear();
+        self.watchers = Arc::new(FileWatchers::new(
+            self.registrations.values().map(|r| &r.registration),
+        ));
+    }
 }
 
 impl LanguageServer {
@@ -109,13 +266,255 @@ impl LanguageServer {
             .map_err(|e| format!("Failed to send notification: {}", e))
     }
 
-    /// Get server capabilities (available after initialization).
-    #[allow(dead_code)]
+    /// Get server capabilities (available after initialization), including
+    /// any registered since for all documents.
     pub fn capabilities(&self) -> Option<ServerCapabilities> {
-        self.capabilities.lock().clone()
+        self.capabilities.lock().get(None)
+    }
+
+    /// Get the server's capabilities for a document, including any
+    /// registered since for documents like it.
+    pub fn document_capabilities(&self, file_path: &str) -> Option<ServerCapabilities> {
+        let language_id = detect_language(file_path);
+        self.capabilities
+            .lock()
+            .get(Some((Path::new(file_path), &language_id)))
+    }
+
+    /// The files the server has registered to hear about changes to.
+    pub fn file_watchers(&self) -> Arc<FileWatchers> {
+        self.capabilities.lock().watchers.clone()
+    }
+}
+
+/// A compiled `GlobPattern`.
+struct PathGlob {
+    /// Paths are matched relative to this, or whole if there's none
+    base: Option<PathBuf>,
+    glob: GlobMatcher,
+}
+
+impl PathGlob {
+    /// Compile a pattern. Fails if the glob is invalid or its base isn't a
+    /// file URI.
+    fn new(pattern: GlobPattern) -> Option<Self> {
+        let (base, pattern) = match pattern {
+            GlobPattern::Pattern(pattern) => (None, pattern),
+            GlobPattern::Relative(RelativePattern { base_uri, pattern }) => {
+                let (BaseUri::Uri(uri) | BaseUri::Folder { uri }) = base_uri;
+                (Some(uri_to_path(&uri)?), pattern)
+            }
+        };
+        let glob = GlobBuilder::new(&pattern)
+            .literal_separator(true)
+            .build()
+            .ok()?
+            .compile_matcher();
+        Some(Self { base, glob })
+    }
+
+    fn is_match(&self, path: &Path) -> bool {
+        match &self.base {
+            Some(base) => path
+                .strip_prefix(base)
+                .is_ok_and(|rest| self.glob.is_match(rest)),
+            None => self.glob.is_match(path),
+        }
+    }
+}
+
+/// Files a server watches through registered
+/// `workspace/didChangeWatchedFiles` watchers.
+#[derive(Default)]
+pub struct FileWatchers(Vec<(PathGlob, u8)>);
+
+impl FileWatchers {
+    /// Compile the watchers of every didChangeWatchedFiles registration.
+    /// Watchers with an invalid glob or a base that isn't a file URI are
+    /// left out.
+    fn new<'a>(registrations: impl Iterator<Item = &'a Registration>) -> Self {
+        let watchers = registrations
+            .filter(|registration| registration.method == "workspace/didChangeWatchedFiles")
+            .filter_map(|registration| {
+                serde_json::from_value::<DidChangeWatchedFilesRegistrationOptions>(
+                    registration.register_options.clone()?,
+                )
+                .ok()
+            })
+            .flat_map(|options| options.watchers)
+            .filter_map(|watcher| {
+                let kind = watcher.kind();
+                Some((PathGlob::new(watcher.glob_pattern)?, kind))
+            })
+            .collect();
+        Self(watchers)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether any watcher wants to hear about this change to `path`.
+    pub fn matches(&self, path: &Path, change_type: FileChangeType) -> bool {
+        self.0
+            .iter()
+            .any(|(glob, kind)| kind & change_type.watch_kind() != 0 && glob.is_match(path))
+    }
+}
+
+/// Capabilities with dynamic registrations applied. Registrations that don't
+/// fit the capability's shape are left out.
+fn with_registrations<'a>(
+    capabilities: ServerCapabilities,
+    registrations: impl Iterator<Item = &'a Registration>,
+) -> ServerCapabilities {
+    let Ok(mut fields) = serde_json::to_value(&capabilities) else {
+        return capabilities;
+    };
+    for registration in registrations {
+        let Some((path, is_bool)) = registration_capability(&registration.method) else {
+            continue;
+        };
+        let value = if is_bool {
+            JsonValue::Bool(true)
+        } else {
+            registration
+                .register_options
+                .clone()
+                .unwrap_or_else(|| serde_json::json!({}))
+        };
+        let mut updated = fields.clone();
+        let Some(field) = path.iter().try_fold(&mut updated, |object, field| {
+            Some(
+                object
+                    .as_object_mut()?
+                    .entry(*field)
+                    .or_insert_with(|| serde_json::json!({})),
+            )
+        }) else {
+            continue;
+        };
+        *field = value;
+        if serde_json::from_value::<ServerCapabilities>(updated.clone()).is_ok() {
+            fields = updated;
+        }
+    }
+    serde_json::from_value(fields).unwrap_or(capabilities)
+}
+
+/// Answers requests the server sends to the client, from the reader thread.
+struct RequestDispatcher {
+    config: LanguageServerConfig,
+    writer: Arc<Mutex<MessageWriter>>,
+    capabilities: Arc<Mutex<Capabilities>>,
+    notification_sender: mpsc::Sender<ServerNotification>,
+}
+
+impl RequestDispatcher {
+    fn dispatch(&self, id: NumberOrString, method: &str, params: Option<JsonValue>) {
+        let result = match method {
+            "workspace/configuration" => self.configuration(params),
+            "client/registerCapability" => self.register_capability(params),
+            "client/unregisterCapability" => self.unregister_capability(params),
+            // Progress isn't shown, but the token has to be accepted before
+            // the server reports against it
+            "window/workDoneProgress/create" => Ok(JsonValue::Null),
+            "workspace/inlayHint/refresh" => {
+                let _ = self
+                    .notification_sender
+                    .send(ServerNotification::InlayHintRefresh);
+                Ok(JsonValue::Null)
+            }
+            "workspace/semanticTokens/refresh" => {
+                let _ = self
+                    .notification_sender
+                    .send(ServerNotification::SemanticTokensRefresh);
+                Ok(JsonValue::Null)
+            }
+            "workspace/applyEdit" => match parse_params(params) {
+                // Answered once the editor has applied it
+                Ok(params) => return self.apply_edit(id, params),
+                Err(err) => Err(err),
+            },
+            _ => Err(ResponseError {
+                code: ResponseError::METHOD_NOT_FOUND,
+                message: format!("Unhandled method {}", method),
+            }),
+        };
+        respond(&self.writer, id, result);
+    }
+
+    fn configuration(&self, params: Option<JsonValue>) -> Result<JsonValue, ResponseError> {
+        let params: ConfigurationParams = parse_params(params)?;
+        let values = params
+            .items
+            .iter()
+            .map(|item| self.config.configuration(item.section.as_deref()))
+            .collect();
+        Ok(JsonValue::Array(values))
+    }
+
+    fn register_capability(&self, params: Option<JsonValue>) -> Result<JsonValue, ResponseError> {
+        let params: RegistrationParams = parse_params(params)?;
+        self.capabilities.lock().register(params.registrations);
+        Ok(JsonValue::Null)
+    }
+
+    fn unregister_capability(&self, params: Option<JsonValue>) -> Result<JsonValue, ResponseError> {
+        let params: UnregistrationParams = parse_params(params)?;
+        self.capabilities.lock().unregister(params.unregisterations);
+        Ok(JsonValue::Null)
+    }
+
+    /// Forward the edit to the editor and answer from another thread, so
+    /// responses to our own requests keep flowing while it's applied.
+    fn apply_edit(&self, id: NumberOrString, params: ApplyWorkspaceEditParams) {
+        let writer = self.writer.clone();
+        let notification_sender = self.notification_sender.clone();
+        thread::spawn(move || {
+            let (reply, receiver) = mpsc::channel();
+            let result = if notification_sender
+                .send(ServerNotification::ApplyEdit { params, reply })
+                .is_err()
+            {
+                ApplyWorkspaceEditResult {
+                    applied: false,
+                    failure_reason: Some("Editor is not listening".to_string()),
+                }
+            } else {
+                receiver
+                    .recv()
+                    .unwrap_or_else(|_| ApplyWorkspaceEditResult {
+                        applied: false,
+                        failure_reason: Some("Editor did not apply the edit".to_string()),
+                    })
+            };
+            respond(&writer, id, Ok(serde_json::to_value(result).unwrap()));
+        });
     }
 }
 
+fn parse_params<T: serde::de::DeserializeOwned>(
+    params: Option<JsonValue>,
+) -> Result<T, ResponseError> {
+    serde_json::from_value(params.unwrap_or(JsonValue::Null)).map_err(|e| ResponseError {
+        code: ResponseError::INVALID_PARAMS,
+        message: format!("Invalid params: {}", e),
+    })
+}
+
+fn respond(
+    writer: &Mutex<MessageWriter>,
+    id: NumberOrString,
+    result: Result<JsonValue, ResponseError>,
+) {
+    let response = match result {
+        Ok(value) => Response::ok(id, value),
+        Err(err) => Response::error(id, err),
+    };
+    let _ = writer.lock().send_response(&response);
+}
+
 /// Find an executable by checking common paths.
 /// GUI apps on macOS don't inherit shell PATH, so we need to search manually.
 fn find_executable(name: &str) -> Option<PathBuf> {
@@ -152,17 +551,18 @@ fn find_executable(name: &str) -> Option<PathBuf> {
 
 /// Spawn a language server and perform the LSP initialization handshake.
 pub fn spawn_and_initialize(
-    command: &str,
-    args: &[String],
+    config: &LanguageServerConfig,
     root_uri: &str,
     notification_sender: mpsc::Sender<ServerNotification>,
 ) -> Result<Arc<LanguageServer>, String> {
     // Find the executable - try PATH first, then search common locations
+    let command = &config.command;
     let executable = find_executable(command).unwrap_or_else(|| PathBuf::from(command));
 
     // Spawn the process
     let mut process = Command::new(&executable)
-        .args(args)
+        .args(&config.args)
+        .envs(&config.env)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .stderr(Stdio::null())
@@ -183,23 +583,33 @@ pub fn spawn_and_initialize(
         .take()
         .ok_or_else(|| "Failed to get stdout".to_string())?;
 
-    let writer = Mutex::new(MessageWriter::new(stdin));
+    let writer = Arc::new(Mutex::new(MessageWriter::new(stdin)));
     let pending: Arc<Mutex<HashMap<u64, PendingRequest>>> = Arc::new(Mutex::new(HashMap::new()));
     let next_id = AtomicU64::new(1);
+    let capabilities = Arc::new(Mutex::new(Capabilities::default()));
 
     // Spawn reader thread
     let pending_for_reader = pending.clone();
     let notif_sender = notification_sender.clone();
+    let dispatcher = RequestDispatcher {
+        config: config.clone(),
+        writer: writer.clone(),
+        capabilities: capabilities.clone(),
+        notification_sender: notification_sender.clone(),
+    };
     let reader_handle = thread::spawn(move || {
         let reader = MessageReader::spawn(stdout);
         loop {
             match reader.recv() {
                 Some(msg) => {
                     if msg.is_response() {
-                        // Route response to waiting request
-                        if let Some(id) = msg.id {
+                        // Route response to waiting request. Our request IDs
+                        // are always numbers.
+                        if let Some(NumberOrString::Number(id)) = msg.id {
                             let mut pending = pending_for_reader.lock();
-                            if let Some(req) = pending.remove(&id) {
+                            if let Some(req) =
+                                u64::try_from(id).ok().and_then(|id| pending.remove(&id))
+                            {
                                 let result = if let Some(err) = msg.error {
                                     Err(err)
                                 } else {
@@ -227,6 +637,11 @@ pub fn spawn_and_initialize(
                                 _ => {}
                             }
                         }
+                    } else if msg.is_request() {
+                        // Answer requests from the server
+                        if let (Some(id), Some(method)) = (msg.id, &msg.method) {
+                            dispatcher.dispatch(id, method, msg.params);
+                        }
                     }
                 }
                 None => {
@@ -305,9 +720,38 @@ pub fn spawn_and_initialize(
             version: Some(env!("CARGO_PKG_VERSION").to_string()),
         }),
         root_uri: Some(root_uri.to_string()),
+        initialization_options: config.initialization_options.clone(),
         capabilities: ClientCapabilities {
+            workspace: Some(WorkspaceClientCapabilities {
+                configuration: Some(true),
+                apply_edit: Some(true),
+                did_change_watched_files: Some(DidChangeWatchedFilesClientCapabilities {
+                    dynamic_registration: Some(true),
+                }),
+                file_operations: Some(FileOperationClientCapabilities {
+                    dynamic_registration: Some(true),
+                    will_rename: Some(true),
+                    did_rename: Some(true),
+                }),
+                workspace_edit: Some(WorkspaceEditClientCapabilities {
+                    document_changes: Some(true),
+                    resource_operations: Some(vec![
+                        "create".to_string(),
+                        "rename".to_string(),
+                        "delete".to_string(),
+                    ]),
+                }),
+                inlay_hint: Some(InlayHintWorkspaceClientCapabilities {
+                    refresh_support: Some(true),
+                }),
+                semantic_tokens: Some(SemanticTokensWorkspaceClientCapabilities {
+                    refresh_support: Some(true),
+                }),
+            }),
             text_document: Some(TextDocumentClientCapabilities {
                 synchronization: Some(TextDocumentSyncClientCapabilities {
+                    will_save: Some(true),
+                    will_save_wait_until: Some(true),
                     did_save: Some(true),
                 }),
                 completion: Some(CompletionClientCapabilities {
@@ -349,6 +793,50 @@ pub fn spawn_and_initialize(
                         properties: vec!["edit".to_string()],
                     }),
                 }),
+                rename: Some(RenameClientCapabilities {
+                    prepare_support: Some(true),
+                }),
+                document_symbol: Some(DocumentSymbolClientCapabilities {
+                    hierarchical_document_symbol_support: Some(true),
+                }),
+                signature_help: Some(SignatureHelpClientCapabilities {
+                    signature_information: Some(SignatureInformationClientCapabilities {
+                        documentation_format: Some(vec![
+                            MarkupKind::Markdown,
+                            MarkupKind::PlainText,
+                        ]),
+                        parameter_information: Some(ParameterInformationClientCapabilities {
+                            label_offset_support: Some(true),
+                        }),
+                        active_parameter_support: Some(true),
+                    }),
+                    context_support: Some(true),
+                }),
+                inlay_hint: Some(InlayHintClientCapabilities {
+                    resolve_support: Some(InlayHintResolveSupport {
+                        properties: vec![
+                            "tooltip".to_string(),
+                            "textEdits".to_string(),
+                            "label.tooltip".to_string(),
+                            "label.location".to_string(),
+                        ],
+                    }),
+                }),
+                semantic_tokens: Some(SemanticTokensClientCapabilities {
+                    requests: SemanticTokensClientRequests {
+                        range: Some(true),
+                        full: Some(SemanticTokensFullCapability::Options { delta: Some(true) }),
+                    },
+                    token_types: SEMANTIC_TOKEN_TYPES.iter().map(|t| t.to_string()).collect(),
+                    token_modifiers: SEMANTIC_TOKEN_MODIFIERS
+                        .iter()
+                        .map(|m| m.to_string())
+                        .collect(),
+                    formats: vec!["relative".to_string()],
+                    overlapping_token_support: Some(false),
+                    multiline_token_support: Some(false),
+                    augments_syntax_tokens: Some(true),
+                }),
             }),
         },
     };
@@ -357,6 +845,8 @@ pub fn spawn_and_initialize(
         "initialize",
         Some(serde_json::to_value(&init_params).unwrap()),
     )?;
+    // Before the server can register anything
+    capabilities.lock().initial = Some(init_result.capabilities);
 
     // Send initialized notification
     helper.notify("initialized", Some(serde_json::json!({})))?;
@@ -367,10 +857,169 @@ pub fn spawn_and_initialize(
         writer,
         next_id,
         pending,
-        capabilities: Mutex::new(Some(init_result.capabilities)),
+        capabilities,
         notification_sender,
         _reader_handle: reader_handle,
     };
 
     Ok(Arc::new(server))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registration(id: &str, method: &str, options: Option<JsonValue>) -> Registration {
+        Registration {
+            id: id.to_string(),
+            method: method.to_string(),
+            register_options: options,
+        }
+    }
+
+    #[test]
+    fn test_with_registrations() {
+        let registrations = [
+            registration("1", "textDocument/hover", None),
+            registration(
+                "2",
+                "textDocument/completion",
+                Some(serde_json::json!({"triggerCharacters": ["."], "documentSelector": null})),
+            ),
+            registration("3", "workspace/didChangeWatchedFiles", None),
+        ];
+        let capabilities = with_registrations(ServerCapabilities::default(), registrations.iter());
+        assert_eq!(capabilities.hover_provider, Some(true));
+        let completion = capabilities.completion_provider.unwrap();
+        assert_eq!(completion.trigger_characters, Some(vec![".".to_string()]));
+        // Methods that aren't capabilities are skipped
+        assert!(capabilities.definition_provider.is_none());
+    }
+
+    #[test]
+    fn test_with_registrations_file_operations() {
+        let registrations = [registration(
+            "1",
+            "workspace/willRenameFiles",
+            Some(serde_json::json!({"filters": [{"pattern": {"glob": "**/*.rs"}}]})),
+        )];
+        let capabilities = with_registrations(ServerCapabilities::default(), registrations.iter());
+        let will_rename = capabilities
+            .workspace
+            .and_then(|w| w.file_operations?.will_rename)
+            .unwrap();
+        assert!(will_rename.matches(Path::new("/p/a.rs"), false));
+    }
+
+    #[test]
+    fn test_capabilities_by_document() {
+        let mut capabilities = Capabilities {
+            initial: Some(ServerCapabilities::default()),
+            ..Default::default()
+        };
+        capabilities.register(vec![
+            registration(
+                "1",
+                "textDocument/hover",
+                Some(serde_json::json!({"documentSelector": [{"language": "rust"}]})),
+            ),
+            registration(
+                "2",
+                "textDocument/definition",
+                Some(serde_json::json!({"documentSelector": [{"pattern": "**/src/*.py"}]})),
+            ),
+            registration(
+                "3",
+                "textDocument/references",
+                Some(serde_json::json!({"documentSelector": null})),
+            ),
+        ]);
+        let rust = capabilities
+            .get(Some((Path::new("/p/src/a.rs"), "rust")))
+            .unwrap();
+        assert_eq!(rust.hover_provider, Some(true));
+        assert!(rust.definition_provider.is_none());
+        assert_eq!(rust.references_provider, Some(true));
+        let python = capabilities
+            .get(Some((Path::new("/p/src/a.py"), "python")))
+            .unwrap();
+        assert!(python.hover_provider.is_none());
+        assert_eq!(python.definition_provider, Some(true));
+        // Without a document, only what applies to every document
+        let any = capabilities.get(None).unwrap();
+        assert!(any.hover_provider.is_none());
+        assert_eq!(any.references_provider, Some(true));
+
+        // Merged once per set of registrations, until they change
+        assert_eq!(capabilities.merged.len(), 3);
+        capabilities.get(Some((Path::new("/p/src/b.rs"), "rust")));
+        assert_eq!(capabilities.merged.len(), 3);
+        capabilities.unregister(vec![Unregistration {
+            id: "1".to_string(),
+            method: "textDocument/hover".to_string(),
+        }]);
+        assert!(capabilities.merged.is_empty());
+        let rust = capabilities
+            .get(Some((Path::new("/p/src/a.rs"), "rust")))
+            .unwrap();
+        assert!(rust.hover_provider.is_none());
+    }
+
+    #[test]
+    fn test_file_watchers() {
+        let registrations = [
+            registration(
+                "1",
+                "workspace/didChangeWatchedFiles",
+                Some(serde_json::json!({"watchers": [
+                    {"globPattern": "**/Cargo.toml"},
+                    {"globPattern": {"baseUri": "file:///p/my%20src", "pattern": "*.rs"}, "kind": 1},
+                    {"globPattern": {"baseUri": {"uri": "file:///q", "name": "q"}, "pattern": "**/*.md"}},
+                    {"globPattern": "[invalid"},
+                ]})),
+            ),
+            registration("2", "textDocument/hover", None),
+        ];
+        let watchers = FileWatchers::new(registrations.iter());
+        assert_eq!(watchers.0.len(), 3);
+        let changed = FileChangeType::CHANGED;
+        assert!(watchers.matches(Path::new("/p/a/Cargo.toml"), changed));
+        assert!(watchers.matches(Path::new("/p/a/Cargo.toml"), FileChangeType::DELETED));
+        assert!(watchers.matches(Path::new("/p/my src/a.rs"), FileChangeType::CREATED));
+        // Only creations were asked for
+        assert!(!watchers.matches(Path::new("/p/my src/a.rs"), changed));
+        // Relative patterns match relative to their base
+        assert!(!watchers.matches(Path::new("/p/my src/a/b.rs"), FileChangeType::CREATED));
+        assert!(!watchers.matches(Path::new("/p/a.rs"), FileChangeType::CREATED));
+        assert!(watchers.matches(Path::new("/q/docs/a.md"), changed));
+        assert!(!watchers.matches(Path::new("/p/docs/a.md"), changed));
+
+        assert!(
+            FileWatchers::new([registration("1", "textDocument/hover", None)].iter()).is_empty()
+        );
+    }
+
+    #[test]
+    fn test_with_registrations_keeps_valid_capabilities() {
+        let capabilities: ServerCapabilities = serde_json::from_value(serde_json::json!({
+            "semanticTokensProvider": {
+                "legend": {"tokenTypes": ["function"], "tokenModifiers": []},
+                "full": true
+            }
+        }))
+        .unwrap();
+        // Semantic tokens options need a legend, so this is left out
+        let registrations = [
+            registration(
+                "1",
+                "textDocument/semanticTokens",
+                Some(serde_json::json!({})),
+            ),
+            registration("2", "textDocument/rename", None),
+        ];
+        let capabilities = with_registrations(capabilities, registrations.iter());
+        let tokens = capabilities.semantic_tokens_provider.unwrap();
+        assert_eq!(tokens.legend.token_types, vec!["function"]);
+        assert!(capabilities.rename_provider.unwrap().is_supported());
+    }
+}
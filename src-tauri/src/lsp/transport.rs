@@ -30,6 +30,15 @@ impl Request {
     }
 }
 
+/// The ID of a request from the server, which may be a number or a string.
+/// Responses echo it back unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum NumberOrString {
+    Number(i64),
+    String(String),
+}
+
 /// A JSON-RPC notification (no id, no response expected).
 #[derive(Debug, Clone, Serialize)]
 pub struct Notification {
@@ -49,12 +58,49 @@ impl Notification {
     }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// A JSON-RPC response to a request from the server.
+#[derive(Debug, Clone, Serialize)]
+pub struct Response {
+    pub jsonrpc: &'static str,
+    pub id: NumberOrString,
+    /// Always present on success, even if null
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<JsonValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ResponseError>,
+}
+
+impl Response {
+    pub fn ok(id: NumberOrString, result: JsonValue) -> Self {
+        Self {
+            jsonrpc: "2.0",
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    pub fn error(id: NumberOrString, error: ResponseError) -> Self {
+        Self {
+            jsonrpc: "2.0",
+            id,
+            result: None,
+            error: Some(error),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseError {
     pub code: i32,
     pub message: String,
 }
 
+impl ResponseError {
+    pub const INVALID_PARAMS: i32 = -32602;
+    pub const METHOD_NOT_FOUND: i32 = -32601;
+}
+
 impl std::fmt::Display for ResponseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "LSP error {}: {}", self.code, self.message)
@@ -67,7 +113,7 @@ pub struct IncomingMessage {
     #[allow(dead_code)]
     pub jsonrpc: String,
     /// Present for requests and responses, absent for notifications
-    pub id: Option<u64>,
+    pub id: Option<NumberOrString>,
     /// Present for requests and notifications
     pub method: Option<String>,
     /// Present for requests and notifications
@@ -88,6 +134,11 @@ impl IncomingMessage {
     pub fn is_notification(&self) -> bool {
         self.method.is_some() && self.id.is_none()
     }
+
+    /// Check if this is a request from the server (has id and method).
+    pub fn is_request(&self) -> bool {
+        self.id.is_some() && self.method.is_some()
+    }
 }
 
 /// Write an LSP message to the server's stdin.
@@ -112,6 +163,12 @@ pub fn send_notification<W: Write>(
     write_message(writer, &content)
 }
 
+/// Send a response to the server.
+pub fn send_response<W: Write>(writer: &mut W, response: &Response) -> std::io::Result<()> {
+    let content = serde_json::to_vec(response)?;
+    write_message(writer, &content)
+}
+
 /// Read a single LSP message from a buffered reader.
 /// Returns the raw JSON value.
 pub fn read_message<R: BufRead>(reader: &mut R) -> std::io::Result<JsonValue> {
@@ -222,6 +279,10 @@ impl MessageWriter {
     pub fn send_notification(&mut self, notification: &Notification) -> std::io::Result<()> {
         send_notification(&mut self.stdin, notification)
     }
+
+    pub fn send_response(&mut self, response: &Response) -> std::io::Result<()> {
+        send_response(&mut self.stdin, response)
+    }
 }
 
 #[cfg(test)]
@@ -269,11 +330,63 @@ mod tests {
         assert!(!json.contains("\"id\""));
     }
 
+    #[test]
+    fn test_response_serialization() {
+        let ok =
+            serde_json::to_value(Response::ok(NumberOrString::Number(7), JsonValue::Null)).unwrap();
+        assert_eq!(
+            ok,
+            serde_json::json!({"jsonrpc": "2.0", "id": 7, "result": null})
+        );
+
+        let err = Response::error(
+            NumberOrString::String("req-8".to_string()),
+            ResponseError {
+                code: ResponseError::METHOD_NOT_FOUND,
+                message: "Unhandled method test".to_string(),
+            },
+        );
+        let err = serde_json::to_value(err).unwrap();
+        assert_eq!(err["id"], "req-8");
+        assert_eq!(err["error"]["code"], -32601);
+        assert!(err.get("result").is_none());
+    }
+
+    #[test]
+    fn test_incoming_message_is_request() {
+        let msg: IncomingMessage = serde_json::from_value(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "workspace/inlayHint/refresh"
+        }))
+        .unwrap();
+        assert!(msg.is_request());
+        assert!(!msg.is_response());
+        assert!(!msg.is_notification());
+    }
+
+    #[test]
+    fn test_incoming_message_string_id() {
+        let msg: IncomingMessage = serde_json::from_value(serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": "abc-1",
+            "method": "workspace/configuration",
+            "params": {"items": []}
+        }))
+        .unwrap();
+        assert!(msg.is_request());
+        let id = msg.id.unwrap();
+        assert_eq!(id, NumberOrString::String("abc-1".to_string()));
+        // Echoed back as it came
+        let response = serde_json::to_value(Response::ok(id, JsonValue::Null)).unwrap();
+        assert_eq!(response["id"], "abc-1");
+    }
+
     #[test]
     fn test_incoming_message_is_response() {
         let msg = IncomingMessage {
             jsonrpc: "2.0".to_string(),
-            id: Some(1),
+            id: Some(NumberOrString::Number(1)),
             method: None,
             params: None,
             result: Some(serde_json::json!({})),
@@ -37,6 +37,8 @@ pub fn pty_spawn(
     cwd: Option<String>,
     rows: u16,
     cols: u16,
+    pixel_width: Option<u16>,
+    pixel_height: Option<u16>,
 ) -> Result<(), String> {
     let manager = app.state::<PtyManager>();
 
@@ -46,8 +48,8 @@ pub fn pty_spawn(
         .openpty(PortablePtySize {
             rows,
             cols,
-            pixel_width: 0,
-            pixel_height: 0,
+            pixel_width: pixel_width.unwrap_or(0),
+            pixel_height: pixel_height.unwrap_or(0),
         })
         .map_err(|e| e.to_string())?;
 
@@ -124,7 +126,14 @@ pub fn pty_write(app: AppHandle, id: String, data: String) -> Result<(), String>
 }
 
 #[tauri::command]
-pub fn pty_resize(app: AppHandle, id: String, rows: u16, cols: u16) -> Result<(), String> {
+pub fn pty_resize(
+    app: AppHandle,
+    id: String,
+    rows: u16,
+    cols: u16,
+    pixel_width: Option<u16>,
+    pixel_height: Option<u16>,
+) -> Result<(), String> {
     let manager = app.state::<PtyManager>();
     let instances = manager.instances.lock();
 
@@ -137,8 +146,8 @@ pub fn pty_resize(app: AppHandle, id: String, rows: u16, cols: u16) -> Result<()
         .resize(PortablePtySize {
             rows,
             cols,
-            pixel_width: 0,
-            pixel_height: 0,
+            pixel_width: pixel_width.unwrap_or(0),
+            pixel_height: pixel_height.unwrap_or(0),
         })
         .map_err(|e| e.to_string())?;
 
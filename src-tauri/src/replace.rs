@@ -0,0 +1,513 @@
+//! Project-wide search and replace.
+//!
+//! Replacing is two steps: `preview_replace_in_project` computes every
+//! replacement with a before/after preview, then `replace_in_project` applies
+//! the ones the user accepted. Before writing anything, the original contents
+//! are saved to an undo journal on disk so `undo_replace` can revert the whole
+//! replace in one go.
+
+use crate::file::{atomic_write, content_hash, project_walk};
+use crate::scope::PathScope;
+use crate::search::{
+    build_overrides, build_regex, preview_line, read_searchable, utf16_len, SearchOptions,
+};
+use directories::ProjectDirs;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::State;
+
+/// Number of undo journals kept; older ones are deleted
+const MAX_JOURNALS: usize = 20;
+
+/// A single replacement within a file. Lines are 0-indexed; columns are UTF-16 offsets.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplacementPreview {
+    /// Index within the file, used to accept a subset of replacements
+    pub index: usize,
+    pub line: u32,
+    pub column: u32,
+    /// Length of the replaced text in UTF-16 code units
+    pub length: u32,
+    pub new_text: String,
+    /// The line before and after just this replacement
+    pub before: String,
+    pub after: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileReplacePreview {
+    pub path: String,
+    pub relative_path: String,
+    /// Content hash when previewed; passed back when applying to detect changes since
+    pub hash: String,
+    pub replacements: Vec<ReplacementPreview>,
+}
+
+/// A file's accepted replacements, sent back from the frontend.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AcceptedFile {
+    pub path: String,
+    pub hash: String,
+    /// Indices of the accepted replacements; all of them if omitted
+    pub indices: Option<Vec<usize>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplaceResult {
+    /// Pass to `undo_replace` to revert; None if nothing was written
+    pub journal_id: Option<String>,
+    pub replaced_files: Vec<String>,
+    pub replacement_count: usize,
+    /// Files open with unsaved changes in the editor, left untouched
+    pub skipped_dirty: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoResult {
+    pub restored_files: Vec<String>,
+    /// Files changed again since the replace, left as they are
+    pub conflicts: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Journal {
+    id: String,
+    files: Vec<JournalEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JournalEntry {
+    path: String,
+    original: String,
+    /// Hash of the contents we wrote, to tell whether the file was edited after
+    replaced_hash: String,
+}
+
+/// A replacement located in a file's text.
+struct Edit {
+    /// Byte range in the whole text
+    start: usize,
+    end: usize,
+    line: u32,
+    /// Byte offset of the start of the line
+    line_start: usize,
+    /// Byte length of the line, excluding the line ending
+    line_len: usize,
+    new_text: String,
+}
+
+/// Find every replacement in `text`. Matching is per line, as in search.
+fn find_edits(text: &str, regex: &Regex, replacement: &str, expand: bool) -> Vec<Edit> {
+    let mut edits = Vec::new();
+    let mut line_start = 0;
+    for (line_number, raw_line) in text.split_inclusive('\n').enumerate() {
+        let line = raw_line.trim_end_matches('\n').trim_end_matches('\r');
+        for caps in regex.captures_iter(line) {
+            let m = caps.get(0).unwrap();
+            if m.is_empty() {
+                continue;
+            }
+            let new_text = if expand {
+                let mut expanded = String::new();
+                caps.expand(replacement, &mut expanded);
+                expanded
+            } else {
+                replacement.to_string()
+            };
+            edits.push(Edit {
+                start: line_start + m.start(),
+                end: line_start + m.end(),
+                line: line_number as u32,
+                line_start,
+                line_len: line.len(),
+                new_text,
+            });
+        }
+        line_start += raw_line.len();
+    }
+    edits
+}
+
+/// Apply edits (in order, non-overlapping) to `text`.
+fn apply_edits<'a>(text: &str, edits: impl Iterator<Item = &'a Edit>) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut last = 0;
+    for edit in edits {
+        result.push_str(&text[last..edit.start]);
+        result.push_str(&edit.new_text);
+        last = edit.end;
+    }
+    result.push_str(&text[last..]);
+    result
+}
+
+fn preview_edit(text: &str, index: usize, edit: &Edit) -> ReplacementPreview {
+    let line = &text[edit.line_start..edit.line_start + edit.line_len];
+    let start = edit.start - edit.line_start;
+    let end = edit.end - edit.line_start;
+    let after = format!("{}{}{}", &line[..start], edit.new_text, &line[end..]);
+    ReplacementPreview {
+        index,
+        line: edit.line,
+        column: utf16_len(&line[..start]),
+        length: utf16_len(&line[start..end]),
+        new_text: edit.new_text.clone(),
+        before: preview_line(line),
+        after: preview_line(&after),
+    }
+}
+
+/// Read a file for replacing. Binary, very large and non-UTF-8 files are skipped,
+/// since writing them back as text would corrupt them.
+fn read_text(path: &Path) -> Option<String> {
+    String::from_utf8(read_searchable(path)?).ok()
+}
+
+fn journal_dir() -> PathBuf {
+    if let Some(proj_dirs) = ProjectDirs::from("com", "innocencelabs", "raven") {
+        proj_dirs.data_dir().join("replace-journal")
+    } else {
+        std::env::temp_dir().join("raven-replace-journal")
+    }
+}
+
+fn journal_path(id: &str) -> Result<PathBuf, String> {
+    // IDs are generated by us; reject anything that could escape the directory
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("Invalid journal ID: {}", id));
+    }
+    Ok(journal_dir().join(format!("{}.json", id)))
+}
+
+fn write_journal(journal: &Journal) -> Result<(), String> {
+    let dir = journal_dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create journal directory: {}", e))?;
+
+    let json = serde_json::to_vec(journal).map_err(|e| e.to_string())?;
+    atomic_write(&journal_path(&journal.id)?, &json)
+        .map_err(|e| format!("Failed to write undo journal: {}", e))?;
+
+    // Keep only the newest journals (IDs start with a timestamp, so they sort by age)
+    if let Ok(entries) = fs::read_dir(&dir) {
+        let mut journals: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        journals.sort();
+        let excess = journals.len().saturating_sub(MAX_JOURNALS);
+        for old in &journals[..excess] {
+            let _ = fs::remove_file(old);
+        }
+    }
+    Ok(())
+}
+
+/// Compute all replacements in the project without changing anything.
+pub fn preview(
+    root: &str,
+    query: &str,
+    replacement: &str,
+    options: &SearchOptions,
+) -> Result<Vec<FileReplacePreview>, String> {
+    let root_path = Path::new(root);
+    if !root_path.is_dir() {
+        return Err(format!("Directory does not exist: {}", root));
+    }
+    if query.is_empty() {
+        return Err("Search query is empty".to_string());
+    }
+    let regex = build_regex(query, options)?;
+
+    let overrides = build_overrides(root_path, options)?;
+
+    let mut files = Vec::new();
+    for entry in project_walk(root_path).overrides(overrides).build() {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let path = entry.path();
+        let Some(text) = read_text(path) else {
+            continue;
+        };
+
+        let edits = find_edits(&text, &regex, replacement, options.regex);
+        if edits.is_empty() {
+            continue;
+        }
+
+        files.push(FileReplacePreview {
+            path: path.to_string_lossy().to_string(),
+            relative_path: path
+                .strip_prefix(root_path)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .to_string(),
+            hash: content_hash(text.as_bytes()),
+            replacements: edits
+                .iter()
+                .enumerate()
+                .map(|(i, edit)| preview_edit(&text, i, edit))
+                .collect(),
+        });
+    }
+
+    files.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+    Ok(files)
+}
+
+/// Apply accepted replacements. Either every accepted file is written or none is:
+/// if any file changed since the preview nothing is written, and if a write fails
+/// the files already written are restored.
+pub fn apply(
+    query: &str,
+    replacement: &str,
+    options: &SearchOptions,
+    accepted: Vec<AcceptedFile>,
+    dirty_paths: &[String],
+) -> Result<ReplaceResult, String> {
+    let regex = build_regex(query, options)?;
+    let dirty: HashSet<&str> = dirty_paths.iter().map(|p| p.as_str()).collect();
+
+    let mut skipped_dirty = Vec::new();
+    let mut conflicts = Vec::new();
+    let mut planned: Vec<(String, String, String)> = Vec::new(); // (path, original, replaced)
+    let mut replacement_count = 0;
+
+    for file in accepted {
+        if dirty.contains(file.path.as_str()) {
+            skipped_dirty.push(file.path);
+            continue;
+        }
+
+        let original = match read_text(Path::new(&file.path)) {
+            Some(text) if content_hash(text.as_bytes()) == file.hash => text,
+            _ => {
+                conflicts.push(file.path);
+                continue;
+            }
+        };
+
+        let edits = find_edits(&original, &regex, replacement, options.regex);
+        let selected: Vec<&Edit> = match &file.indices {
+            Some(indices) => {
+                let indices: HashSet<usize> = indices.iter().copied().collect();
+                edits
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| indices.contains(i))
+                    .map(|(_, e)| e)
+                    .collect()
+            }
+            None => edits.iter().collect(),
+        };
+        if selected.is_empty() {
+            continue;
+        }
+
+        replacement_count += selected.len();
+        let replaced = apply_edits(&original, selected.into_iter());
+        planned.push((file.path, original, replaced));
+    }
+
+    if !conflicts.is_empty() {
+        return Err(format!(
+            "Files changed since the preview, nothing was replaced: {}",
+            conflicts.join(", ")
+        ));
+    }
+
+    if planned.is_empty() {
+        return Ok(ReplaceResult {
+            journal_id: None,
+            replaced_files: Vec::new(),
+            replacement_count: 0,
+            skipped_dirty,
+        });
+    }
+
+    // Record the originals before touching anything
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let journal = Journal {
+        id: format!("{:013}-{}", millis, uuid::Uuid::new_v4().simple()),
+        files: planned
+            .iter()
+            .map(|(path, original, replaced)| JournalEntry {
+                path: path.clone(),
+                original: original.clone(),
+                replaced_hash: content_hash(replaced.as_bytes()),
+            })
+            .collect(),
+    };
+    write_journal(&journal)?;
+
+    for (i, (path, _, replaced)) in planned.iter().enumerate() {
+        if let Err(e) = atomic_write(Path::new(path), replaced.as_bytes()) {
+            // Roll back the files already written
+            for (written, original, _) in &planned[..i] {
+                let _ = atomic_write(Path::new(written), original.as_bytes());
+            }
+            let _ = fs::remove_file(journal_path(&journal.id)?);
+            return Err(format!("Failed to write file '{}': {}", path, e));
+        }
+    }
+
+    Ok(ReplaceResult {
+        journal_id: Some(journal.id),
+        replaced_files: planned.into_iter().map(|(path, _, _)| path).collect(),
+        replacement_count,
+        skipped_dirty,
+    })
+}
+
+/// Revert a replace from its journal. Files edited since are left alone and reported.
+pub fn undo(journal_id: &str, scope: &PathScope) -> Result<UndoResult, String> {
+    let path = journal_path(journal_id)?;
+    let json = fs::read(&path).map_err(|_| format!("Undo journal not found: {}", journal_id))?;
+    let journal: Journal =
+        serde_json::from_slice(&json).map_err(|e| format!("Invalid undo journal: {}", e))?;
+    // A journal could have been written by anything, so it gets no more trust
+    // than a path from the webview
+    for entry in &journal.files {
+        scope.check(&entry.path)?;
+    }
+
+    let mut restored_files = Vec::new();
+    let mut conflicts = Vec::new();
+    for entry in journal.files {
+        let unchanged = fs::read(&entry.path)
+            .map(|current| content_hash(&current) == entry.replaced_hash)
+            .unwrap_or(false);
+        if !unchanged {
+            conflicts.push(entry.path);
+            continue;
+        }
+        atomic_write(Path::new(&entry.path), entry.original.as_bytes())
+            .map_err(|e| format!("Failed to restore '{}': {}", entry.path, e))?;
+        restored_files.push(entry.path);
+    }
+
+    let _ = fs::remove_file(&path);
+    Ok(UndoResult {
+        restored_files,
+        conflicts,
+    })
+}
+
+// Tauri commands
+
+/// Preview a project-wide replace. `replacement` may use `$1`/`${name}` capture
+/// groups when `options.regex` is set.
+#[tauri::command]
+pub fn preview_replace_in_project(
+    scope: State<'_, PathScope>,
+    root: String,
+    query: String,
+    replacement: String,
+    options: Option<SearchOptions>,
+) -> Result<Vec<FileReplacePreview>, String> {
+    scope.check(&root)?;
+    preview(&root, &query, &replacement, &options.unwrap_or_default())
+}
+
+/// Apply accepted replacements from a preview. Paths in `dirty_paths` (open with
+/// unsaved changes) are skipped and reported rather than overwritten.
+#[tauri::command]
+pub fn replace_in_project(
+    scope: State<'_, PathScope>,
+    query: String,
+    replacement: String,
+    options: Option<SearchOptions>,
+    accepted: Vec<AcceptedFile>,
+    dirty_paths: Option<Vec<String>>,
+) -> Result<ReplaceResult, String> {
+    for file in &accepted {
+        scope.check(&file.path)?;
+    }
+    apply(
+        &query,
+        &replacement,
+        &options.unwrap_or_default(),
+        accepted,
+        &dirty_paths.unwrap_or_default(),
+    )
+}
+
+#[tauri::command]
+pub fn undo_replace(scope: State<'_, PathScope>, journal_id: String) -> Result<UndoResult, String> {
+    undo(&journal_id, &scope)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replace_all(text: &str, pattern: &str, replacement: &str, expand: bool) -> String {
+        let regex = Regex::new(pattern).unwrap();
+        let edits = find_edits(text, &regex, replacement, expand);
+        apply_edits(text, edits.iter())
+    }
+
+    #[test]
+    fn test_find_edits_positions() {
+        let text = "let foo = 1;\r\nfoo(foo);\n";
+        let regex = Regex::new("foo").unwrap();
+        let edits = find_edits(text, &regex, "bar", false);
+        let found: Vec<_> = edits.iter().map(|e| (e.line, e.start, e.end)).collect();
+        assert_eq!(found, vec![(0, 4, 7), (1, 14, 17), (1, 18, 21)]);
+        // Line lengths exclude the CRLF
+        assert_eq!(edits[0].line_len, 12);
+        assert_eq!(edits[1].line_start, 14);
+        assert_eq!(
+            apply_edits(text, edits.iter()),
+            "let bar = 1;\r\nbar(bar);\n"
+        );
+    }
+
+    #[test]
+    fn test_find_edits_expansion() {
+        assert_eq!(
+            replace_all("a=1\nb=2\n", r"(\w)=(\d)", "$2=$1", true),
+            "1=a\n2=b\n"
+        );
+        // Without expansion the replacement is literal
+        assert_eq!(replace_all("a=1\n", r"(\w)=(\d)", "$2", false), "$2\n");
+    }
+
+    #[test]
+    fn test_find_edits_per_line() {
+        // Empty matches are skipped, and matches don't span lines
+        assert_eq!(replace_all("ab\n\ncd\n", "x*", "-", false), "ab\n\ncd\n");
+        assert_eq!(replace_all("a\nb\n", r"a\s+b", "-", false), "a\nb\n");
+        assert_eq!(replace_all("end$\nend", "d$", "D", false), "end$\nenD");
+    }
+
+    #[test]
+    fn test_preview_edit() {
+        let text = "first\nsay héllo world\n";
+        let regex = Regex::new("world").unwrap();
+        let edits = find_edits(text, &regex, "there", false);
+        let preview = preview_edit(text, 0, &edits[0]);
+        assert_eq!(preview.line, 1);
+        // UTF-16 columns: "é" is one unit
+        assert_eq!(preview.column, 10);
+        assert_eq!(preview.length, 5);
+        assert_eq!(preview.before, "say héllo world");
+        assert_eq!(preview.after, "say héllo there");
+    }
+}
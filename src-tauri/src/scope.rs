@@ -0,0 +1,71 @@
+//! Commands that add and remove the directories file commands may touch,
+//! asking the user in native dialogs. See `raven_core::scope`.
+
+use raven_core::scope::canonical_dir;
+pub use raven_core::scope::{OutOfScope, PathScope};
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, State};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+// Tauri commands
+//
+// These are async so the blocking dialogs don't run on the main thread.
+
+/// Let the user pick a project folder in a native dialog and allow access to
+/// it. Returns the folder, or None if the dialog was cancelled.
+#[tauri::command]
+pub async fn pick_project_directory(
+    app: AppHandle,
+    scope: State<'_, PathScope>,
+) -> Result<Option<String>, String> {
+    let Some(picked) = app
+        .dialog()
+        .file()
+        .set_title("Open Project")
+        .blocking_pick_folder()
+    else {
+        return Ok(None);
+    };
+    let path = picked
+        .into_path()
+        .map_err(|e| format!("Failed to open folder: {}", e))?;
+    let root = canonical_dir(&path.to_string_lossy())?;
+    scope.approve(&root)?;
+    Ok(Some(path.to_string_lossy().to_string()))
+}
+
+/// Allow access to a directory, asking the user to confirm in a native dialog
+/// unless they approved it before. Returns whether access was allowed.
+#[tauri::command]
+pub async fn allow_path(
+    app: AppHandle,
+    scope: State<'_, PathScope>,
+    path: String,
+) -> Result<bool, String> {
+    let root = canonical_dir(&path)?;
+    if scope.allow_if_approved(&root) {
+        return Ok(true);
+    }
+    let confirmed = app
+        .dialog()
+        .message(format!(
+            "Allow Raven to read and write files in '{}'?",
+            root.display()
+        ))
+        .title("Allow Access")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancel)
+        .blocking_show();
+    if confirmed {
+        scope.approve(&root)?;
+    }
+    Ok(confirmed)
+}
+
+/// Withdraw access to a directory, e.g. when its project is closed.
+#[tauri::command]
+pub fn revoke_path(scope: State<'_, PathScope>, path: String) -> Result<(), String> {
+    let root = fs::canonicalize(&path).unwrap_or_else(|_| PathBuf::from(&path));
+    scope.revoke(&root)
+}
@@ -0,0 +1,357 @@
+//! Project-wide text search.
+//!
+//! Searches run on a background thread over the same walk as `list_files`,
+//! with files searched in parallel. Matches are streamed to the frontend in
+//! batches as `search:results` events, followed by a single `search:done`.
+
+use crate::file::project_walk;
+use crate::scope::PathScope;
+use ignore::overrides::{Override, OverrideBuilder};
+use ignore::WalkState;
+use parking_lot::Mutex;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// Matches per `search:results` event
+const BATCH_SIZE: usize = 200;
+/// Longest a match waits before its batch is sent anyway
+const BATCH_INTERVAL: Duration = Duration::from_millis(50);
+const DEFAULT_MAX_RESULTS: usize = 10_000;
+/// Files larger than this are skipped
+const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+/// Bytes checked for NUL when deciding whether a file is binary
+const BINARY_CHECK_LEN: usize = 8192;
+/// Preview lines are cut to this many characters
+const MAX_PREVIEW_CHARS: usize = 250;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchOptions {
+    /// Treat the query as a regular expression rather than literal text
+    pub regex: bool,
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    /// Only search files matching one of these globs (relative to the root)
+    pub include: Vec<String>,
+    /// Skip files matching any of these globs
+    pub exclude: Vec<String>,
+    pub max_results: Option<usize>,
+}
+
+/// A single match. Lines are 0-indexed; columns are UTF-16 offsets like the editor's.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchMatch {
+    pub path: String,
+    pub relative_path: String,
+    pub line: u32,
+    pub column: u32,
+    /// Length of the match in UTF-16 code units
+    pub length: u32,
+    /// The matching line (cut down if very long)
+    pub preview: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchResultsEvent {
+    search_id: String,
+    matches: Vec<SearchMatch>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchDoneEvent {
+    search_id: String,
+    total_matches: usize,
+    /// Stopped at `max_results`
+    truncated: bool,
+    cancelled: bool,
+}
+
+/// Cancellation flags for running searches by search ID.
+pub struct SearchManager {
+    searches: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl SearchManager {
+    pub fn new() -> Self {
+        Self {
+            searches: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Start a search in the background. Fails immediately on an invalid pattern or glob.
+    pub fn start(
+        &self,
+        search_id: String,
+        root: String,
+        query: &str,
+        options: SearchOptions,
+        app: AppHandle,
+    ) -> Result<(), String> {
+        let root_path = Path::new(&root);
+        if !root_path.is_dir() {
+            return Err(format!("Directory does not exist: {}", root));
+        }
+        if query.is_empty() {
+            return Err("Search query is empty".to_string());
+        }
+
+        let regex = build_regex(query, &options)?;
+
+        let overrides = build_overrides(root_path, &options)?;
+
+        let cancelled = Arc::new(AtomicBool::new(false));
+        {
+            let mut searches = self.searches.lock();
+            // Starting a search with a running search's ID replaces it
+            if let Some(old) = searches.insert(search_id.clone(), cancelled.clone()) {
+                old.store(true, Ordering::SeqCst);
+            }
+        }
+
+        let max_results = options.max_results.unwrap_or(DEFAULT_MAX_RESULTS);
+        let (sender, receiver) = mpsc::channel::<Vec<SearchMatch>>();
+
+        // Walk and search on worker threads
+        let walker = project_walk(root_path)
+            .overrides(overrides)
+            .build_parallel();
+        let found = Arc::new(AtomicUsize::new(0));
+        let worker_found = found.clone();
+        let worker_cancelled = cancelled.clone();
+        thread::spawn(move || {
+            walker.run(|| {
+                let sender = sender.clone();
+                let regex = regex.clone();
+                let root = root.clone();
+                let found = worker_found.clone();
+                let cancelled = worker_cancelled.clone();
+                Box::new(move |entry| {
+                    if cancelled.load(Ordering::Relaxed)
+                        || found.load(Ordering::Relaxed) >= max_results
+                    {
+                        return WalkState::Quit;
+                    }
+                    let Ok(entry) = entry else {
+                        return WalkState::Continue;
+                    };
+                    if !entry.file_type().is_some_and(|t| t.is_file()) {
+                        return WalkState::Continue;
+                    }
+
+                    let matches = search_file(entry.path(), Path::new(&root), &regex);
+                    if !matches.is_empty() {
+                        found.fetch_add(matches.len(), Ordering::Relaxed);
+                        if sender.send(matches).is_err() {
+                            return WalkState::Quit;
+                        }
+                    }
+                    WalkState::Continue
+                })
+            });
+        });
+
+        // Batch results and emit them until the walk finishes
+        thread::spawn(move || {
+            let mut batch: Vec<SearchMatch> = Vec::new();
+            let mut total = 0;
+            let mut last_emit = Instant::now();
+
+            let emit = |batch: &mut Vec<SearchMatch>| {
+                if !batch.is_empty() {
+                    let _ = app.emit(
+                        "search:results",
+                        SearchResultsEvent {
+                            search_id: search_id.clone(),
+                            matches: std::mem::take(batch),
+                        },
+                    );
+                }
+            };
+
+            while !cancelled.load(Ordering::SeqCst) {
+                match receiver.recv_timeout(BATCH_INTERVAL) {
+                    Ok(matches) => {
+                        let remaining = max_results - total;
+                        total += matches.len().min(remaining);
+                        batch.extend(matches.into_iter().take(remaining));
+                    }
+                    Err(mpsc::RecvTimeoutError::Timeout) => {}
+                    Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                }
+
+                if batch.len() >= BATCH_SIZE || last_emit.elapsed() >= BATCH_INTERVAL {
+                    emit(&mut batch);
+                    last_emit = Instant::now();
+                }
+                if total >= max_results {
+                    break;
+                }
+            }
+
+            let was_cancelled = cancelled.load(Ordering::SeqCst);
+            if !was_cancelled {
+                emit(&mut batch);
+            }
+            let _ = app.emit(
+                "search:done",
+                SearchDoneEvent {
+                    search_id: search_id.clone(),
+                    total_matches: total,
+                    truncated: total >= max_results,
+                    cancelled: was_cancelled,
+                },
+            );
+
+            // Stop the workers if we finished early
+            cancelled.store(true, Ordering::SeqCst);
+            app.state::<SearchManager>().finish(&search_id, &cancelled);
+        });
+
+        Ok(())
+    }
+
+    /// Cancel a running search. Unknown IDs are ignored (the search may have just finished).
+    pub fn cancel(&self, search_id: &str) {
+        if let Some(cancelled) = self.searches.lock().remove(search_id) {
+            cancelled.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Forget a finished search, unless its ID has been reused by a newer one.
+    fn finish(&self, search_id: &str, cancelled: &Arc<AtomicBool>) {
+        let mut searches = self.searches.lock();
+        if searches
+            .get(search_id)
+            .is_some_and(|c| Arc::ptr_eq(c, cancelled))
+        {
+            searches.remove(search_id);
+        }
+    }
+}
+
+pub fn build_regex(query: &str, options: &SearchOptions) -> Result<Regex, String> {
+    let pattern = if options.regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    let pattern = if options.whole_word {
+        format!(r"\b(?:{})\b", pattern)
+    } else {
+        pattern
+    };
+
+    RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive)
+        .build()
+        .map_err(|e| format!("Invalid search pattern: {}", e))
+}
+
+/// Include/exclude globs from the options as walker overrides.
+pub fn build_overrides(root: &Path, options: &SearchOptions) -> Result<Override, String> {
+    let mut overrides = OverrideBuilder::new(root);
+    for glob in &options.include {
+        overrides
+            .add(glob)
+            .map_err(|e| format!("Invalid include glob '{}': {}", glob, e))?;
+    }
+    for glob in &options.exclude {
+        overrides
+            .add(&format!("!{}", glob))
+            .map_err(|e| format!("Invalid exclude glob '{}': {}", glob, e))?;
+    }
+    overrides
+        .build()
+        .map_err(|e| format!("Invalid globs: {}", e))
+}
+
+/// Read a file's contents unless it's binary or too large to be worth searching.
+pub fn read_searchable(path: &Path) -> Option<Vec<u8>> {
+    let too_large = fs::metadata(path).map_or(true, |m| m.len() > MAX_FILE_SIZE);
+    if too_large {
+        return None;
+    }
+    let bytes = fs::read(path).ok()?;
+    if bytes[..bytes.len().min(BINARY_CHECK_LEN)].contains(&0) {
+        return None;
+    }
+    Some(bytes)
+}
+
+/// Search one file, skipping binary and very large files.
+fn search_file(path: &Path, root: &Path, regex: &Regex) -> Vec<SearchMatch> {
+    let Some(bytes) = read_searchable(path) else {
+        return Vec::new();
+    };
+
+    let text = String::from_utf8_lossy(&bytes);
+    let path_str = path.to_string_lossy().to_string();
+    let relative_path = path
+        .strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string();
+
+    let mut matches = Vec::new();
+    for (line_number, line) in text.lines().enumerate() {
+        for m in regex.find_iter(line) {
+            if m.is_empty() {
+                continue;
+            }
+            matches.push(SearchMatch {
+                path: path_str.clone(),
+                relative_path: relative_path.clone(),
+                line: line_number as u32,
+                column: utf16_len(&line[..m.start()]),
+                length: utf16_len(m.as_str()),
+                preview: preview_line(line),
+            });
+        }
+    }
+    matches
+}
+
+pub fn utf16_len(s: &str) -> u32 {
+    s.chars().map(|c| c.len_utf16() as u32).sum()
+}
+
+pub fn preview_line(line: &str) -> String {
+    match line.char_indices().nth(MAX_PREVIEW_CHARS) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line.to_string(),
+    }
+}
+
+// Tauri commands
+
+/// Search the project, streaming `search:results` events tagged with `search_id`
+/// and finishing with `search:done`.
+#[tauri::command]
+pub fn search_project(
+    app: AppHandle,
+    state: State<'_, SearchManager>,
+    scope: State<'_, PathScope>,
+    search_id: String,
+    root: String,
+    query: String,
+    options: Option<SearchOptions>,
+) -> Result<(), String> {
+    scope.check(&root)?;
+    state.start(search_id, root, &query, options.unwrap_or_default(), app)
+}
+
+#[tauri::command]
+pub fn cancel_search(state: State<'_, SearchManager>, search_id: String) {
+    state.cancel(&search_id)
+}
@@ -0,0 +1,197 @@
+//! Project filesystem watcher.
+//!
+//! Watches the directories of a project that `list_files` would enter, so
+//! ignored trees like `target/` cost no watches, drops paths it would skip
+//! (hidden or gitignored), debounces bursts of changes and emits them as a
+//! single `fs:changed` event. Changes are also forwarded to any running
+//! language servers as `workspace/didChangeWatchedFiles`.
+
+use crate::file_index::FileIndexManager;
+use crate::lsp::LspManager;
+use crate::scope::PathScope;
+use notify_debouncer_full::notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_full::{new_debouncer, DebounceEventResult, Debouncer, RecommendedCache};
+use parking_lot::Mutex;
+use raven_core::watcher::{collect_changes, lsp_changes, project_dirs, IgnoreFilter};
+pub use raven_core::watcher::{FsChange, FsChangeKind};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// How long the filesystem must be quiet before a batch of changes is emitted
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Payload of the `fs:changed` event.
+#[derive(Debug, Clone, Serialize)]
+pub struct FsChangedEvent {
+    pub root: String,
+    pub changes: Vec<FsChange>,
+}
+
+/// A watched project, with a non-recursive watch on each of its directories.
+struct ProjectWatcher {
+    debouncer: Debouncer<RecommendedWatcher, RecommendedCache>,
+    dirs: HashSet<PathBuf>,
+}
+
+/// Active watchers by project root.
+pub struct WatcherManager {
+    watchers: Mutex<HashMap<String, ProjectWatcher>>,
+}
+
+impl WatcherManager {
+    pub fn new() -> Self {
+        Self {
+            watchers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Start watching a project. Does nothing if it's already watched.
+    pub fn watch(&self, root: &str, app: AppHandle) -> Result<(), String> {
+        let mut watchers = self.watchers.lock();
+        if watchers.contains_key(root) {
+            return Ok(());
+        }
+
+        let root_path = PathBuf::from(root);
+        if !root_path.is_dir() {
+            return Err(format!("Directory does not exist: {}", root));
+        }
+
+        let root_name = root.to_string();
+        let watch_root = root_path.clone();
+        let mut filter = IgnoreFilter::new(&root_path);
+        let mut debouncer = new_debouncer(DEBOUNCE, None, move |result: DebounceEventResult| {
+            let Ok(events) = result else {
+                // Changes may have been missed, so the index can't be trusted
+                app.state::<FileIndexManager>().invalidate(&root_name);
+                return;
+            };
+
+            let touched_ignore_file = events.iter().any(|e| {
+                e.paths
+                    .iter()
+                    .any(|p| p.file_name().is_some_and(|n| n == ".gitignore"))
+            });
+            let watcher = app.state::<WatcherManager>();
+            if touched_ignore_file {
+                filter = IgnoreFilter::new(&watch_root);
+                watcher.sync_dirs(&root_name, &watch_root);
+            }
+
+            let changes = collect_changes(events.iter().map(|e| (&e.kind, &e.paths[..])), &filter);
+            if changes.is_empty() {
+                return;
+            }
+
+            // Watch directories that appeared and drop watches on ones that went
+            for change in &changes {
+                let path = Path::new(&change.path);
+                if change.kind != FsChangeKind::Removed && path.is_dir() {
+                    watcher.sync_dirs(&root_name, path);
+                }
+                match (&change.kind, &change.old_path) {
+                    (FsChangeKind::Removed, _) => watcher.sync_dirs(&root_name, path),
+                    (FsChangeKind::Renamed, Some(old_path)) => {
+                        watcher.sync_dirs(&root_name, Path::new(old_path))
+                    }
+                    _ => {}
+                }
+            }
+
+            app.state::<FileIndexManager>()
+                .apply_changes(&root_name, &changes);
+            let (created, changed, deleted) = lsp_changes(&changes);
+            app.state::<LspManager>()
+                .did_change_watched_files(&created, &changed, &deleted);
+            let _ = app.emit(
+                "fs:changed",
+                FsChangedEvent {
+                    root: root_name.clone(),
+                    changes,
+                },
+            );
+        })
+        .map_err(|e| format!("Failed to create watcher: {}", e))?;
+
+        debouncer
+            .watch(&root_path, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch '{}': {}", root, e))?;
+        let mut dirs = HashSet::from([root_path.clone()]);
+        for dir in project_dirs(&root_path) {
+            // Directories that can't be watched (e.g. unreadable) are skipped
+            if !dirs.contains(&dir) && debouncer.watch(&dir, RecursiveMode::NonRecursive).is_ok() {
+                dirs.insert(dir);
+            }
+        }
+
+        watchers.insert(root.to_string(), ProjectWatcher { debouncer, dirs });
+        Ok(())
+    }
+
+    /// Bring the watches on `dir` and the directories under it in line with
+    /// what's there now: new directories are watched, and watches on
+    /// removed or newly ignored ones are dropped.
+    fn sync_dirs(&self, root: &str, dir: &Path) {
+        let current: HashSet<PathBuf> = project_dirs(dir).into_iter().collect();
+        let mut watchers = self.watchers.lock();
+        let Some(project) = watchers.get_mut(root) else {
+            return;
+        };
+
+        let stale: Vec<PathBuf> = project
+            .dirs
+            .iter()
+            .filter(|d| d.starts_with(dir) && !current.contains(*d))
+            .cloned()
+            .collect();
+        for stale_dir in stale {
+            // Fails if the directory is already gone, which drops the watch anyway
+            let _ = project.debouncer.unwatch(&stale_dir);
+            project.dirs.remove(&stale_dir);
+        }
+        for new_dir in current {
+            if !project.dirs.contains(&new_dir)
+                && project
+                    .debouncer
+                    .watch(&new_dir, RecursiveMode::NonRecursive)
+                    .is_ok()
+            {
+                project.dirs.insert(new_dir);
+            }
+        }
+    }
+
+    /// Stop watching a project.
+    pub fn unwatch(&self, root: &str) {
+        // Dropping the debouncer stops its watcher and thread
+        self.watchers.lock().remove(root);
+    }
+}
+
+// Tauri commands
+
+/// Start watching a project and emitting `fs:changed` events for it.
+#[tauri::command]
+pub fn watch_project(
+    app: AppHandle,
+    state: State<'_, WatcherManager>,
+    scope: State<'_, PathScope>,
+    root: String,
+) -> Result<(), String> {
+    scope.check(&root)?;
+    state.watch(&root, app)
+}
+
+/// Stop watching a project. Its file index is dropped too, as nothing keeps it current.
+#[tauri::command]
+pub fn unwatch_project(
+    state: State<'_, WatcherManager>,
+    index: State<'_, FileIndexManager>,
+    root: String,
+) {
+    state.unwatch(&root);
+    index.invalidate(&root);
+}
@@ -1,5 +1,5 @@
 import { Show, onMount, createSignal } from "solid-js";
-import { open } from "@tauri-apps/plugin-dialog";
+import { invoke } from "@tauri-apps/api/core";
 import { WindowChrome } from "./components/WindowChrome";
 import { Workspace } from "./components/Workspace";
 import { TopBar } from "./components/TopBar";
@@ -14,13 +14,9 @@ function App() {
     setLoading(false);
     
     if (projectState.all.length === 0) {
-      const selected = await open({
-        directory: true,
-        multiple: false,
-        title: "Open Project",
-      });
+      const selected = await invoke<string | null>("pick_project_directory");
       
-      if (selected && typeof selected === "string") {
+      if (selected) {
         await addProject(selected);
       }
     }
@@ -51,13 +47,9 @@ function App() {
 
 function ProjectPicker() {
   const handleOpen = async () => {
-    const selected = await open({
-      directory: true,
-      multiple: false,
-      title: "Open Project",
-    });
+    const selected = await invoke<string | null>("pick_project_directory");
     
-    if (selected && typeof selected === "string") {
+    if (selected) {
       addProject(selected);
     }
   };
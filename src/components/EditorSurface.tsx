@@ -3,21 +3,52 @@ import { invoke } from "@tauri-apps/api/core";
 import { readText, writeText } from "@tauri-apps/plugin-clipboard-manager";
 import { editorStore, getEditorState, updateEditorState, initializeEditor, setEditorState } from "../store/editor";
 import { getLine, getLineCount, getText, insertText as bufferInsertText, deleteCharBefore, Position, comparePositions, computeSmartIndent, getLineIndent } from "../editor/buffer";
-import { executeCommand, markExtendedDirty, markExtendedClean, clampExtendedCursor } from "../editor/commands";
+import { executeCommand, markExtendedDirty, markExtendedClean, clampExtendedCursor, INDENT_SIZE } from "../editor/commands";
 import { parseInput, createVimState, findAllMatches } from "../editor/vim";
 import { pushHistory } from "../editor/history";
-import { getHighlighter, type HighlightResult, type LanguageId } from "../editor/highlighting";
+import { getHighlighter, applySemanticTokens, highlightClass, type HighlightResult, type LanguageId } from "../editor/highlighting";
 
-import { startServerForFile, openDocument, changeDocument, closeDocument, getDiagnostics, gotoDefinition, hover, complete, references, codeActions, resolveCodeAction, lspStore, type ServerState } from "../store/lsp";
-import { setSurfaceType } from "../store/surface";
-import type { Diagnostic, CompletionItem, CodeAction, TextEdit, WorkspaceEdit } from "../lsp/types";
-import { uriToPath } from "../lsp/types";
+import { startServerForFile, openDocument, changeDocument, closeDocument, getDiagnostics, gotoDefinition, hover, complete, references, codeActions, resolveCodeAction, formatDocument, formatRange, willSave, signatureHelp, inlayHints, resolveInlayHint, semanticTokens, registerBufferEditor, lspStore, type ServerState } from "../store/lsp";
+import { setSurfaceType, closeSurface } from "../store/surface";
+import type { Diagnostic, CompletionItem, CodeAction, TextEdit, WorkspaceEdit, Position as LspPosition, FormattingOptions, SignatureHelp, SignatureInfo, InlayHint, SemanticToken } from "../lsp/types";
+import { uriToPath, pathToUri, SignatureHelpTriggerKind } from "../lsp/types";
 import { renderMarkdown } from "../utils/markdown";
 import { getCompletionIcon, IconMacro, IconLightbulb, IconQuickfix, IconRefactor, IconExtract, IconSource } from "./icons";
 import { CursorPopup } from "./CursorPopup";
 import "./EditorSurface.css";
 import "./icons/icons.css";
 
+interface FileVersion {
+  mtime: number;
+  hash: string;
+}
+
+/** Decoded file text (with \n line endings) and how the file is stored on disk */
+type FileContents =
+  | { kind: "text"; content: string; encoding: string; lineEnding: "lf" | "crlf"; bom: boolean }
+  | { kind: "binary"; size: number; mtime: number }
+  | { kind: "large"; size: number; mtime: number };
+
+function formatSize(bytes: number): string {
+  if (bytes < 1024) return `${bytes} B`;
+  if (bytes < 1024 * 1024) return `${(bytes / 1024).toFixed(1)} KiB`;
+  return `${(bytes / (1024 * 1024)).toFixed(1)} MiB`;
+}
+
+/** A buffer position for an LSP one, for comparing edit ranges */
+function toPosition(position: LspPosition): Position {
+  return { line: position.line, column: position.character };
+}
+
+/** Outside the projects' allowed roots, or any other failure */
+type FileError =
+  | { kind: "permissionDenied"; path: string; message: string }
+  | { kind: "failed"; message: string };
+
+type WriteFileError =
+  | { kind: "conflict"; path: string; diskContent: string; diskVersion: FileVersion }
+  | FileError;
+
 interface Props {
   id: string;
   focused: boolean;
@@ -36,6 +67,9 @@ export function EditorSurface(props: Props) {
   const [error, setError] = createSignal<string | null>(null);
   const [loadedFilePath, setLoadedFilePath] = createSignal<string | null>(null);
   
+  // Version of the file on disk when it was loaded or last saved, to detect outside changes
+  let diskVersion: FileVersion | null = null;
+  
   // Pending vim input for multi-key sequences
   const [pendingInput, setPendingInput] = createSignal("");
   
@@ -58,6 +92,10 @@ export function EditorSurface(props: Props) {
   const [hoverFlipped, setHoverFlipped] = createSignal(false);
   const [hoverAnchorCursor, setHoverAnchorCursor] = createSignal<{ line: number; column: number } | null>(null);
   
+  // Signature help state
+  const [signatures, setSignatures] = createSignal<SignatureHelp | null>(null);
+  const [signaturePosition, setSignaturePosition] = createSignal<{ x: number; anchorTop: number; anchorBottom: number } | null>(null);
+
   // Completion menu state
   const [completionItems, setCompletionItems] = createSignal<CompletionItem[]>([]);
   const [completionIndex, setCompletionIndex] = createSignal(0);
@@ -89,20 +127,16 @@ export function EditorSurface(props: Props) {
     const rootPath = lspRootPath();
     if (!rootPath) return { state: "stopped", label: "" };
     
-    // Only show for Rust files
-    const filePath = props.filePath;
-    if (!filePath?.endsWith(".rs")) return { state: "stopped", label: "" };
-    
     const status = lspStore.servers[rootPath];
     const state = status?.state ?? "stopped";
     
     switch (state) {
       case "starting":
-        return { state, label: "rust-analyzer starting..." };
+        return { state, label: "language server starting..." };
       case "running":
-        return { state, label: "rust-analyzer" };
+        return { state, label: "language server" };
       case "error":
-        return { state, label: `rust-analyzer error: ${status?.error ?? "unknown"}` };
+        return { state, label: `language server error: ${status?.error ?? "unknown"}` };
       default:
         return { state: "stopped", label: "" };
     }
@@ -155,11 +189,21 @@ export function EditorSurface(props: Props) {
       setLoading(true);
       setError(null);
       try {
-        const content = await invoke<string>("read_file", { path: filePath });
+        const file = await invoke<FileContents>("read_file", { path: filePath });
+        if (file.kind !== "text") {
+          const what = file.kind === "binary" ? "binary file" : "file too large to edit";
+          setError(`Can't open ${what} (${formatSize(file.size)})`);
+          initializeEditor(props.id, "");
+          return;
+        }
+        const content = file.content;
+        diskVersion = await invoke<FileVersion>("file_version", { path: filePath });
         initializeEditor(props.id, content);
         setLoadedFilePath(filePath);
+        // Rank recently opened files higher in the file finder
+        invoke("record_file_open", { path: filePath }).catch(() => {});
       } catch (e) {
-        setError(`Failed to load file: ${e}`);
+        setError(`Failed to load file: ${(e as FileError).message ?? e}`);
         initializeEditor(props.id, "");
       } finally {
         setLoading(false);
@@ -205,6 +249,9 @@ export function EditorSurface(props: Props) {
     });
   });
   
+  // LSP: Semantic tokens and the document text they were computed for
+  const [documentSemanticTokens, setDocumentSemanticTokens] = createSignal<{ content: string; tokens: SemanticToken[] } | null>(null);
+
   // Parse and highlight when content changes (synchronous, no async issues)
   createEffect(() => {
     const langId = loadedLangId();
@@ -223,10 +270,15 @@ export function EditorSurface(props: Props) {
     
     const highlighter = getHighlighter();
     const newTokens = highlighter.parseFile(filePath, langId, content);
-    setHighlightTokens(newTokens);
+
+    // Semantic tokens only apply to the text they were computed for; until
+    // they catch up with an edit, Tree-sitter highlighting shows alone
+    const semantic = documentSemanticTokens();
+    setHighlightTokens(semantic?.content === content ? applySemanticTokens(newTokens, semantic.tokens) : newTokens);
   });
 
-  // LSP: Start server and open document when file loads (only for Rust files)
+  // LSP: Start servers and open document when file loads. Files no configured
+  // server handles have no LSP root.
   // Combined into single effect to avoid race condition between server start and document open
   createEffect(() => {
     const filePath = props.filePath;
@@ -234,12 +286,6 @@ export function EditorSurface(props: Props) {
     
     if (!filePath) return;
     
-    // Only start LSP for Rust files
-    if (!filePath.endsWith(".rs")) {
-      setLspRootPath(null);
-      return;
-    }
-    
     // Wait until file is loaded
     if (loaded !== filePath) {
       return;
@@ -258,7 +304,10 @@ export function EditorSurface(props: Props) {
           return;
         }
         setLspRootPath(rootPath);
-        return openDocument(rootPath, filePath, content);
+        return openDocument(rootPath, filePath, content).then(() => {
+          refreshInlayHints(rootPath, filePath);
+          refreshSemanticTokens(rootPath, filePath, content);
+        });
       })
       .catch((e: unknown) => {
         console.error("LSP: Failed to start/open:", e);
@@ -275,7 +324,6 @@ export function EditorSurface(props: Props) {
     const state = editorStore.editors[props.id];
     
     if (!rootPath || !filePath || !state) return;
-    if (!filePath.endsWith(".rs")) return;
     
     // Track buffer changes by accessing the buffer
     const content = getText(state.buffer);
@@ -286,12 +334,84 @@ export function EditorSurface(props: Props) {
     }
     
     lspChangeTimeout = setTimeout(() => {
-      changeDocument(rootPath, filePath, content).catch((e) => {
-        console.warn("LSP: Failed to send document change:", e);
-      });
+      changeDocument(rootPath, filePath, content)
+        .then(() => {
+          refreshInlayHints(rootPath, filePath);
+          refreshSemanticTokens(rootPath, filePath, content);
+        })
+        .catch((e) => {
+          console.warn("LSP: Failed to send document change:", e);
+        });
     }, LSP_CHANGE_DEBOUNCE_MS);
   });
 
+  // LSP: Inlay hints for the whole document, requested again after changes
+  // are sent and when the server asks for a refresh
+  const [documentInlayHints, setDocumentInlayHints] = createSignal<InlayHint[]>([]);
+
+  const refreshInlayHints = async (rootPath: string, filePath: string) => {
+    const lineCount = getLineCount(getEditorState(props.id).buffer);
+    const hints = await inlayHints(rootPath, filePath, {
+      start: { line: 0, character: 0 },
+      end: { line: lineCount, character: 0 },
+    });
+    if (lspRootPath() === rootPath && props.filePath === filePath) {
+      setDocumentInlayHints(hints);
+    }
+  };
+
+  createEffect(() => {
+    const rootPath = lspRootPath();
+    const filePath = props.filePath;
+    if (!rootPath || !filePath) return;
+    if (lspStore.inlayHintsRefresh[rootPath]) {
+      refreshInlayHints(rootPath, filePath);
+    }
+  });
+
+  // Request semantic tokens for the text last sent to the server
+  const refreshSemanticTokens = async (rootPath: string, filePath: string, content: string) => {
+    const tokens = await semanticTokens(rootPath, filePath);
+    if (lspRootPath() === rootPath && props.filePath === filePath) {
+      setDocumentSemanticTokens({ content, tokens });
+    }
+  };
+
+  createEffect(() => {
+    const rootPath = lspRootPath();
+    const filePath = props.filePath;
+    if (!rootPath || !filePath) return;
+    if (lspStore.semanticTokensRefresh[rootPath]) {
+      flushLspChanges(rootPath, filePath).then(() =>
+        refreshSemanticTokens(rootPath, filePath, getText(getEditorState(props.id).buffer))
+      );
+    }
+  });
+
+  // Fill in a hint's tooltip the first time it's pointed at
+  const handleInlayHintHover = async (hint: InlayHint) => {
+    const rootPath = lspRootPath();
+    const filePath = props.filePath;
+    if (!hint.needsResolve || !rootPath || !filePath) return;
+
+    const resolved = await resolveInlayHint(rootPath, filePath, hint);
+    setDocumentInlayHints((hints) => hints.map((h) => (h === hint ? { ...resolved, needsResolve: false } : h)));
+  };
+
+  // Send any pending document change now, before a request that needs the latest text
+  const flushLspChanges = async (rootPath: string, filePath: string) => {
+    if (lspChangeTimeout) {
+      clearTimeout(lspChangeTimeout);
+      lspChangeTimeout = null;
+    }
+    await changeDocument(rootPath, filePath, getText(getEditorState(props.id).buffer));
+  };
+
+  const formattingOptions = (): FormattingOptions => ({
+    tabSize: INDENT_SIZE,
+    insertSpaces: true,
+  });
+
   // LSP: Close document on cleanup
   onCleanup(() => {
     if (lspChangeTimeout) {
@@ -301,7 +421,7 @@ export function EditorSurface(props: Props) {
     const rootPath = lspRootPath();
     const filePath = props.filePath;
     
-    if (rootPath && filePath && filePath.endsWith(".rs")) {
+    if (rootPath && filePath) {
       closeDocument(rootPath, filePath).catch((e) => {
         console.warn("LSP: Failed to close document:", e);
       });
@@ -369,7 +489,6 @@ export function EditorSurface(props: Props) {
     setCodeActionIndicatorPosition(null);
     
     if (!rootPath || !filePath || !s) return;
-    if (!filePath.endsWith(".rs")) return;
     if (s.mode !== "normal") return; // Only show in normal mode
     
     const { line, column } = s.cursor;
@@ -489,14 +608,41 @@ export function EditorSurface(props: Props) {
       return;
     }
 
+    // Don't overwrite a file that couldn't be loaded (e.g. a binary) with the empty buffer
+    if (loadedFilePath() !== filePath) {
+      showToast("This file couldn't be opened, so it can't be saved.", 3000);
+      return;
+    }
+
+    // Let language servers adjust the document first, e.g. format on save
+    const rootPath = lspRootPath();
+    if (rootPath) {
+      await flushLspChanges(rootPath, filePath);
+      const edits = await willSave(rootPath, filePath, formattingOptions());
+      if (edits.length > 0) {
+        applyTextEdits(edits);
+      }
+    }
+
     const state = getEditorState(props.id);
     const content = getText(state.buffer);
 
     try {
-      await invoke("write_file", { path: filePath, content });
+      diskVersion = await invoke<FileVersion>("save_file", {
+        path: filePath,
+        content,
+        expectedHash: diskVersion?.hash,
+      });
       updateEditorState(props.id, (s) => markExtendedClean(s));
     } catch (e) {
-      setError(`Failed to save: ${e}`);
+      const err = e as WriteFileError;
+      if (err.kind === "conflict") {
+        // Saving again overwrites the newer version on disk
+        diskVersion = err.diskVersion;
+        showToast("File changed on disk since it was opened. Save again to overwrite it.", 4000);
+      } else {
+        setError(`Failed to save: ${err.message ?? e}`);
+      }
     }
   };
 
@@ -584,6 +730,57 @@ export function EditorSurface(props: Props) {
     setHoverAnchorCursor(null);
   };
 
+  // Show signature help for the call at the cursor. A typed `triggerCharacter`
+  // the server doesn't trigger on leaves the popup as it was, unless it's open.
+  const handleSignatureHelp = async (triggerCharacter?: string) => {
+    const rootPath = lspRootPath();
+    const filePath = props.filePath;
+
+    if (!rootPath || !filePath) return;
+
+    const showing = signatures();
+    await flushLspChanges(rootPath, filePath);
+    const { line, column } = getEditorState(props.id).cursor;
+    const result = await signatureHelp(rootPath, filePath, line, column, {
+      triggerKind: triggerCharacter
+        ? SignatureHelpTriggerKind.TriggerCharacter
+        : showing
+          ? SignatureHelpTriggerKind.ContentChange
+          : SignatureHelpTriggerKind.Invoked,
+      triggerCharacter,
+      isRetrigger: showing !== null,
+      activeSignature: showing?.activeSignature,
+    });
+
+    if (!result) {
+      if (showing) closeSignatureHelp();
+      return;
+    }
+
+    const lineEl = lineRefs.get(line);
+    if (lineEl) {
+      const rect = lineEl.getBoundingClientRect();
+      const x = rect.left + column * 7.8; // approximate char width
+      setSignaturePosition({ x, anchorTop: rect.top, anchorBottom: rect.bottom });
+    }
+    setSignatures(result);
+  };
+
+  const closeSignatureHelp = () => {
+    setSignatures(null);
+    setSignaturePosition(null);
+  };
+
+  // Split a signature label around its active parameter for highlighting
+  const signatureLabelParts = (signature: SignatureInfo): [string, string, string] => {
+    const active = signature.parameters.find((p) => p.active);
+    if (!active?.labelOffsets) {
+      return [signature.label, "", ""];
+    }
+    const [start, end] = active.labelOffsets;
+    return [signature.label.slice(0, start), signature.label.slice(start, end), signature.label.slice(end)];
+  };
+
   // Get completion context - the word prefix and whether we're after a trigger char
   const getCompletionContext = (): { prefix: string; startColumn: number; afterTrigger: boolean } => {
     const state = getEditorState(props.id);
@@ -1057,48 +1254,96 @@ export function EditorSurface(props: Props) {
     }
   };
 
+  // Format the visual selection, or the whole document
+  const handleFormat = async () => {
+    const rootPath = lspRootPath();
+    const filePath = props.filePath;
+
+    if (!rootPath || !filePath) return;
+
+    await flushLspChanges(rootPath, filePath);
+    const s = getEditorState(props.id);
+    let edits: TextEdit[];
+    if (s.visualAnchor && s.visualMode) {
+      const start = comparePositions(s.visualAnchor, s.cursor) < 0 ? s.visualAnchor : s.cursor;
+      const end = comparePositions(s.visualAnchor, s.cursor) < 0 ? s.cursor : s.visualAnchor;
+      const range = s.visualMode === "line"
+        ? { start: { line: start.line, character: 0 }, end: { line: end.line + 1, character: 0 } }
+        : { start: { line: start.line, character: start.column }, end: { line: end.line, character: end.column + 1 } };
+      edits = await formatRange(rootPath, filePath, range, formattingOptions());
+      updateEditorState(props.id, (state) => ({ ...state, visualAnchor: null, visualMode: null }));
+    } else {
+      edits = await formatDocument(rootPath, filePath, formattingOptions());
+    }
+
+    if (edits.length > 0) {
+      applyTextEdits(edits);
+    }
+  };
+
   const closeCodeActions = () => {
     setCodeActionItems([]);
     setCodeActionIndex(0);
   };
 
-  // Apply a workspace edit to the buffer
+  // Apply text edits for this file to the buffer
+  const applyTextEdits = (edits: TextEdit[]) => {
+    if (!props.filePath) return;
+    applyWorkspaceEdit({ changes: { [pathToUri(props.filePath)]: edits } });
+  };
+
+  // Apply a workspace edit to the buffer. Each change to this file is
+  // applied in turn, as later changes' positions account for earlier ones.
   const applyWorkspaceEdit = (edit: WorkspaceEdit) => {
     const filePath = props.filePath;
     if (!filePath) return;
     
-    // Get edits for the current file
-    let editsToApply: TextEdit[] = [];
-    
     // Check changes (simple format)
     if (edit.changes) {
-      const fileUri = `file://${filePath}`;
-      const fileEdits = edit.changes[fileUri];
-      if (fileEdits) {
-        editsToApply = fileEdits;
+      for (const [uri, fileEdits] of Object.entries(edit.changes)) {
+        if (uriToPath(uri) === filePath) {
+          applyEdits(fileEdits);
+        }
       }
     }
     
     // Check documentChanges (more complex format)
     if (edit.documentChanges) {
       for (const change of edit.documentChanges) {
-        const changePath = uriToPath(change.uri);
-        if (changePath === filePath) {
-          editsToApply = editsToApply.concat(change.edits);
+        // Resource operations are applied on disk by the backend
+        if (!("edits" in change)) continue;
+        if (uriToPath(change.uri) === filePath) {
+          applyEdits(change.edits);
         }
       }
     }
-    
-    if (editsToApply.length === 0) return;
+  };
+
+  // Apply one change's edits to the buffer. Their positions all refer to the
+  // buffer before the change, so they must fit it and not overlap. Returns
+  // whether they were applied.
+  const applyEdits = (edits: TextEdit[]): boolean => {
+    if (edits.length === 0) return true;
     
     // Sort edits by position (reverse order so we can apply from end to start)
-    // This prevents position shifts from affecting later edits
-    editsToApply.sort((a, b) => {
-      if (a.range.start.line !== b.range.start.line) {
-        return b.range.start.line - a.range.start.line;
-      }
-      return b.range.start.character - a.range.start.character;
+    // This prevents position shifts from affecting later edits. Insertions at
+    // the same position end up in the order given.
+    const sorted = edits
+      .map((edit, index) => ({ edit, index }))
+      .sort((a, b) => comparePositions(toPosition(b.edit.range.start), toPosition(a.edit.range.start)) || b.index - a.index)
+      .map(({ edit }) => edit);
+    
+    const lines = getEditorState(props.id).buffer.lines;
+    const fits = sorted.every((edit, i) => {
+      const { start, end } = edit.range;
+      if (comparePositions(toPosition(start), toPosition(end)) > 0) return false;
+      if (end.line >= lines.length || end.character > lines[end.line].length) return false;
+      if (start.character > lines[start.line].length) return false;
+      // Sorted from the end, so the next edit must end before this one starts
+      const next = sorted[i + 1];
+      return !next || comparePositions(toPosition(next.range.end), toPosition(start)) <= 0;
     });
+    if (!fits) return false;
     
     // Apply all edits
     updateEditorState(props.id, (state) => {
@@ -1106,7 +1351,7 @@ export function EditorSurface(props: Props) {
       let newLines = [...state.buffer.lines];
       let newCursor = { ...state.cursor };
       
-      for (const edit of editsToApply) {
+      for (const edit of sorted) {
         const { range, newText } = edit;
         const startLine = range.start.line;
         const startChar = range.start.character;
@@ -1155,8 +1400,35 @@ export function EditorSurface(props: Props) {
       const newBuffer = { ...state.buffer, lines: newLines };
       return markExtendedDirty({ ...state, buffer: newBuffer, cursor: newCursor, history: newHistory });
     });
+    return true;
   };
 
+  // Apply edits servers ask for in this file, and follow it when they move
+  // or delete it
+  createEffect(() => {
+    const filePath = props.filePath;
+    if (!filePath) return;
+    onCleanup(registerBufferEditor(filePath, {
+      applyEdits,
+      moved: (to) => {
+        // Keep the buffer rather than loading the file again from its new path
+        if (lspChangeTimeout) {
+          clearTimeout(lspChangeTimeout);
+          lspChangeTimeout = null;
+        }
+        const rootPath = lspRootPath();
+        if (rootPath) {
+          closeDocument(rootPath, filePath).catch((e) => {
+            console.warn("LSP: Failed to close document:", e);
+          });
+        }
+        setLoadedFilePath(to);
+        setSurfaceType(props.id, "editor", to);
+      },
+      deleted: () => closeSurface(props.id),
+    }));
+  });
+
   const executeCodeAction = async (index: number) => {
     const actions = codeActionItems();
     if (index < 0 || index >= actions.length) {
@@ -1166,10 +1438,11 @@ export function EditorSurface(props: Props) {
     
     let action = actions[index];
     const rootPath = lspRootPath();
+    const filePath = props.filePath;
     
     // If the action needs resolution, resolve it first
-    if (action.needsResolve && rootPath) {
-      const resolved = await resolveCodeAction(rootPath, {
+    if (action.needsResolve && rootPath && filePath) {
+      const resolved = await resolveCodeAction(rootPath, filePath, {
         title: action.title,
         kind: action.kind,
         isPreferred: action.isPreferred,
@@ -1324,7 +1597,7 @@ export function EditorSurface(props: Props) {
     }
 
     // Handle Ctrl+N for completion (in insert mode) - vim-style
-    if (e.ctrlKey && e.key === "n" && state.mode === "insert" && props.filePath?.endsWith(".rs")) {
+    if (e.ctrlKey && e.key === "n" && state.mode === "insert" && lspRootPath()) {
       e.preventDefault();
       handleCompletion();
       return;
@@ -1777,32 +2050,39 @@ export function EditorSurface(props: Props) {
     }
     
     // Handle special LSP commands before vim parsing
-    if (input === "gd" && state.mode === "normal" && props.filePath?.endsWith(".rs")) {
+    if (input === "gd" && state.mode === "normal" && lspRootPath()) {
       handleGotoDefinition();
       setPendingInput("");
       return;
     }
     
     // K - show hover information
-    if (input === "K" && state.mode === "normal" && props.filePath?.endsWith(".rs")) {
+    if (input === "K" && state.mode === "normal" && lspRootPath()) {
       handleHover();
       setPendingInput("");
       return;
     }
     
     // gr - find references
-    if (input === "gr" && state.mode === "normal" && props.filePath?.endsWith(".rs")) {
+    if (input === "gr" && state.mode === "normal" && lspRootPath()) {
       handleFindReferences();
       setPendingInput("");
       return;
     }
     
     // ga - code actions (like VS Code's Ctrl+.)
-    if (input === "ga" && state.mode === "normal" && props.filePath?.endsWith(".rs")) {
+    if (input === "ga" && state.mode === "normal" && lspRootPath()) {
       handleCodeActions();
       setPendingInput("");
       return;
     }
+
+    // gq - format the document, or the selection in visual mode
+    if (input === "gq" && state.mode === "normal" && lspRootPath()) {
+      handleFormat();
+      setPendingInput("");
+      return;
+    }
     
     // Parse with a fresh vim state that preserves only find char info and registers
     // The full input string contains all the context needed for parsing
@@ -1977,6 +2257,7 @@ export function EditorSurface(props: Props) {
         autoCompleteTimeout = null;
       }
       closeCompletion();
+      closeSignatureHelp();
       updateEditorState(props.id, (state) => {
         const lineLen = state.buffer.lines[state.cursor.line]?.length ?? 0;
         const newCol = Math.max(0, Math.min(state.cursor.column - 1, lineLen - 1));
@@ -2050,8 +2331,11 @@ export function EditorSurface(props: Props) {
       });
       e.preventDefault();
       // Re-trigger autocomplete after backspace (might still be in a word)
-      if (props.filePath?.endsWith(".rs")) {
+      if (lspRootPath()) {
         triggerAutoComplete();
+        if (signatures()) {
+          handleSignatureHelp();
+        }
       }
       return;
     }
@@ -2117,7 +2401,7 @@ export function EditorSurface(props: Props) {
       e.preventDefault();
       
       // Trigger autocomplete for identifier characters and trigger characters
-      if (props.filePath?.endsWith(".rs")) {
+      if (lspRootPath()) {
         if (/[a-zA-Z0-9_]/.test(key)) {
           // Identifier character - trigger with debounce
           triggerAutoComplete();
@@ -2128,6 +2412,16 @@ export function EditorSurface(props: Props) {
           // Other characters - close completion
           closeCompletion();
         }
+
+        // Signature help opens on a call's `(` or `,` and follows the cursor
+        // while it's open, until the call is closed
+        if (key === ")") {
+          closeSignatureHelp();
+        } else if (key === "(" || key === ",") {
+          handleSignatureHelp(key);
+        } else if (signatures()) {
+          handleSignatureHelp();
+        }
       }
     }
   };
@@ -2184,7 +2478,7 @@ export function EditorSurface(props: Props) {
       
       const tokenStart = Math.max(token.startCol, startCol) - startCol;
       const tokenEnd = Math.min(token.endCol, endCol) - startCol;
-      const className = `syntax-${token.type.replace(/\./g, "-")}`;
+      const className = highlightClass(token);
       
       for (let i = tokenStart; i < tokenEnd; i++) {
         charInfos[i].syntaxClass = className;
@@ -2287,7 +2581,7 @@ export function EditorSurface(props: Props) {
     
     for (const token of tokens) {
       if (col >= token.startCol && col < token.endCol) {
-        return `syntax-${token.type.replace(/\./g, "-")}`;
+        return highlightClass(token);
       }
     }
     return null;
@@ -2348,6 +2642,35 @@ export function EditorSurface(props: Props) {
 
   // EditorLine component - renders a single line with cursor and selection
   // This is a component so SolidJS properly tracks reactive dependencies
+  // Render a line with its inlay hints between the pieces of text
+  const renderWithInlayHints = (text: string, lineIndex: number, hints: InlayHint[], allTokens: HighlightResult, lineDiags?: Diagnostic[]) => {
+    const sorted = [...hints].sort((a, b) => a.position.character - b.position.character);
+    const parts = [];
+    let col = 0;
+    for (const hint of sorted) {
+      const at = Math.min(hint.position.character, text.length);
+      parts.push(renderHighlightedText(text.slice(col, at), lineIndex, col, allTokens, lineDiags));
+      parts.push(
+        <span
+          class="editor-surface__inlay-hint"
+          classList={{
+            "editor-surface__inlay-hint--type": hint.kind === 1,
+            "editor-surface__inlay-hint--parameter": hint.kind === 2,
+            "editor-surface__inlay-hint--pad-left": hint.paddingLeft,
+            "editor-surface__inlay-hint--pad-right": hint.paddingRight,
+          }}
+          title={hint.tooltip}
+          onMouseEnter={() => handleInlayHintHover(hint)}
+        >
+          {hint.label}
+        </span>
+      );
+      col = at;
+    }
+    parts.push(renderHighlightedText(text.slice(col), lineIndex, col, allTokens, lineDiags));
+    return parts;
+  };
+
   function EditorLine(lineProps: { lineIndex: number }) {
     const lineIndex = lineProps.lineIndex;
     
@@ -2373,6 +2696,9 @@ export function EditorSurface(props: Props) {
       );
     };
     
+    // Inlay hints are left out of the cursor line, so columns there match the text
+    const lineInlayHints = () => documentInlayHints().filter((h) => h.position.line === lineIndex);
+
     // Get the most severe diagnostic for the gutter
     const gutterSeverity = () => {
       const diags = lineDiagnostics();
@@ -2489,7 +2815,9 @@ export function EditorSurface(props: Props) {
         <span class="editor-surface__line-content">
           <Show when={isCursorLine()} fallback={
             <Show when={visualSelection().inSelection} fallback={
-              lineContent() ? renderHighlightedText(lineContent(), lineIndex, 0, tokens(), lineDiagnostics()) : " "
+              lineInlayHints().length > 0
+                ? renderWithInlayHints(lineContent(), lineIndex, lineInlayHints(), tokens(), lineDiagnostics())
+                : lineContent() ? renderHighlightedText(lineContent(), lineIndex, 0, tokens(), lineDiagnostics()) : " "
             }>
               <span>{renderHighlightedText(lineContent().slice(0, visualSelection().selStart), lineIndex, 0, tokens(), lineDiagnostics())}</span>
               <span class="editor-surface__selection">{renderHighlightedText(lineContent().slice(visualSelection().selStart, visualSelection().selEnd) || " ", lineIndex, visualSelection().selStart, tokens(), lineDiagnostics())}</span>
@@ -2717,6 +3045,49 @@ export function EditorSurface(props: Props) {
               />
             </CursorPopup>
           </Show>
+          <Show when={signatures() && signaturePosition()}>
+            <CursorPopup
+              position={signaturePosition}
+              preferredPlacement="above"
+              maxWidth={600}
+              maxHeight={250}
+              class="editor-surface__signature"
+              onClick={closeSignatureHelp}
+            >
+              {(() => {
+                const help = signatures()!;
+                const signature = help.signatures[help.activeSignature];
+                const [before, param, after] = signatureLabelParts(signature);
+                const activeParam = signature.parameters.find((p) => p.active);
+                return (
+                  <div class="editor-surface__signature-content">
+                    <div class="editor-surface__signature-label">
+                      {before}
+                      <span class="editor-surface__signature-param--active">{param}</span>
+                      {after}
+                      <Show when={help.signatures.length > 1}>
+                        <span class="editor-surface__signature-count">
+                          {help.activeSignature + 1}/{help.signatures.length}
+                        </span>
+                      </Show>
+                    </div>
+                    <Show when={activeParam?.documentation}>
+                      <div
+                        class="editor-surface__hover-content"
+                        innerHTML={renderMarkdown(activeParam!.documentation!)}
+                      />
+                    </Show>
+                    <Show when={signature.documentation}>
+                      <div
+                        class="editor-surface__hover-content"
+                        innerHTML={renderMarkdown(signature.documentation!)}
+                      />
+                    </Show>
+                  </div>
+                );
+              })()}
+            </CursorPopup>
+          </Show>
           <Show when={completionItems().length > 0 && completionPosition()}>
             <CursorPopup
               position={completionPosition}
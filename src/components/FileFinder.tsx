@@ -1,6 +1,7 @@
-import { createSignal, createEffect, For, Show, onMount } from "solid-js";
+import { createSignal, createEffect, For, Show, onMount, onCleanup } from "solid-js";
 import { invoke } from "@tauri-apps/api/core";
-import { fuzzyFilter, FuzzyMatch } from "../utils/fuzzy";
+import { listen, UnlistenFn } from "@tauri-apps/api/event";
+import type { FuzzyMatch } from "../utils/fuzzy";
 import "./FileFinder.css";
 
 interface Props {
@@ -13,37 +14,65 @@ export function FileFinder(props: Props) {
   let inputRef: HTMLInputElement | undefined;
   
   const [query, setQuery] = createSignal("");
-  const [files, setFiles] = createSignal<string[]>([]);
   const [filtered, setFiltered] = createSignal<FuzzyMatch[]>([]);
   const [selectedIndex, setSelectedIndex] = createSignal(0);
   const [loading, setLoading] = createSignal(true);
   const [error, setError] = createSignal<string | null>(null);
 
-  // Load files on mount
-  onMount(async () => {
+  let unlistenFsChanged: UnlistenFn | undefined;
+  // Ignore responses to queries that have since been superseded
+  let latestRequest = 0;
+
+  async function findFiles(q: string, resetSelection: boolean) {
+    const request = ++latestRequest;
     try {
-      const fileList = await invoke<string[]>("list_files", { root: props.projectPath });
-      setFiles(fileList);
-      setFiltered(fuzzyFilter("", fileList));
+      // Matching and ranking happen in the backend's file index
+      const matches = await invoke<FuzzyMatch[]>("fuzzy_find_files", {
+        root: props.projectPath,
+        query: q,
+        limit: 100,
+      });
+      if (request !== latestRequest) return;
+      setFiltered(matches);
+      if (resetSelection) {
+        setSelectedIndex(0);
+      } else {
+        setSelectedIndex((i) => Math.min(i, Math.max(matches.length - 1, 0)));
+      }
+      setError(null);
     } catch (e) {
+      if (request !== latestRequest) return;
       setError(`Failed to load files: ${e}`);
     } finally {
-      setLoading(false);
+      if (request === latestRequest) setLoading(false);
     }
+  }
 
+  onMount(async () => {
     // Focus input
     inputRef?.focus();
+
+    // Re-run the query when files are added, removed or renamed on disk
+    unlistenFsChanged = await listen<{ root: string; changes: { kind: string }[] }>(
+      "fs:changed",
+      (event) => {
+        if (
+          event.payload.root === props.projectPath &&
+          event.payload.changes.some((c) => c.kind !== "modified")
+        ) {
+          findFiles(query(), false);
+        }
+      }
+    );
+  });
+
+  onCleanup(() => {
+    unlistenFsChanged?.();
   });
 
-  // Update filtered list when query changes
+  // Update results when the query changes
   createEffect(() => {
-    const q = query();
-    const f = files();
-    if (f.length > 0) {
-      const matches = fuzzyFilter(q, f);
-      setFiltered(matches.slice(0, 100)); // Limit to 100 results for performance
-      setSelectedIndex(0);
-    }
+    findFiles(query(), true);
   });
 
   const handleKeyDown = (e: KeyboardEvent) => {
@@ -27,6 +27,20 @@ export function TerminalSurface(props: Props) {
   // Guard against concurrent connection attempts
   let connectingTo: string | null = null;
   
+  // Pixel size of the terminal's cells, reported to the PTY for image
+  // protocols. Programs divide it by columns and rows to find the cell size,
+  // so it leaves out padding and any space the fit leaves over.
+  function pixelSize() {
+    const metrics = term?.renderer?.getMetrics();
+    if (!term || !metrics) return { pixelSize: null };
+    return {
+      pixelSize: {
+        width: Math.round(term.cols * metrics.width),
+        height: Math.round(term.rows * metrics.height),
+      },
+    };
+  }
+  
   // Helper to connect to a session (attach or spawn+attach)
   async function connectToSession(sessionId: string) {
     if (!term) return;
@@ -80,6 +94,7 @@ export function TerminalSurface(props: Props) {
           cwd: props.projectPath,
           rows: term.rows,
           cols: term.cols,
+          ...pixelSize(),
         });
         console.log(`[Terminal] Spawned new session: ${sessionId}`);
         
@@ -205,7 +220,7 @@ export function TerminalSurface(props: Props) {
     term.onResize((size: { rows: number; cols: number }) => {
       const sid = currentSessionId();
       if (sid) {
-        invoke("daemon_resize", { id: sid, rows: size.rows, cols: size.cols });
+        invoke("daemon_resize", { id: sid, rows: size.rows, cols: size.cols, ...pixelSize() });
       }
     });
 
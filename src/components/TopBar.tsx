@@ -1,5 +1,5 @@
 import { For, Show, createSignal } from "solid-js";
-import { open } from "@tauri-apps/plugin-dialog";
+import { invoke } from "@tauri-apps/api/core";
 import { useWindow } from "./WindowChrome";
 import { projectState, setCurrentProject, Project } from "../store/project";
 import { lspStore, getProjectDiagnosticCounts } from "../store/lsp";
@@ -15,13 +15,10 @@ export function TopBar(props: Props) {
   const [isHoveringTraffic, setIsHoveringTraffic] = createSignal(false);
 
   const handleAddProject = async () => {
-    const selected = await open({
-      directory: true,
-      multiple: false,
-      title: "Open Project",
-    });
+    // Picked in the backend, which allows file access to the folder
+    const selected = await invoke<string | null>("pick_project_directory");
     
-    if (selected && typeof selected === "string") {
+    if (selected) {
       await props.onAddProject(selected);
     }
   };
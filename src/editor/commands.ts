@@ -34,7 +34,7 @@ import {
 import { UndoHistory, pushHistory, undo, redo } from "./history";
 
 // Default indent size in spaces
-const INDENT_SIZE = 4;
+export const INDENT_SIZE = 4;
 
 // ============================================================================
 // Extended Editor State
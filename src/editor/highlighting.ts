@@ -6,6 +6,7 @@
  */
 
 import { Parser, Language, Tree, Query, Edit } from "web-tree-sitter";
+import type { SemanticToken } from "../lsp/types";
 
 // ============================================================================
 // Types
@@ -18,6 +19,8 @@ export interface HighlightToken {
   endCol: number;
   /** Highlight category (e.g., "keyword", "string", "function") */
   type: string;
+  /** Semantic token modifiers from the language server (e.g., "mutable", "unsafe") */
+  modifiers?: string[];
 }
 
 export interface HighlightResult {
@@ -998,6 +1001,97 @@ function mergeOverlappingTokens(tokens: HighlightToken[]): HighlightToken[] {
   return result;
 }
 
+/**
+ * Map semantic token types from the language server to highlight types.
+ * Types not listed keep the Tree-sitter highlighting underneath.
+ */
+const SEMANTIC_TOKEN_HIGHLIGHTS: Record<string, string> = {
+  namespace: "namespace",
+  type: "type",
+  class: "type",
+  enum: "type",
+  interface: "type",
+  struct: "type",
+  typeAlias: "type",
+  union: "type",
+  typeParameter: "type",
+  builtinType: "type.builtin",
+  parameter: "parameter",
+  variable: "variable",
+  property: "property",
+  enumMember: "constant",
+  constParameter: "constant",
+  function: "function",
+  method: "function",
+  macro: "macro",
+  keyword: "keyword",
+  selfKeyword: "keyword",
+  selfTypeKeyword: "keyword",
+  comment: "comment",
+  string: "string",
+  number: "number",
+  boolean: "boolean",
+  escapeSequence: "escape",
+  formatSpecifier: "escape",
+  operator: "operator",
+  decorator: "attribute",
+  attribute: "attribute",
+  builtinAttribute: "attribute",
+  lifetime: "label",
+  label: "label",
+};
+
+/**
+ * Overlay semantic tokens from the language server on Tree-sitter tokens.
+ * Semantic tokens replace the parts of syntax tokens they cover.
+ */
+export function applySemanticTokens(result: HighlightResult, semanticTokens: SemanticToken[]): HighlightResult {
+  const byLine = new Map<number, HighlightToken[]>();
+  for (const token of semanticTokens) {
+    const type = SEMANTIC_TOKEN_HIGHLIGHTS[token.tokenType];
+    if (!type || token.length === 0) continue;
+    addToken(byLine, token.line, {
+      startCol: token.startCharacter,
+      endCol: token.startCharacter + token.length,
+      type,
+      modifiers: token.modifiers.length > 0 ? token.modifiers : undefined,
+    });
+  }
+
+  const lines = new Map(result.lines);
+  for (const [lineNum, overlays] of byLine) {
+    const merged: HighlightToken[] = [];
+    for (const token of lines.get(lineNum) ?? []) {
+      // Keep the parts of the syntax token outside every overlay
+      let pieces = [token];
+      for (const overlay of overlays) {
+        pieces = pieces.flatMap((piece) => {
+          if (overlay.endCol <= piece.startCol || overlay.startCol >= piece.endCol) return [piece];
+          const kept: HighlightToken[] = [];
+          if (piece.startCol < overlay.startCol) kept.push({ ...piece, endCol: overlay.startCol });
+          if (piece.endCol > overlay.endCol) kept.push({ ...piece, startCol: overlay.endCol });
+          return kept;
+        });
+      }
+      merged.push(...pieces);
+    }
+    merged.push(...overlays);
+    merged.sort((a, b) => a.startCol - b.startCol);
+    lines.set(lineNum, merged);
+  }
+
+  return { lines };
+}
+
+/**
+ * CSS classes for a highlight token, e.g. "syntax-variable semantic-mutable".
+ */
+export function highlightClass(token: HighlightToken): string {
+  const base = `syntax-${token.type.replace(/\./g, "-")}`;
+  if (!token.modifiers) return base;
+  return [base, ...token.modifiers.map((m) => `semantic-${m}`)].join(" ");
+}
+
 // ============================================================================
 // Singleton Instance
 // ============================================================================
@@ -9,7 +9,21 @@ import type {
   CompletionItem,
   CodeAction,
   CodeActionForResolve,
+  ContentChange,
   Diagnostic,
+  PrepareRenameResult,
+  WorkspaceEdit,
+  AppliedWorkspaceEdit,
+  SymbolItem,
+  FormattingOptions,
+  TextEdit,
+  Range,
+  SignatureHelp,
+  SignatureHelpContext,
+  InlayHint,
+  SemanticToken,
+  HierarchyItem,
+  HierarchyCall,
 } from "./types";
 
 /**
@@ -21,10 +35,10 @@ export async function lspFindRoot(filePath: string): Promise<string | null> {
 }
 
 /**
- * Start the language server for a project.
+ * Start the language servers for a file's language in a project.
  */
-export async function lspStart(rootPath: string): Promise<void> {
-  await invoke("lsp_start", { rootPath });
+export async function lspStart(rootPath: string, filePath: string): Promise<void> {
+  await invoke("lsp_start", { rootPath, filePath });
 }
 
 /**
@@ -46,14 +60,18 @@ export async function lspOpenDocument(
 }
 
 /**
- * Notify the server that a document changed.
+ * Notify the server that a document changed. `version` is the document
+ * version after the changes; if it doesn't match the backend's copy the call
+ * fails and the whole document should be resent with a null version.
+ * Returns the new version.
  */
 export async function lspChangeDocument(
   rootPath: string,
   filePath: string,
-  content: string
-): Promise<void> {
-  await invoke("lsp_change_document", { rootPath, filePath, content });
+  version: number | null,
+  changes: ContentChange[]
+): Promise<number> {
+  return invoke("lsp_change_document", { rootPath, filePath, version, changes });
 }
 
 /**
@@ -149,10 +167,240 @@ export async function lspCodeActions(
  */
 export async function lspResolveCodeAction(
   rootPath: string,
+  filePath: string,
   codeAction: CodeActionForResolve
 ): Promise<CodeAction> {
   return invoke("lsp_resolve_code_action", {
     rootPath,
+    filePath,
     codeAction,
   });
 }
+
+/**
+ * Check the symbol at a position can be renamed. Returns null if it can't.
+ */
+export async function lspPrepareRename(
+  rootPath: string,
+  filePath: string,
+  line: number,
+  character: number
+): Promise<PrepareRenameResult | null> {
+  return invoke("lsp_prepare_rename", { rootPath, filePath, line, character });
+}
+
+/**
+ * Get the edit renaming the symbol at a position. Returns null if there's
+ * nothing to change.
+ */
+export async function lspRename(
+  rootPath: string,
+  filePath: string,
+  line: number,
+  character: number,
+  newName: string
+): Promise<WorkspaceEdit | null> {
+  return invoke("lsp_rename", { rootPath, filePath, line, character, newName });
+}
+
+/**
+ * Apply a workspace edit to files on disk. Text edits to `openPaths` are
+ * returned for the editor to apply to its buffers instead.
+ */
+export async function lspApplyWorkspaceEdit(
+  edit: WorkspaceEdit,
+  openPaths: string[]
+): Promise<AppliedWorkspaceEdit> {
+  return invoke("lsp_apply_workspace_edit", { edit, openPaths });
+}
+
+/**
+ * Tell the server whether an edit it asked for in an "lsp:apply-edit" event
+ * was applied.
+ */
+export async function lspApplyEditResult(
+  id: number,
+  applied: boolean,
+  failureReason?: string
+): Promise<void> {
+  return invoke("lsp_apply_edit_result", { id, applied, failureReason });
+}
+
+/**
+ * Get a document's symbols as an outline tree.
+ */
+export async function lspDocumentSymbols(
+  rootPath: string,
+  filePath: string
+): Promise<SymbolItem[]> {
+  return invoke("lsp_document_symbols", { rootPath, filePath });
+}
+
+/**
+ * Search a project's symbols by name.
+ */
+export async function lspWorkspaceSymbols(
+  rootPath: string,
+  query: string
+): Promise<SymbolItem[]> {
+  return invoke("lsp_workspace_symbols", { rootPath, query });
+}
+
+/**
+ * Format a document. Returns the edits to apply to it.
+ */
+export async function lspFormatDocument(
+  rootPath: string,
+  filePath: string,
+  options: FormattingOptions
+): Promise<TextEdit[]> {
+  return invoke("lsp_format_document", { rootPath, filePath, options });
+}
+
+/**
+ * Format part of a document. Returns the edits to apply to it.
+ */
+export async function lspFormatRange(
+  rootPath: string,
+  filePath: string,
+  range: Range,
+  options: FormattingOptions
+): Promise<TextEdit[]> {
+  return invoke("lsp_format_range", { rootPath, filePath, range, options });
+}
+
+/**
+ * Get the edits to apply to a document before saving it, e.g. formatting
+ * for servers configured to format on save.
+ */
+export async function lspWillSave(
+  rootPath: string,
+  filePath: string,
+  reason: number,
+  options: FormattingOptions
+): Promise<TextEdit[]> {
+  return invoke("lsp_will_save", { rootPath, filePath, reason, options });
+}
+
+/**
+ * Get the signatures of the call at a position. Returns null if there are
+ * none, or the trigger character isn't one the server triggers on.
+ */
+export async function lspSignatureHelp(
+  rootPath: string,
+  filePath: string,
+  line: number,
+  character: number,
+  context?: SignatureHelpContext
+): Promise<SignatureHelp | null> {
+  return invoke("lsp_signature_help", { rootPath, filePath, line, character, context });
+}
+
+/**
+ * Get the inlay hints in a range of a document.
+ */
+export async function lspInlayHints(
+  rootPath: string,
+  filePath: string,
+  range: Range
+): Promise<InlayHint[]> {
+  return invoke("lsp_inlay_hints", { rootPath, filePath, range });
+}
+
+/**
+ * Fill in an inlay hint's tooltip.
+ */
+export async function lspResolveInlayHint(
+  rootPath: string,
+  filePath: string,
+  hint: InlayHint
+): Promise<InlayHint> {
+  return invoke("lsp_resolve_inlay_hint", { rootPath, filePath, hint });
+}
+
+/**
+ * Get the semantic tokens for a whole document.
+ */
+export async function lspSemanticTokens(
+  rootPath: string,
+  filePath: string
+): Promise<SemanticToken[]> {
+  return invoke("lsp_semantic_tokens", { rootPath, filePath });
+}
+
+/**
+ * Get the semantic tokens in a range of a document.
+ */
+export async function lspSemanticTokensRange(
+  rootPath: string,
+  filePath: string,
+  range: Range
+): Promise<SemanticToken[]> {
+  return invoke("lsp_semantic_tokens_range", { rootPath, filePath, range });
+}
+
+/**
+ * Get the call hierarchy items at a position, to expand with
+ * lspIncomingCalls and lspOutgoingCalls.
+ */
+export async function lspPrepareCallHierarchy(
+  rootPath: string,
+  filePath: string,
+  line: number,
+  character: number
+): Promise<HierarchyItem[]> {
+  return invoke("lsp_prepare_call_hierarchy", { rootPath, filePath, line, character });
+}
+
+/**
+ * Find the callers of a call hierarchy item.
+ */
+export async function lspIncomingCalls(
+  rootPath: string,
+  item: HierarchyItem
+): Promise<HierarchyCall[]> {
+  return invoke("lsp_incoming_calls", { rootPath, item });
+}
+
+/**
+ * Find what a call hierarchy item calls.
+ */
+export async function lspOutgoingCalls(
+  rootPath: string,
+  item: HierarchyItem
+): Promise<HierarchyCall[]> {
+  return invoke("lsp_outgoing_calls", { rootPath, item });
+}
+
+/**
+ * Get the type hierarchy items at a position, to expand with
+ * lspSupertypes and lspSubtypes.
+ */
+export async function lspPrepareTypeHierarchy(
+  rootPath: string,
+  filePath: string,
+  line: number,
+  character: number
+): Promise<HierarchyItem[]> {
+  return invoke("lsp_prepare_type_hierarchy", { rootPath, filePath, line, character });
+}
+
+/**
+ * Find the supertypes of a type hierarchy item.
+ */
+export async function lspSupertypes(
+  rootPath: string,
+  item: HierarchyItem
+): Promise<HierarchyItem[]> {
+  return invoke("lsp_supertypes", { rootPath, item });
+}
+
+/**
+ * Find the subtypes of a type hierarchy item.
+ */
+export async function lspSubtypes(
+  rootPath: string,
+  item: HierarchyItem
+): Promise<HierarchyItem[]> {
+  return invoke("lsp_subtypes", { rootPath, item });
+}
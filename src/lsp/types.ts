@@ -83,20 +83,43 @@ export interface PublishDiagnosticsParams {
   diagnostics: Diagnostic[];
 }
 
-/** Convert a file:// URI to a file path. */
+/** Convert a file:// URI to a file path, decoding percent escapes. */
 export function uriToPath(uri: string): string {
-  if (uri.startsWith("file://")) {
-    return uri.slice(7);
+  if (!uri.startsWith("file://")) {
+    return uri;
   }
-  return uri;
+  // Escapes that don't decode to UTF-8 are kept as they are
+  return uri.slice(7).replace(/(?:%[0-9A-Fa-f]{2})+/g, (escapes) => {
+    try {
+      return decodeURIComponent(escapes);
+    } catch {
+      return escapes;
+    }
+  });
 }
 
-/** Convert a file path to a file:// URI. */
+/**
+ * Convert a file path to a file:// URI, percent-encoding everything but
+ * unreserved characters and separators, as the backend does.
+ */
 export function pathToUri(path: string): string {
   if (path.startsWith("file://")) {
     return path;
   }
-  return `file://${path}`;
+  let uri = "file://";
+  for (const byte of new TextEncoder().encode(path)) {
+    const char = String.fromCharCode(byte);
+    uri += /[A-Za-z0-9\-._~/]/.test(char)
+      ? char
+      : `%${byte.toString(16).toUpperCase().padStart(2, "0")}`;
+  }
+  return uri;
+}
+
+/** A change to an open document: `text` replaces `range`, or the whole document if there's no range. */
+export interface ContentChange {
+  range?: Range;
+  text: string;
 }
 
 // === Code Action Types ===
@@ -107,12 +130,37 @@ export interface TextEdit {
   newText: string;
 }
 
-/** Document change - edits to a specific document. */
-export interface DocumentChange {
+/** Edits to a specific document. */
+export interface TextDocumentChange {
   uri: string;
   edits: TextEdit[];
 }
 
+/** Create a file. */
+export interface CreateFile {
+  kind: "create";
+  uri: string;
+  options?: { overwrite?: boolean; ignoreIfExists?: boolean };
+}
+
+/** Rename or move a file or folder. */
+export interface RenameFile {
+  kind: "rename";
+  oldUri: string;
+  newUri: string;
+  options?: { overwrite?: boolean; ignoreIfExists?: boolean };
+}
+
+/** Delete a file or folder. */
+export interface DeleteFile {
+  kind: "delete";
+  uri: string;
+  options?: { recursive?: boolean; ignoreIfNotExists?: boolean };
+}
+
+/** Document change - edits to a document, or a resource operation. */
+export type DocumentChange = TextDocumentChange | CreateFile | RenameFile | DeleteFile;
+
 /** Workspace edit - a collection of changes to documents. */
 export interface WorkspaceEdit {
   /** Map of file URI to list of text edits. */
@@ -121,6 +169,204 @@ export interface WorkspaceEdit {
   documentChanges?: DocumentChange[];
 }
 
+/** How to format a document. */
+export interface FormattingOptions {
+  tabSize: number;
+  insertSpaces: boolean;
+  trimTrailingWhitespace?: boolean;
+  insertFinalNewline?: boolean;
+  trimFinalNewlines?: boolean;
+}
+
+/** Why a document is being saved. */
+export const SaveReason = {
+  Manual: 1,
+  AfterDelay: 2,
+  FocusOut: 3,
+} as const;
+
+/** What triggered a signature help request. */
+export const SignatureHelpTriggerKind = {
+  Invoked: 1,
+  TriggerCharacter: 2,
+  ContentChange: 3,
+} as const;
+
+/** Context sent with a signature help request. */
+export interface SignatureHelpContext {
+  triggerKind: number;
+  triggerCharacter?: string;
+  /** Signature help is already showing. */
+  isRetrigger?: boolean;
+  /** The signature the user picked in the help showing. */
+  activeSignature?: number;
+}
+
+/** A parameter of a signature. */
+export interface SignatureParameter {
+  label: string;
+  /** Start and end of the parameter in the signature label, in UTF-16 code units. */
+  labelOffsets?: [number, number];
+  /** Markdown. */
+  documentation?: string;
+  /** The cursor is in this parameter. */
+  active: boolean;
+}
+
+/** A signature of a callable. */
+export interface SignatureInfo {
+  label: string;
+  /** Markdown. */
+  documentation?: string;
+  parameters: SignatureParameter[];
+  activeParameter?: number;
+}
+
+/** Signatures of the call at the cursor. */
+export interface SignatureHelp {
+  signatures: SignatureInfo[];
+  /** Index of the signature to show. */
+  activeSignature: number;
+}
+
+/** A semantic token from the language server, e.g. a mutable variable. */
+export interface SemanticToken {
+  line: number;
+  startCharacter: number;
+  length: number;
+  /** A type from the server's legend, e.g. "variable" or "macro". */
+  tokenType: string;
+  /** Modifiers from the server's legend, e.g. "mutable" or "unsafe". */
+  modifiers: string[];
+}
+
+/** Inlay hint kinds. */
+export const InlayHintKind = {
+  Type: 1,
+  Parameter: 2,
+} as const;
+
+/** A hint shown inline in the source, e.g. an inferred type or a parameter name. */
+export interface InlayHint {
+  /** Where the hint goes; it's shown before the character here. */
+  position: Position;
+  label: string;
+  kind?: number;
+  /** Markdown. */
+  tooltip?: string;
+  /** Edits inserting the hint into the document. */
+  textEdits?: TextEdit[];
+  paddingLeft: boolean;
+  paddingRight: boolean;
+  /** Data preserved for resolve requests. */
+  data?: unknown;
+  /** Whether the tooltip may be filled in by resolving the hint. */
+  needsResolve: boolean;
+}
+
+/** Symbol kinds. */
+export const SymbolKind = {
+  File: 1,
+  Module: 2,
+  Namespace: 3,
+  Package: 4,
+  Class: 5,
+  Method: 6,
+  Property: 7,
+  Field: 8,
+  Constructor: 9,
+  Enum: 10,
+  Interface: 11,
+  Function: 12,
+  Variable: 13,
+  Constant: 14,
+  String: 15,
+  Number: 16,
+  Boolean: 17,
+  Array: 18,
+  Object: 19,
+  Key: 20,
+  Null: 21,
+  EnumMember: 22,
+  Struct: 23,
+  Event: 24,
+  Operator: 25,
+  TypeParameter: 26,
+} as const;
+
+/** A symbol from a document outline or a workspace symbol search. */
+export interface SymbolItem {
+  name: string;
+  detail?: string;
+  kind: number;
+  /** Name of the symbol containing this one. */
+  container?: string;
+  uri: string;
+  /** The whole symbol, e.g. a function including its body. */
+  range: Range;
+  /** The part to select when jumping to the symbol, e.g. its name. */
+  selectionRange: Range;
+  /** Nested symbols, in a document outline. */
+  children: SymbolItem[];
+}
+
+/** An item in a call or type hierarchy, e.g. a function or a trait. Pass it back to expand it. */
+export interface HierarchyItem {
+  name: string;
+  kind: number;
+  tags?: number[];
+  detail?: string;
+  uri: string;
+  /** The whole item, e.g. a function including its body. */
+  range: Range;
+  /** The part to select when jumping to the item, e.g. its name. */
+  selectionRange: Range;
+  /** Data preserved for expanding the item. */
+  data?: unknown;
+}
+
+/** A call to or from a hierarchy item. */
+export interface HierarchyCall {
+  /** The caller for incoming calls, the callee for outgoing ones. */
+  item: HierarchyItem;
+  /** Where the calls are: in `item`'s file for incoming calls, in the expanded item's file for outgoing ones. */
+  ranges: Range[];
+}
+
+/** Result of a prepare rename request. Without a range, rename the word at the position. */
+export interface PrepareRenameResult {
+  range?: Range;
+  placeholder?: string;
+}
+
+/** A path deleted by a workspace edit, restorable from the trash. */
+export interface TrashedPath {
+  id: string;
+  originalPath: string;
+  deletedAt: number;
+}
+
+/** What's left for the editor after a workspace edit is applied on disk. */
+export interface AppliedWorkspaceEdit {
+  /** Edits to open files, to apply in order by their paths after moves. */
+  bufferEdits?: WorkspaceEdit;
+  /** Moves in the order made. */
+  moved: { from: string; to: string }[];
+  /** Paths moved to the trash. */
+  deleted: TrashedPath[];
+  /** Open files moved, from their path before the edit to the one after it. */
+  movedBuffers: { from: string; to: string }[];
+  /** Open files deleted, by their path before the edit; their buffers should close. */
+  closedBuffers: string[];
+}
+
+/** An edit a server asked the editor to apply, answered with lspApplyEditResult. */
+export interface ApplyEditRequest {
+  id: number;
+  label?: string;
+  edit: WorkspaceEdit;
+}
+
 /** Code action kinds. */
 export const CodeActionKind = {
   QuickFix: "quickfix",
@@ -17,6 +17,26 @@ import {
   lspReferences,
   lspCodeActions,
   lspResolveCodeAction,
+  lspPrepareRename,
+  lspRename,
+  lspDocumentSymbols,
+  lspWorkspaceSymbols,
+  lspFormatDocument,
+  lspFormatRange,
+  lspWillSave,
+  lspSignatureHelp,
+  lspInlayHints,
+  lspResolveInlayHint,
+  lspSemanticTokens,
+  lspSemanticTokensRange,
+  lspPrepareCallHierarchy,
+  lspIncomingCalls,
+  lspOutgoingCalls,
+  lspPrepareTypeHierarchy,
+  lspSupertypes,
+  lspSubtypes,
+  lspApplyWorkspaceEdit,
+  lspApplyEditResult,
 } from "../lsp/client";
 import type {
   Diagnostic,
@@ -26,8 +46,24 @@ import type {
   PublishDiagnosticsParams,
   CodeAction,
   CodeActionForResolve,
+  ContentChange,
+  Position,
+  PrepareRenameResult,
+  WorkspaceEdit,
+  SymbolItem,
+  FormattingOptions,
+  TextEdit,
+  Range,
+  SignatureHelp,
+  SignatureHelpContext,
+  InlayHint,
+  SemanticToken,
+  HierarchyItem,
+  HierarchyCall,
+  ApplyEditRequest,
+  AppliedWorkspaceEdit,
 } from "../lsp/types";
-import { uriToPath } from "../lsp/types";
+import { uriToPath, SaveReason } from "../lsp/types";
 
 export type ServerState = "stopped" | "starting" | "running" | "error";
 
@@ -43,12 +79,18 @@ interface LspStoreState {
   diagnostics: Record<string, Diagnostic[]>;
   /** Document versions for sync, keyed by file path */
   documentVersions: Record<string, number>;
+  /** Bumped when a project's servers ask for inlay hints to be requested again, keyed by root path */
+  inlayHintsRefresh: Record<string, number>;
+  /** Bumped when a project's servers ask for semantic tokens to be requested again, keyed by root path */
+  semanticTokensRefresh: Record<string, number>;
 }
 
 const [store, setStore] = createStore<LspStoreState>({
   servers: {},
   diagnostics: {},
   documentVersions: {},
+  inlayHintsRefresh: {},
+  semanticTokensRefresh: {},
 });
 
 export const lspStore = store;
@@ -65,6 +107,108 @@ async function initListener(): Promise<void> {
     const filePath = uriToPath(uri);
     setStore("diagnostics", filePath, diagnostics);
   });
+
+  await listen<string>("lsp:inlay-hints-refresh", (event) => {
+    const rootPath = event.payload;
+    setStore("inlayHintsRefresh", rootPath, (n) => (n ?? 0) + 1);
+  });
+
+  await listen<string>("lsp:semantic-tokens-refresh", (event) => {
+    const rootPath = event.payload;
+    setStore("semanticTokensRefresh", rootPath, (n) => (n ?? 0) + 1);
+  });
+
+  await listen<ApplyEditRequest>("lsp:apply-edit", (event) => {
+    applyServerEdit(event.payload).catch(console.error);
+  });
+}
+
+/** An editor with a file open, for edits servers ask for. */
+export interface BufferEditor {
+  /** Apply one change's edits to the buffer; false if they don't fit it. */
+  applyEdits(edits: TextEdit[]): boolean;
+  /** The file moved on disk; the buffer now belongs to `to`. */
+  moved(to: string): void;
+  /** The file was deleted; the buffer should close. */
+  deleted(): void;
+}
+
+/** Editors applying edits to their buffers, keyed by file path */
+const bufferEditors = new Map<string, BufferEditor>();
+
+/**
+ * Register an editor to apply edits servers ask for to a file's buffer.
+ * Returns a function unregistering it.
+ */
+export function registerBufferEditor(
+  filePath: string,
+  editor: BufferEditor
+): () => void {
+  bufferEditors.set(filePath, editor);
+  return () => {
+    if (bufferEditors.get(filePath) === editor) {
+      bufferEditors.delete(filePath);
+    }
+  };
+}
+
+/**
+ * Apply an edit a server asked for: on disk, except for open files, whose
+ * editors follow moves and deletes and apply its changes to their buffers in
+ * order. The server is told how it went.
+ */
+async function applyServerEdit({ id, edit }: ApplyEditRequest): Promise<void> {
+  try {
+    const applied = await lspApplyWorkspaceEdit(edit, [...bufferEditors.keys()]);
+    const failures = applyToBuffers(applied, new Map(bufferEditors));
+    if (failures.length > 0) {
+      await lspApplyEditResult(id, false, failures.join("; "));
+      return;
+    }
+    await lspApplyEditResult(id, true);
+  } catch (e) {
+    console.error("LSP: Failed to apply edit from server:", e);
+    await lspApplyEditResult(id, false, String(e));
+  }
+}
+
+/**
+ * Bring the editors for open files in line with an edit applied on disk,
+ * given them keyed by their paths before it. Returns why any of its changes
+ * couldn't be applied to a buffer.
+ */
+export function applyToBuffers(
+  applied: AppliedWorkspaceEdit,
+  editors: Map<string, BufferEditor>
+): string[] {
+  // Editors by where their files are after the edit
+  const current = new Map<string, BufferEditor>();
+  const moves = new Map(applied.movedBuffers.map(({ from, to }) => [from, to]));
+  const closed = new Set(applied.closedBuffers);
+  for (const [path, editor] of editors) {
+    if (closed.has(path)) {
+      editor.deleted();
+      continue;
+    }
+    const to = moves.get(path);
+    if (to !== undefined) {
+      editor.moved(to);
+    }
+    current.set(to ?? path, editor);
+  }
+
+  const failures: string[] = [];
+  for (const change of applied.bufferEdits?.documentChanges ?? []) {
+    if (!("edits" in change)) continue;
+    const path = uriToPath(change.uri);
+    const editor = current.get(path);
+    if (!editor) {
+      failures.push(`${path} is no longer open`);
+    } else if (!editor.applyEdits(change.edits)) {
+      failures.push(`Edits don't fit the buffer for ${path}`);
+    }
+  }
+  return failures;
 }
 
 // Initialize listener on module load
@@ -77,26 +221,40 @@ export async function findLspRoot(filePath: string): Promise<string | null> {
   return lspFindRoot(filePath);
 }
 
+/** Starts in progress by root path, so servers aren't spawned twice */
+const pendingStarts: Record<string, Promise<void>> = {};
+
 /**
- * Start the language server for a project.
+ * Start the language servers for a file's language in a project. A project
+ * can run several servers (e.g. rust-analyzer and taplo); ones already
+ * running are left alone.
  */
-export async function startServer(rootPath: string): Promise<void> {
-  // Check if already running or starting
-  const current = store.servers[rootPath];
-  if (current?.state === "running" || current?.state === "starting") {
-    return;
-  }
+export async function startServer(rootPath: string, filePath: string): Promise<void> {
+  // Wait for any start in progress for the project
+  await pendingStarts[rootPath]?.catch(() => {});
 
-  setStore("servers", rootPath, { state: "starting" });
+  const wasRunning = store.servers[rootPath]?.state === "running";
+  if (!wasRunning) {
+    setStore("servers", rootPath, { state: "starting" });
+  }
 
+  const start = lspStart(rootPath, filePath);
+  pendingStarts[rootPath] = start;
   try {
-    await lspStart(rootPath);
+    await start;
     setStore("servers", rootPath, { state: "running" });
   } catch (e) {
     const error = e instanceof Error ? e.message : String(e);
     console.error("LSP: Failed to start server:", error);
-    setStore("servers", rootPath, { state: "error", error });
+    // Other servers for the project keep running
+    if (!wasRunning) {
+      setStore("servers", rootPath, { state: "error", error });
+    }
     throw e;
+  } finally {
+    if (pendingStarts[rootPath] === start) {
+      delete pendingStarts[rootPath];
+    }
   }
 }
 
@@ -110,7 +268,7 @@ export async function startServerForFile(filePath: string): Promise<string | nul
     return null;
   }
   
-  await startServer(rootPath);
+  await startServer(rootPath, filePath);
   return rootPath;
 }
 
@@ -141,6 +299,55 @@ export function getServerStatus(rootPath: string): ServerStatus {
   return store.servers[rootPath] ?? { state: "stopped" };
 }
 
+/** Text of each open document as last sent to the language servers, by file path */
+const syncedText: Record<string, string> = {};
+
+/** Position of a UTF-16 offset in a text, as the LSP counts it */
+function positionAt(text: string, offset: number): Position {
+  const before = text.slice(0, offset);
+  const lineStart = before.lastIndexOf("\n") + 1;
+  return {
+    line: before.split("\n").length - 1,
+    character: offset - lineStart,
+  };
+}
+
+function isSurrogate(code: number, low: boolean): boolean {
+  return low ? code >= 0xdc00 && code <= 0xdfff : code >= 0xd800 && code <= 0xdbff;
+}
+
+/**
+ * The change turning `oldText` into `newText`: a single replacement of the
+ * span where they differ, which for typing is a one-character edit.
+ */
+export function diffChange(oldText: string, newText: string): ContentChange {
+  const maxPrefix = Math.min(oldText.length, newText.length);
+  let start = 0;
+  while (start < maxPrefix && oldText[start] === newText[start]) {
+    start++;
+  }
+  // Don't split a surrogate pair
+  if (start > 0 && isSurrogate(oldText.charCodeAt(start - 1), false)) {
+    start--;
+  }
+
+  let oldEnd = oldText.length;
+  let newEnd = newText.length;
+  while (oldEnd > start && newEnd > start && oldText[oldEnd - 1] === newText[newEnd - 1]) {
+    oldEnd--;
+    newEnd--;
+  }
+  if (oldEnd < oldText.length && isSurrogate(oldText.charCodeAt(oldEnd), true)) {
+    oldEnd++;
+    newEnd++;
+  }
+
+  return {
+    range: { start: positionAt(oldText, start), end: positionAt(oldText, oldEnd) },
+    text: newText.slice(start, newEnd),
+  };
+}
+
 /**
  * Open a document and notify the language server.
  */
@@ -156,6 +363,7 @@ export async function openDocument(
 
   try {
     await lspOpenDocument(rootPath, filePath, content);
+    syncedText[filePath] = content;
     setStore("documentVersions", filePath, 1);
   } catch (e) {
     console.error("LSP: Failed to open document:", e);
@@ -163,7 +371,8 @@ export async function openDocument(
 }
 
 /**
- * Update a document and notify the language server.
+ * Update a document and notify the language server. Only the changed span
+ * is sent; the backend sends servers the whole document if they need it.
  */
 export async function changeDocument(
   rootPath: string,
@@ -175,11 +384,33 @@ export async function changeDocument(
     return;
   }
 
+  const previous = syncedText[filePath];
+  if (previous === content) {
+    return;
+  }
+  // Updated before sending, so the next change is diffed against this one
+  syncedText[filePath] = content;
+  const expectedVersion = (store.documentVersions[filePath] ?? 1) + 1;
+  setStore("documentVersions", filePath, expectedVersion);
+
   try {
-    await lspChangeDocument(rootPath, filePath, content);
-    const currentVersion = store.documentVersions[filePath] ?? 0;
-    setStore("documentVersions", filePath, currentVersion + 1);
+    let version: number;
+    if (previous === undefined) {
+      version = await lspChangeDocument(rootPath, filePath, null, [{ text: content }]);
+    } else {
+      try {
+        version = await lspChangeDocument(rootPath, filePath, expectedVersion, [
+          diffChange(previous, content),
+        ]);
+      } catch (e) {
+        // Out of step with the backend's copy: resend the whole document
+        console.warn("LSP: Resyncing document:", e);
+        version = await lspChangeDocument(rootPath, filePath, null, [{ text: content }]);
+      }
+    }
+    setStore("documentVersions", filePath, version);
   } catch (e) {
+    delete syncedText[filePath];
     console.error("LSP: Failed to change document:", e);
   }
 }
@@ -198,6 +429,7 @@ export async function closeDocument(
 
   try {
     await lspCloseDocument(rootPath, filePath);
+    delete syncedText[filePath];
     setStore("documentVersions", filePath, undefined as unknown as number);
     setStore("diagnostics", filePath, undefined as unknown as Diagnostic[]);
   } catch (e) {
@@ -376,6 +608,7 @@ export async function codeActions(
  */
 export async function resolveCodeAction(
   rootPath: string,
+  filePath: string,
   codeAction: CodeActionForResolve
 ): Promise<CodeAction | null> {
   const serverStatus = store.servers[rootPath];
@@ -384,9 +617,366 @@ export async function resolveCodeAction(
   }
 
   try {
-    return await lspResolveCodeAction(rootPath, codeAction);
+    return await lspResolveCodeAction(rootPath, filePath, codeAction);
   } catch (e) {
     console.error("LSP: resolveCodeAction failed:", e);
     return null;
   }
 }
+
+/**
+ * Check the symbol at a position can be renamed.
+ */
+export async function prepareRename(
+  rootPath: string,
+  filePath: string,
+  line: number,
+  character: number
+): Promise<PrepareRenameResult | null> {
+  const serverStatus = store.servers[rootPath];
+  if (serverStatus?.state !== "running") {
+    return null;
+  }
+
+  try {
+    return await lspPrepareRename(rootPath, filePath, line, character);
+  } catch (e) {
+    console.error("LSP: prepareRename failed:", e);
+    return null;
+  }
+}
+
+/**
+ * Get the edit renaming the symbol at a position.
+ */
+export async function rename(
+  rootPath: string,
+  filePath: string,
+  line: number,
+  character: number,
+  newName: string
+): Promise<WorkspaceEdit | null> {
+  const serverStatus = store.servers[rootPath];
+  if (serverStatus?.state !== "running") {
+    return null;
+  }
+
+  try {
+    return await lspRename(rootPath, filePath, line, character, newName);
+  } catch (e) {
+    console.error("LSP: rename failed:", e);
+    return null;
+  }
+}
+
+/**
+ * Get a document's symbols as an outline tree.
+ */
+export async function documentSymbols(
+  rootPath: string,
+  filePath: string
+): Promise<SymbolItem[]> {
+  const serverStatus = store.servers[rootPath];
+  if (serverStatus?.state !== "running") {
+    return [];
+  }
+
+  try {
+    return await lspDocumentSymbols(rootPath, filePath);
+  } catch (e) {
+    console.error("LSP: documentSymbols failed:", e);
+    return [];
+  }
+}
+
+/**
+ * Search a project's symbols by name.
+ */
+export async function workspaceSymbols(
+  rootPath: string,
+  query: string
+): Promise<SymbolItem[]> {
+  const serverStatus = store.servers[rootPath];
+  if (serverStatus?.state !== "running") {
+    return [];
+  }
+
+  try {
+    return await lspWorkspaceSymbols(rootPath, query);
+  } catch (e) {
+    console.error("LSP: workspaceSymbols failed:", e);
+    return [];
+  }
+}
+
+/**
+ * Format a document. Returns the edits to apply to it.
+ */
+export async function formatDocument(
+  rootPath: string,
+  filePath: string,
+  options: FormattingOptions
+): Promise<TextEdit[]> {
+  const serverStatus = store.servers[rootPath];
+  if (serverStatus?.state !== "running") {
+    return [];
+  }
+
+  try {
+    return await lspFormatDocument(rootPath, filePath, options);
+  } catch (e) {
+    console.error("LSP: formatDocument failed:", e);
+    return [];
+  }
+}
+
+/**
+ * Format part of a document. Returns the edits to apply to it.
+ */
+export async function formatRange(
+  rootPath: string,
+  filePath: string,
+  range: Range,
+  options: FormattingOptions
+): Promise<TextEdit[]> {
+  const serverStatus = store.servers[rootPath];
+  if (serverStatus?.state !== "running") {
+    return [];
+  }
+
+  try {
+    return await lspFormatRange(rootPath, filePath, range, options);
+  } catch (e) {
+    console.error("LSP: formatRange failed:", e);
+    return [];
+  }
+}
+
+/**
+ * Get the edits to apply to a document before it's saved. Failures return no
+ * edits, so they never stop a save.
+ */
+export async function willSave(
+  rootPath: string,
+  filePath: string,
+  options: FormattingOptions
+): Promise<TextEdit[]> {
+  const serverStatus = store.servers[rootPath];
+  if (serverStatus?.state !== "running") {
+    return [];
+  }
+
+  try {
+    return await lspWillSave(rootPath, filePath, SaveReason.Manual, options);
+  } catch (e) {
+    console.warn("LSP: willSave failed:", e);
+    return [];
+  }
+}
+
+/**
+ * Get signature help for the call at a position.
+ */
+export async function signatureHelp(
+  rootPath: string,
+  filePath: string,
+  line: number,
+  character: number,
+  context?: SignatureHelpContext
+): Promise<SignatureHelp | null> {
+  const serverStatus = store.servers[rootPath];
+  if (serverStatus?.state !== "running") {
+    return null;
+  }
+
+  try {
+    return await lspSignatureHelp(rootPath, filePath, line, character, context);
+  } catch (e) {
+    console.error("LSP: signatureHelp failed:", e);
+    return null;
+  }
+}
+
+/**
+ * Get the inlay hints in a range of a document.
+ */
+export async function inlayHints(
+  rootPath: string,
+  filePath: string,
+  range: Range
+): Promise<InlayHint[]> {
+  const serverStatus = store.servers[rootPath];
+  if (serverStatus?.state !== "running") {
+    return [];
+  }
+
+  try {
+    return await lspInlayHints(rootPath, filePath, range);
+  } catch (e) {
+    console.error("LSP: inlayHints failed:", e);
+    return [];
+  }
+}
+
+/**
+ * Fill in an inlay hint's tooltip. Returns the hint unchanged on failure.
+ */
+export async function resolveInlayHint(
+  rootPath: string,
+  filePath: string,
+  hint: InlayHint
+): Promise<InlayHint> {
+  try {
+    return await lspResolveInlayHint(rootPath, filePath, hint);
+  } catch (e) {
+    console.error("LSP: resolveInlayHint failed:", e);
+    return hint;
+  }
+}
+
+/**
+ * Get the semantic tokens for a document, or for a range of it.
+ */
+export async function semanticTokens(
+  rootPath: string,
+  filePath: string,
+  range?: Range
+): Promise<SemanticToken[]> {
+  const serverStatus = store.servers[rootPath];
+  if (serverStatus?.state !== "running") {
+    return [];
+  }
+
+  try {
+    return range
+      ? await lspSemanticTokensRange(rootPath, filePath, range)
+      : await lspSemanticTokens(rootPath, filePath);
+  } catch (e) {
+    console.error("LSP: semanticTokens failed:", e);
+    return [];
+  }
+}
+
+/**
+ * Get the call hierarchy items at a position.
+ */
+export async function prepareCallHierarchy(
+  rootPath: string,
+  filePath: string,
+  line: number,
+  character: number
+): Promise<HierarchyItem[]> {
+  const serverStatus = store.servers[rootPath];
+  if (serverStatus?.state !== "running") {
+    return [];
+  }
+
+  try {
+    return await lspPrepareCallHierarchy(rootPath, filePath, line, character);
+  } catch (e) {
+    console.error("LSP: prepareCallHierarchy failed:", e);
+    return [];
+  }
+}
+
+/**
+ * Find the callers of a call hierarchy item.
+ */
+export async function incomingCalls(
+  rootPath: string,
+  item: HierarchyItem
+): Promise<HierarchyCall[]> {
+  const serverStatus = store.servers[rootPath];
+  if (serverStatus?.state !== "running") {
+    return [];
+  }
+
+  try {
+    return await lspIncomingCalls(rootPath, item);
+  } catch (e) {
+    console.error("LSP: incomingCalls failed:", e);
+    return [];
+  }
+}
+
+/**
+ * Find what a call hierarchy item calls.
+ */
+export async function outgoingCalls(
+  rootPath: string,
+  item: HierarchyItem
+): Promise<HierarchyCall[]> {
+  const serverStatus = store.servers[rootPath];
+  if (serverStatus?.state !== "running") {
+    return [];
+  }
+
+  try {
+    return await lspOutgoingCalls(rootPath, item);
+  } catch (e) {
+    console.error("LSP: outgoingCalls failed:", e);
+    return [];
+  }
+}
+
+/**
+ * Get the type hierarchy items at a position.
+ */
+export async function prepareTypeHierarchy(
+  rootPath: string,
+  filePath: string,
+  line: number,
+  character: number
+): Promise<HierarchyItem[]> {
+  const serverStatus = store.servers[rootPath];
+  if (serverStatus?.state !== "running") {
+    return [];
+  }
+
+  try {
+    return await lspPrepareTypeHierarchy(rootPath, filePath, line, character);
+  } catch (e) {
+    console.error("LSP: prepareTypeHierarchy failed:", e);
+    return [];
+  }
+}
+
+/**
+ * Find the supertypes of a type hierarchy item.
+ */
+export async function supertypes(
+  rootPath: string,
+  item: HierarchyItem
+): Promise<HierarchyItem[]> {
+  const serverStatus = store.servers[rootPath];
+  if (serverStatus?.state !== "running") {
+    return [];
+  }
+
+  try {
+    return await lspSupertypes(rootPath, item);
+  } catch (e) {
+    console.error("LSP: supertypes failed:", e);
+    return [];
+  }
+}
+
+/**
+ * Find the subtypes of a type hierarchy item.
+ */
+export async function subtypes(
+  rootPath: string,
+  item: HierarchyItem
+): Promise<HierarchyItem[]> {
+  const serverStatus = store.servers[rootPath];
+  if (serverStatus?.state !== "running") {
+    return [];
+  }
+
+  try {
+    return await lspSubtypes(rootPath, item);
+  } catch (e) {
+    console.error("LSP: subtypes failed:", e);
+    return [];
+  }
+}
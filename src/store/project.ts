@@ -1,4 +1,5 @@
 import { createSignal } from "solid-js";
+import { invoke } from "@tauri-apps/api/core";
 import { createStore } from "solid-js/store";
 import { loadAppState, saveAppState, SerializedWorkspace, AppState } from "./session";
 import { restoreWorkspace, getSerializedWorkspace, setWorkspaceChangeCallback } from "./surface";
@@ -124,6 +125,23 @@ async function switchToProject(path: string): Promise<void> {
   
   setCurrentPath(path);
   
+  // File commands only accept paths in allowed roots. Projects picked in this
+  // session already are; the user is asked once about any others.
+  try {
+    if (!(await invoke<boolean>("allow_path", { path }))) {
+      console.error("Access to project was not allowed:", path);
+    }
+  } catch (e) {
+    console.error("Failed to allow project:", e);
+  }
+  
+  // Watch the project so open files and the file finder see changes on disk
+  try {
+    await invoke("watch_project", { root: path });
+  } catch (e) {
+    console.error("Failed to watch project:", e);
+  }
+  
   // Restore workspace for new project
   const cached = workspaceCache[path];
   if (cached) {
@@ -144,6 +162,8 @@ export async function setCurrentProject(path: string): Promise<void> {
 export async function removeProject(path: string): Promise<void> {
   setProjects(projects.filter(p => p.path !== path));
   delete workspaceCache[path];
+  await invoke("unwatch_project", { root: path });
+  await invoke("revoke_path", { path });
   
   if (currentPath() === path) {
     const nextPath = projects[0]?.path ?? null;
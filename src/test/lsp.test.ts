@@ -1,5 +1,7 @@
 import { describe, it, expect, vi, beforeEach } from "vitest";
 import { uriToPath, pathToUri, DiagnosticSeverity, CompletionItemKind } from "../lsp/types";
+import type { ContentChange, Position } from "../lsp/types";
+import type { BufferEditor } from "../store/lsp";
 
 describe("LSP Types", () => {
   describe("uriToPath", () => {
@@ -18,6 +20,16 @@ describe("LSP Types", () => {
         "/C:/Users/test/file.rs"
       );
     });
+
+    it("decodes percent escapes", () => {
+      expect(uriToPath("file:///p/my%20src/caf%C3%A9%25.rs")).toBe(
+        "/p/my src/café%.rs"
+      );
+    });
+
+    it("keeps escapes that aren't UTF-8", () => {
+      expect(uriToPath("file:///p/%FF/100%")).toBe("/p/%FF/100%");
+    });
   });
 
   describe("pathToUri", () => {
@@ -27,6 +39,17 @@ describe("LSP Types", () => {
       );
     });
 
+    it("encodes what the backend encodes", () => {
+      expect(pathToUri("/p/my src/café%.rs")).toBe(
+        "file:///p/my%20src/caf%C3%A9%25.rs"
+      );
+    });
+
+    it("round-trips through uriToPath", () => {
+      const path = "/p/[a] #1?/~x-y_z.rs";
+      expect(uriToPath(pathToUri(path))).toBe(path);
+    });
+
     it("returns URI unchanged if already a file:// URI", () => {
       expect(pathToUri("file:///already/a/uri")).toBe("file:///already/a/uri");
     });
@@ -78,4 +101,125 @@ describe("LSP Store", () => {
       expect(result.state).toBe("stopped");
     });
   });
+
+  describe("diffChange", () => {
+    // Apply a change the way a server would, with UTF-16 positions
+    function applyChange(text: string, change: ContentChange): string {
+      const offset = (pos: Position) =>
+        text
+          .split("\n")
+          .slice(0, pos.line)
+          .reduce((n, line) => n + line.length + 1, 0) + pos.character;
+      const { start, end } = change.range!;
+      return text.slice(0, offset(start)) + change.text + text.slice(offset(end));
+    }
+
+    it("sends typing as a one-character edit", async () => {
+      const { diffChange } = await import("../store/lsp");
+      const change = diffChange("fn main() {\n}\n", "fn main() {\n    x\n}\n");
+      expect(change).toEqual({
+        range: { start: { line: 1, character: 0 }, end: { line: 1, character: 0 } },
+        text: "    x\n",
+      });
+    });
+
+    it("describes deletions and replacements across lines", async () => {
+      const { diffChange } = await import("../store/lsp");
+      const cases: [string, string][] = [
+        ["let a = 1;\nlet b = 2;\n", "let a = 1;\n"],
+        ["one\ntwo\nthree", "one\n2\nthree"],
+        ["aaa", "aaaa"],
+        ["", "new file\n"],
+        ["same", "same"],
+      ];
+      for (const [oldText, newText] of cases) {
+        expect(applyChange(oldText, diffChange(oldText, newText))).toBe(newText);
+      }
+      expect(diffChange("let a = 1;\nlet b = 2;\n", "let a = 1;\n").range).toEqual({
+        start: { line: 1, character: 0 },
+        end: { line: 2, character: 0 },
+      });
+    });
+
+    it("doesn't split surrogate pairs", async () => {
+      const { diffChange } = await import("../store/lsp");
+      // 😀 and 😃 share their high surrogate, 🈀 and 😀 their low one
+      const cases: [string, string, string][] = [
+        ["a😀b", "a😃b", "😃"],
+        ["x\u{1F200}", "x\u{1F600}", "\u{1F600}"],
+        ["😀", "", ""],
+      ];
+      for (const [oldText, newText, inserted] of cases) {
+        const change = diffChange(oldText, newText);
+        expect(change.text).toBe(inserted);
+        expect(applyChange(oldText, change)).toBe(newText);
+      }
+    });
+  });
+
+  describe("applyToBuffers", () => {
+    // An editor recording what it was asked to do
+    function editor(fits = true) {
+      const log: string[] = [];
+      const buffer: BufferEditor = {
+        applyEdits: (edits) => {
+          log.push(`edit ${edits.map((e) => e.newText).join(",")}`);
+          return fits;
+        },
+        moved: (to) => log.push(`moved ${to}`),
+        deleted: () => log.push("deleted"),
+      };
+      return { buffer, log };
+    }
+
+    function edits(path: string, ...newTexts: string[]) {
+      const range = { start: { line: 0, character: 0 }, end: { line: 0, character: 0 } };
+      return { uri: `file://${path}`, edits: newTexts.map((newText) => ({ range, newText })) };
+    }
+
+    it("follows moves and applies each change in order", async () => {
+      const { applyToBuffers } = await import("../store/lsp");
+      const moved = editor();
+      const other = editor();
+      const failures = applyToBuffers(
+        {
+          bufferEdits: {
+            documentChanges: [edits("/p/lib/a.rs", "1"), edits("/p/b.rs", "2"), edits("/p/lib/a.rs", "3", "4")],
+          },
+          moved: [{ from: "/p/src", to: "/p/lib" }],
+          deleted: [],
+          movedBuffers: [{ from: "/p/src/a.rs", to: "/p/lib/a.rs" }],
+          closedBuffers: [],
+        },
+        new Map([
+          ["/p/src/a.rs", moved.buffer],
+          ["/p/b.rs", other.buffer],
+        ])
+      );
+      expect(failures).toEqual([]);
+      expect(moved.log).toEqual(["moved /p/lib/a.rs", "edit 1", "edit 3,4"]);
+      expect(other.log).toEqual(["edit 2"]);
+    });
+
+    it("closes deleted buffers and reports edits that weren't applied", async () => {
+      const { applyToBuffers } = await import("../store/lsp");
+      const deleted = editor();
+      const stale = editor(false);
+      const failures = applyToBuffers(
+        {
+          bufferEdits: { documentChanges: [edits("/p/a.rs", "1"), edits("/p/b.rs", "2")] },
+          moved: [],
+          deleted: [],
+          movedBuffers: [],
+          closedBuffers: ["/p/a.rs"],
+        },
+        new Map([
+          ["/p/a.rs", deleted.buffer],
+          ["/p/b.rs", stale.buffer],
+        ])
+      );
+      expect(deleted.log).toEqual(["deleted"]);
+      expect(failures).toHaveLength(2);
+    });
+  });
 });